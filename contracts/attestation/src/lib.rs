@@ -0,0 +1,184 @@
+#![no_std]
+
+//! KYC attestation registry for Tikka raffles.
+//!
+//! The admin approves attestors (KYC providers); an approved attestor marks
+//! addresses as verified until an expiry and may revoke them again.  The
+//! factory records this contract with `set_kyc_registry`, and raffles whose
+//! `Compliance` sets `kyc_required` only sell to verified addresses.
+//! `is_eligible` lets the registry also serve directly as a raffle's
+//! attestation hook.
+
+use soroban_sdk::{
+    contract, contracterror, contractevent, contractimpl, contracttype, Address, Env,
+};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    /// `true` for each approved attestor.
+    Attestor(Address),
+    /// `Attestation` of an address.
+    Attestation(Address),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Attestation {
+    pub attestor: Address,
+    /// Ledger timestamp after which the verification lapses.
+    pub expires_at: u64,
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    NotAttestor = 3,
+    InvalidExpiry = 4,
+    NotAttested = 5,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["attestation", "attestor_updated"])]
+pub struct AttestorUpdated {
+    #[topic]
+    pub attestor: Address,
+    pub approved: bool,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["attestation", "attested"])]
+pub struct Attested {
+    #[topic]
+    pub subject: Address,
+    pub attestor: Address,
+    pub expires_at: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["attestation", "revoked"])]
+pub struct Revoked {
+    #[topic]
+    pub subject: Address,
+    pub revoked_by: Address,
+}
+
+fn admin(env: &Env) -> Result<Address, Error> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(Error::NotInitialized)
+}
+
+fn require_attestor(env: &Env, attestor: &Address) -> Result<(), Error> {
+    attestor.require_auth();
+    if !AttestationRegistry::is_attestor(env.clone(), attestor.clone()) {
+        return Err(Error::NotAttestor);
+    }
+    Ok(())
+}
+
+#[contract]
+pub struct AttestationRegistry;
+
+#[contractimpl]
+impl AttestationRegistry {
+    pub fn init(env: Env, admin: Address) -> Result<(), Error> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(Error::AlreadyInitialized);
+        }
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        Ok(())
+    }
+
+    /// Admin-only.  Approves or removes an attestor.  Verifications an
+    /// attestor already issued stay valid until they expire or are revoked.
+    pub fn set_attestor(env: Env, attestor: Address, approved: bool) -> Result<(), Error> {
+        admin(&env)?.require_auth();
+        let key = DataKey::Attestor(attestor.clone());
+        if approved {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+        AttestorUpdated { attestor, approved }.publish(&env);
+        Ok(())
+    }
+
+    pub fn is_attestor(env: Env, attestor: Address) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Attestor(attestor))
+            .unwrap_or(false)
+    }
+
+    /// Marks `subject` as verified until `expires_at`, replacing any earlier
+    /// attestation.
+    pub fn attest(
+        env: Env,
+        attestor: Address,
+        subject: Address,
+        expires_at: u64,
+    ) -> Result<(), Error> {
+        require_attestor(&env, &attestor)?;
+        if expires_at <= env.ledger().timestamp() {
+            return Err(Error::InvalidExpiry);
+        }
+        env.storage().persistent().set(
+            &DataKey::Attestation(subject.clone()),
+            &Attestation {
+                attestor: attestor.clone(),
+                expires_at,
+            },
+        );
+        Attested {
+            subject,
+            attestor,
+            expires_at,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Withdraws `subject`'s verification.  Any approved attestor may
+    /// revoke, not only the one that issued it.
+    pub fn revoke(env: Env, attestor: Address, subject: Address) -> Result<(), Error> {
+        require_attestor(&env, &attestor)?;
+        let key = DataKey::Attestation(subject.clone());
+        if !env.storage().persistent().has(&key) {
+            return Err(Error::NotAttested);
+        }
+        env.storage().persistent().remove(&key);
+        Revoked {
+            subject,
+            revoked_by: attestor,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    pub fn attestation(env: Env, subject: Address) -> Option<Attestation> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Attestation(subject))
+    }
+
+    /// Whether `subject` holds an unexpired verification.
+    pub fn is_verified(env: Env, subject: Address) -> bool {
+        matches!(
+            Self::attestation(env.clone(), subject),
+            Some(attestation) if attestation.expires_at > env.ledger().timestamp()
+        )
+    }
+
+    /// Raffle attestation hook; same as `is_verified`.
+    pub fn is_eligible(env: Env, buyer: Address) -> bool {
+        Self::is_verified(env, buyer)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,42 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::testutils::{Address as _, Ledger};
+
+#[test]
+fn attestations_expire_and_can_be_revoked() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let client = AttestationRegistryClient::new(&env, &env.register(AttestationRegistry, ()));
+    let admin = Address::generate(&env);
+    let provider = Address::generate(&env);
+    let user = Address::generate(&env);
+    client.init(&admin);
+
+    assert_eq!(
+        client.try_attest(&provider, &user, &2_000),
+        Err(Ok(Error::NotAttestor))
+    );
+    client.set_attestor(&provider, &true);
+    assert_eq!(
+        client.try_attest(&provider, &user, &1_000),
+        Err(Ok(Error::InvalidExpiry))
+    );
+
+    client.attest(&provider, &user, &2_000);
+    assert!(client.is_verified(&user));
+    assert!(client.is_eligible(&user));
+
+    env.ledger().set_timestamp(2_000);
+    assert!(!client.is_verified(&user));
+
+    client.attest(&provider, &user, &3_000);
+    client.revoke(&provider, &user);
+    assert!(!client.is_verified(&user));
+    assert_eq!(
+        client.try_revoke(&provider, &user),
+        Err(Ok(Error::NotAttested))
+    );
+}
@@ -0,0 +1,283 @@
+#![no_std]
+
+//! Buyer insurance pool for Tikka raffles.
+//!
+//! Raffles send the slice of their protocol fees set by the factory's
+//! `InsuranceRoute` straight to this contract.  A buyer of a raffle shown to
+//! be fraudulent files a claim; an arbiter (or the admin) approves it, which
+//! pays the claimant from the pool, or rejects it.  The pool's reserves in a
+//! token are simply its balance of that token.
+
+use soroban_sdk::{
+    contract, contracterror, contractevent, contractimpl, contracttype, token, Address, Env, String,
+};
+
+/// Maximum byte-length of a claim's evidence string.
+const MAX_EVIDENCE_LENGTH: u32 = 1_000;
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    /// `true` for each approved arbiter.
+    Arbiter(Address),
+    ClaimCounter,
+    Claim(u32),
+    /// Total paid out in a token, across claims.
+    TotalPaid(Address),
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ClaimStatus {
+    Pending,
+    Paid,
+    Rejected,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Claim {
+    pub claimant: Address,
+    /// Raffle instance the claim is about.
+    pub raffle: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub evidence: String,
+    pub status: ClaimStatus,
+    pub filed_at: u64,
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    NotArbiter = 3,
+    InvalidAmount = 4,
+    ClaimNotFound = 5,
+    ClaimNotPending = 6,
+    InsufficientReserves = 7,
+    InvalidParameters = 8,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["insurance", "arbiter_updated"])]
+pub struct ArbiterUpdated {
+    #[topic]
+    pub arbiter: Address,
+    pub approved: bool,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["insurance", "claim_filed"])]
+pub struct ClaimFiled {
+    #[topic]
+    pub claim_id: u32,
+    pub claimant: Address,
+    pub raffle: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["insurance", "claim_paid"])]
+pub struct ClaimPaid {
+    #[topic]
+    pub claim_id: u32,
+    pub claimant: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub approved_by: Address,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["insurance", "claim_rejected"])]
+pub struct ClaimRejected {
+    #[topic]
+    pub claim_id: u32,
+    pub rejected_by: Address,
+}
+
+fn admin(env: &Env) -> Result<Address, Error> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(Error::NotInitialized)
+}
+
+/// Admin or an approved arbiter.
+fn require_arbiter(env: &Env, arbiter: &Address) -> Result<(), Error> {
+    arbiter.require_auth();
+    if *arbiter != admin(env)? && !InsurancePool::is_arbiter(env.clone(), arbiter.clone()) {
+        return Err(Error::NotArbiter);
+    }
+    Ok(())
+}
+
+/// Loads claim `claim_id`, which must still be pending.
+fn pending_claim(env: &Env, claim_id: u32) -> Result<Claim, Error> {
+    let claim: Claim = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Claim(claim_id))
+        .ok_or(Error::ClaimNotFound)?;
+    if claim.status != ClaimStatus::Pending {
+        return Err(Error::ClaimNotPending);
+    }
+    Ok(claim)
+}
+
+#[contract]
+pub struct InsurancePool;
+
+#[contractimpl]
+impl InsurancePool {
+    pub fn init(env: Env, admin: Address) -> Result<(), Error> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(Error::AlreadyInitialized);
+        }
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        Ok(())
+    }
+
+    /// Admin-only.  Approves or removes an arbiter.
+    pub fn set_arbiter(env: Env, arbiter: Address, approved: bool) -> Result<(), Error> {
+        admin(&env)?.require_auth();
+        let key = DataKey::Arbiter(arbiter.clone());
+        if approved {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+        ArbiterUpdated { arbiter, approved }.publish(&env);
+        Ok(())
+    }
+
+    pub fn is_arbiter(env: Env, arbiter: Address) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Arbiter(arbiter))
+            .unwrap_or(false)
+    }
+
+    /// Files a claim for `amount` of `token` lost to `raffle`.  Returns the
+    /// claim ID.
+    pub fn file_claim(
+        env: Env,
+        claimant: Address,
+        raffle: Address,
+        token: Address,
+        amount: i128,
+        evidence: String,
+    ) -> Result<u32, Error> {
+        claimant.require_auth();
+        admin(&env)?;
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if evidence.len() > MAX_EVIDENCE_LENGTH {
+            return Err(Error::InvalidParameters);
+        }
+        let claim_id: u32 = env
+            .storage()
+            .instance()
+            .get::<_, u32>(&DataKey::ClaimCounter)
+            .unwrap_or(0)
+            + 1;
+        env.storage()
+            .instance()
+            .set(&DataKey::ClaimCounter, &claim_id);
+        env.storage().persistent().set(
+            &DataKey::Claim(claim_id),
+            &Claim {
+                claimant: claimant.clone(),
+                raffle: raffle.clone(),
+                token: token.clone(),
+                amount,
+                evidence,
+                status: ClaimStatus::Pending,
+                filed_at: env.ledger().timestamp(),
+            },
+        );
+
+        ClaimFiled {
+            claim_id,
+            claimant,
+            raffle,
+            token,
+            amount,
+        }
+        .publish(&env);
+        Ok(claim_id)
+    }
+
+    /// Approves a pending claim and pays it from the pool.
+    pub fn approve_claim(env: Env, arbiter: Address, claim_id: u32) -> Result<(), Error> {
+        require_arbiter(&env, &arbiter)?;
+        let mut claim = pending_claim(&env, claim_id)?;
+        if claim.amount > Self::reserves(env.clone(), claim.token.clone()) {
+            return Err(Error::InsufficientReserves);
+        }
+
+        claim.status = ClaimStatus::Paid;
+        env.storage()
+            .persistent()
+            .set(&DataKey::Claim(claim_id), &claim);
+        let paid_key = DataKey::TotalPaid(claim.token.clone());
+        let paid: i128 = env.storage().persistent().get(&paid_key).unwrap_or(0);
+        env.storage()
+            .persistent()
+            .set(&paid_key, &(paid + claim.amount));
+        token::Client::new(&env, &claim.token).transfer(
+            &env.current_contract_address(),
+            &claim.claimant,
+            &claim.amount,
+        );
+
+        ClaimPaid {
+            claim_id,
+            claimant: claim.claimant,
+            token: claim.token,
+            amount: claim.amount,
+            approved_by: arbiter,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    pub fn reject_claim(env: Env, arbiter: Address, claim_id: u32) -> Result<(), Error> {
+        require_arbiter(&env, &arbiter)?;
+        let mut claim = pending_claim(&env, claim_id)?;
+        claim.status = ClaimStatus::Rejected;
+        env.storage()
+            .persistent()
+            .set(&DataKey::Claim(claim_id), &claim);
+
+        ClaimRejected {
+            claim_id,
+            rejected_by: arbiter,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    pub fn get_claim(env: Env, claim_id: u32) -> Option<Claim> {
+        env.storage().persistent().get(&DataKey::Claim(claim_id))
+    }
+
+    /// Pool reserves in `token`.
+    pub fn reserves(env: Env, token: Address) -> i128 {
+        token::Client::new(&env, &token).balance(&env.current_contract_address())
+    }
+
+    pub fn total_paid(env: Env, token: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TotalPaid(token))
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod test;
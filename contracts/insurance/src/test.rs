@@ -0,0 +1,54 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::token::StellarAssetClient;
+
+#[test]
+fn arbiters_pay_or_reject_claims_from_the_pool() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let pool_id = env.register(InsurancePool, ());
+    let client = InsurancePoolClient::new(&env, &pool_id);
+    let admin = Address::generate(&env);
+    let arbiter = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let raffle = Address::generate(&env);
+    let token = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    client.init(&admin);
+    client.set_arbiter(&arbiter, &true);
+    // Fee slices arrive as plain transfers.
+    StellarAssetClient::new(&env, &token).mint(&pool_id, &500);
+
+    let evidence = String::from_str(&env, "creator never deposited the prize");
+    let too_big = client.file_claim(&buyer, &raffle, &token, &600, &evidence);
+    assert_eq!(
+        client.try_approve_claim(&arbiter, &too_big),
+        Err(Ok(Error::InsufficientReserves))
+    );
+    client.reject_claim(&arbiter, &too_big);
+    assert_eq!(
+        client.get_claim(&too_big).unwrap().status,
+        ClaimStatus::Rejected
+    );
+
+    let claim_id = client.file_claim(&buyer, &raffle, &token, &200, &evidence);
+    assert_eq!(
+        client.try_approve_claim(&buyer, &claim_id),
+        Err(Ok(Error::NotArbiter))
+    );
+    client.approve_claim(&arbiter, &claim_id);
+    assert_eq!(
+        client.try_approve_claim(&admin, &claim_id),
+        Err(Ok(Error::ClaimNotPending))
+    );
+    assert_eq!(client.reserves(&token), 300);
+    assert_eq!(client.total_paid(&token), 200);
+    assert_eq!(
+        soroban_sdk::token::Client::new(&env, &token).balance(&buyer),
+        200
+    );
+}
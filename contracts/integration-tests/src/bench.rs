@@ -0,0 +1,167 @@
+//! Cost regression checks.
+//!
+//! Each check meters one call with the host budget and fails when its CPU
+//! instructions or memory exceed the ceiling listed next to it.  The
+//! ceilings sit well inside the per-transaction network limits; a change
+//! that pushes a call past one has made it meaningfully more expensive and
+//! should either be reworked or raise the ceiling in the same PR with the
+//! new figures in its description.
+//!
+//! Costs are printed for every check; `cargo test -p integration-tests
+//! bench -- --nocapture` lists them.
+
+use soroban_sdk::{Address, Env};
+
+use crate::setup::{deploy_protocol, funded_user, raffle_config, Protocol, TICKET_PRICE};
+use crate::{factory, instance};
+
+/// Per-transaction network limits at the time of writing.
+const TX_MAX_CPU_INSTRUCTIONS: u64 = 100_000_000;
+const TX_MAX_MEMORY_BYTES: u64 = 41_943_040;
+
+struct Ceiling {
+    cpu: u64,
+    mem: u64,
+}
+
+const fn ceiling(cpu: u64, mem: u64) -> Ceiling {
+    assert!(cpu <= TX_MAX_CPU_INSTRUCTIONS && mem <= TX_MAX_MEMORY_BYTES);
+    Ceiling { cpu, mem }
+}
+
+/// `buy_tickets` by quantity.
+const BUY_TICKETS: [(u32, Ceiling); 3] = [
+    (1, ceiling(20_000_000, 6_000_000)),
+    (10, ceiling(30_000_000, 8_000_000)),
+    (25, ceiling(45_000_000, 12_000_000)),
+];
+
+/// `finalize_raffle` by tickets sold.
+const FINALIZE_RAFFLE: [(u32, Ceiling); 3] = [
+    (10, ceiling(25_000_000, 8_000_000)),
+    (100, ceiling(35_000_000, 12_000_000)),
+    (500, ceiling(60_000_000, 24_000_000)),
+];
+
+/// Instance `get_participants` by page size, over 200 participants.
+const GET_PARTICIPANTS: [(u32, Ceiling); 3] = [
+    (10, ceiling(5_000_000, 2_000_000)),
+    (100, ceiling(15_000_000, 6_000_000)),
+    (200, ceiling(25_000_000, 10_000_000)),
+];
+
+/// Factory `get_all_raffles` by page size, over 20 raffles.
+const GET_ALL_RAFFLES: [(u32, Ceiling); 2] = [
+    (5, ceiling(10_000_000, 4_000_000)),
+    (20, ceiling(30_000_000, 10_000_000)),
+];
+
+/// Runs `call` against a fresh, unlimited budget and checks what it used
+/// against `limit`.
+fn measure<T>(env: &Env, label: &str, limit: &Ceiling, call: impl FnOnce() -> T) -> T {
+    let mut budget = env.cost_estimate().budget();
+    budget.reset_unlimited();
+    let out = call();
+    let cpu = budget.cpu_instruction_cost();
+    let mem = budget.memory_bytes_cost();
+    assert!(
+        cpu <= limit.cpu,
+        "{label} used {cpu} CPU instructions, ceiling is {}",
+        limit.cpu
+    );
+    assert!(
+        mem <= limit.mem,
+        "{label} used {mem} bytes of memory, ceiling is {}",
+        limit.mem
+    );
+    out
+}
+
+/// Deploys an active raffle of `max_tickets` through the factory.
+fn active_raffle<'a>(env: &'a Env, protocol: &Protocol, max_tickets: u32) -> instance::Client<'a> {
+    let creator = funded_user(env, protocol, TICKET_PRICE * 1_000);
+    let mut config = raffle_config(env, &protocol.token, "Cost check");
+    config.max_tickets = max_tickets;
+    config.max_tickets_per_tx = max_tickets;
+    let raffle = instance::Client::new(env, &protocol.factory.create_raffle(&creator, &config));
+    raffle.deposit_prize();
+    raffle
+}
+
+#[test]
+fn buy_tickets_stays_under_its_ceilings() {
+    let env = Env::default();
+    let protocol = deploy_protocol(&env);
+
+    for (quantity, limit) in BUY_TICKETS.iter() {
+        env.cost_estimate().budget().reset_unlimited();
+        let raffle = active_raffle(&env, &protocol, *quantity);
+        let buyer = funded_user(&env, &protocol, TICKET_PRICE * *quantity as i128);
+
+        let label = format!("buy_tickets({quantity})");
+        measure(&env, &label, limit, || {
+            raffle.buy_tickets(&buyer, quantity, &0)
+        });
+    }
+}
+
+#[test]
+fn finalize_raffle_stays_under_its_ceilings() {
+    let env = Env::default();
+    let protocol = deploy_protocol(&env);
+
+    for (tickets, limit) in FINALIZE_RAFFLE.iter() {
+        env.cost_estimate().budget().reset_unlimited();
+        let raffle = active_raffle(&env, &protocol, *tickets);
+        let buyer = funded_user(&env, &protocol, TICKET_PRICE * *tickets as i128);
+        // Selling out closes the raffle without waiting for `end_time`.
+        raffle.buy_tickets(&buyer, tickets, &0);
+
+        let label = format!("finalize_raffle({tickets} tickets)");
+        measure(&env, &label, limit, || raffle.finalize_raffle());
+        assert_eq!(
+            raffle.get_raffle().status,
+            instance::RaffleStatus::Finalized
+        );
+    }
+}
+
+#[test]
+fn paginated_queries_stay_under_their_ceilings() {
+    let env = Env::default();
+    let protocol = deploy_protocol(&env);
+    env.cost_estimate().budget().reset_unlimited();
+
+    let raffle = active_raffle(&env, &protocol, 200);
+    for _ in 0..200 {
+        let buyer: Address = funded_user(&env, &protocol, TICKET_PRICE);
+        raffle.buy_tickets(&buyer, &1, &0);
+    }
+    for (limit_items, limit) in GET_PARTICIPANTS.iter() {
+        let label = format!("get_participants(limit {limit_items})");
+        let page = measure(&env, &label, limit, || {
+            raffle.get_participants(&instance::PaginationParams {
+                limit: *limit_items,
+                offset: 0,
+            })
+        });
+        assert_eq!(page.items.len(), *limit_items);
+    }
+
+    for _ in 1..20 {
+        env.cost_estimate().budget().reset_unlimited();
+        active_raffle(&env, &protocol, 10);
+    }
+    for (limit_items, limit) in GET_ALL_RAFFLES.iter() {
+        let label = format!("get_all_raffles(limit {limit_items})");
+        let page = measure(&env, &label, limit, || {
+            protocol
+                .factory
+                .get_all_raffles(&factory::PaginationParams {
+                    limit: *limit_items,
+                    offset: 0,
+                })
+        });
+        assert_eq!(page.items.len(), *limit_items);
+    }
+}
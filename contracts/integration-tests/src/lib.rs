@@ -0,0 +1,33 @@
+//! End-to-end tests of the factory and raffle instance as deployed WASM.
+//!
+//! The unit tests of each contract run it natively and stub the other side
+//! (the factory registers instances with `env.register`; instances talk to a
+//! `MockFactory`).  Here the factory is registered from its release WASM and
+//! deploys real instance WASM through `deploy_v2`, so every call between
+//! them crosses a contract boundary exactly as on chain.  That also makes
+//! the budget metered here the real guest cost, which `bench` holds under
+//! fixed ceilings.
+//!
+//! Build the contracts first:
+//!
+//! ```text
+//! cargo build --target wasm32-unknown-unknown --release
+//! cargo test -p integration-tests
+//! ```
+#![cfg(test)]
+
+mod factory {
+    soroban_sdk::contractimport!(
+        file = "../../target/wasm32-unknown-unknown/release/raffle_factory.wasm"
+    );
+}
+
+mod instance {
+    soroban_sdk::contractimport!(
+        file = "../../target/wasm32-unknown-unknown/release/raffle_instance.wasm"
+    );
+}
+
+mod bench;
+mod setup;
+mod test;
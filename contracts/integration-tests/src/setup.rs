@@ -0,0 +1,89 @@
+//! Protocol deployment shared by the lifecycle tests and the cost checks.
+
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::token::StellarAssetClient;
+use soroban_sdk::{vec, Address, BytesN, Env, String};
+
+use crate::{factory, instance};
+
+pub(crate) const TICKET_PRICE: i128 = 10_000;
+pub(crate) const PROTOCOL_FEE_BP: u32 = 500;
+/// `TIMELOCK_DELAY_SECONDS` in `raffle-shared`.
+pub(crate) const TIMELOCK_DELAY_SECONDS: u64 = 172_800;
+/// `DEFAULT_CLAIM_LOCKUP_SECONDS` in `raffle-shared`.
+pub(crate) const DEFAULT_CLAIM_LOCKUP_SECONDS: u64 = 3_600;
+
+pub(crate) struct Protocol<'a> {
+    pub factory: factory::Client<'a>,
+    pub treasury: Address,
+    pub token: Address,
+}
+
+pub(crate) fn deploy_protocol(env: &Env) -> Protocol<'_> {
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let instance_hash = env.deployer().upload_contract_wasm(instance::WASM);
+    let factory = factory::Client::new(env, &env.register(factory::WASM, ()));
+    let treasury = Address::generate(env);
+    factory.init_factory(
+        &Address::generate(env),
+        &instance_hash,
+        &PROTOCOL_FEE_BP,
+        &treasury,
+    );
+    factory.set_creation_delay(&0);
+
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    Protocol {
+        factory,
+        treasury,
+        token,
+    }
+}
+
+pub(crate) fn raffle_config(
+    env: &Env,
+    token: &Address,
+    description: &str,
+) -> factory::RaffleConfig {
+    factory::RaffleConfig {
+        description: String::from_str(env, description),
+        end_time: env.ledger().timestamp() + 100,
+        no_deadline: false,
+        max_tickets: 10,
+        max_tickets_per_tx: 10,
+        min_tickets: 1,
+        allow_multiple: true,
+        ticket_price: TICKET_PRICE,
+        payment_token: token.clone(),
+        prize_amount: TICKET_PRICE * 10,
+        prizes: vec![env, 10_000u32],
+        randomness_source: factory::RandomnessSource::Internal,
+        oracle_address: None,
+        oracle_public_key: None,
+        auto_distribute: false,
+        protocol_fee_bp: 0,
+        treasury_address: None,
+        swap_router: None,
+        tikka_token: None,
+        metadata_hash: BytesN::from_array(env, &[1u8; 32]),
+        claim_lockup_seconds: 0,
+        prize_vesting_seconds: 0,
+        proceeds_vesting_seconds: 0,
+        swap_deadline_seconds: 0,
+        early_bird_ticket_percentage: 0,
+        early_bird_discount_bp: 0,
+        nft_contract: None,
+        category: None,
+    }
+}
+
+/// A fresh address holding `amount` of the protocol's payment token.
+pub(crate) fn funded_user(env: &Env, protocol: &Protocol, amount: i128) -> Address {
+    let user = Address::generate(env);
+    StellarAssetClient::new(env, &protocol.token).mint(&user, &amount);
+    user
+}
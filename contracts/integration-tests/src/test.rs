@@ -0,0 +1,95 @@
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::token::{StellarAssetClient, TokenClient};
+use soroban_sdk::{Address, Env};
+
+use crate::setup::{
+    deploy_protocol, raffle_config, DEFAULT_CLAIM_LOCKUP_SECONDS, PROTOCOL_FEE_BP, TICKET_PRICE,
+    TIMELOCK_DELAY_SECONDS,
+};
+use crate::{factory, instance};
+
+#[test]
+fn raffle_lifecycle_across_contract_boundaries() {
+    let env = Env::default();
+    let protocol = deploy_protocol(&env);
+    let token = TokenClient::new(&env, &protocol.token);
+    let mint = StellarAssetClient::new(&env, &protocol.token);
+    let creator = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    mint.mint(&creator, &1_000_000);
+    mint.mint(&buyer, &1_000_000);
+
+    let raffle_address = protocol.factory.create_raffle(
+        &creator,
+        &raffle_config(&env, &protocol.token, "Integration raffle"),
+    );
+    assert_eq!(
+        protocol.factory.get_raffle_by_id(&0),
+        Some(raffle_address.clone())
+    );
+    let raffle = instance::Client::new(&env, &raffle_address);
+    // The factory's fee and treasury were written into the instance.
+    let state = raffle.get_raffle();
+    assert_eq!(state.protocol_fee_bp, PROTOCOL_FEE_BP);
+    assert_eq!(state.treasury_address, Some(protocol.treasury.clone()));
+
+    raffle.deposit_prize();
+    raffle.buy_tickets(&buyer, &5, &0);
+
+    env.ledger().set_timestamp(1_101);
+    raffle.finalize_raffle();
+    let state = raffle.get_raffle();
+    assert_eq!(state.status, instance::RaffleStatus::Finalized);
+    // Purchase fees accrue in the raffle until they are remitted.
+    assert_eq!(token.balance(&protocol.treasury), 0);
+    raffle.remit_fees(&token.address);
+    assert_eq!(
+        token.balance(&protocol.treasury),
+        TICKET_PRICE * 5 * PROTOCOL_FEE_BP as i128 / 10_000
+    );
+    let winner = state.winners.get(0).unwrap();
+    assert_eq!(winner, buyer);
+
+    env.ledger()
+        .set_timestamp(1_101 + DEFAULT_CLAIM_LOCKUP_SECONDS);
+    let before = token.balance(&buyer);
+    let paid = raffle.claim_prize(&winner, &0);
+    assert!(paid > 0);
+    assert_eq!(token.balance(&buyer), before + paid);
+    assert_eq!(raffle.get_raffle().status, instance::RaffleStatus::Claimed);
+}
+
+#[test]
+fn upgrades_keep_state_and_rotate_the_instance_wasm_behind_the_timelock() {
+    let env = Env::default();
+    let protocol = deploy_protocol(&env);
+    let mint = StellarAssetClient::new(&env, &protocol.token);
+    let creator = Address::generate(&env);
+    mint.mint(&creator, &1_000_000);
+
+    protocol.factory.create_raffle(
+        &creator,
+        &raffle_config(&env, &protocol.token, "Before upgrade"),
+    );
+
+    let factory_hash = env.deployer().upload_contract_wasm(factory::WASM);
+    protocol.factory.upgrade(&factory_hash);
+    assert_eq!(protocol.factory.get_raffle_count(), 1);
+
+    let instance_hash = env.deployer().upload_contract_wasm(instance::WASM);
+    let op_id = protocol
+        .factory
+        .queue_action(&factory::AdminOp::UpdateWasmHash(instance_hash));
+    assert!(protocol.factory.try_execute_action(&op_id).is_err());
+    env.ledger().set_timestamp(1_000 + TIMELOCK_DELAY_SECONDS);
+    protocol.factory.execute_action(&op_id);
+
+    let raffle_address = protocol.factory.create_raffle(
+        &creator,
+        &raffle_config(&env, &protocol.token, "After upgrade"),
+    );
+    let raffle = instance::Client::new(&env, &raffle_address);
+    raffle.deposit_prize();
+    assert_eq!(raffle.get_raffle().status, instance::RaffleStatus::Active);
+    assert_eq!(protocol.factory.get_raffle_count(), 2);
+}
@@ -0,0 +1,162 @@
+#![no_std]
+
+//! Mock randomness oracle for tests.
+//!
+//! Stands in for the off-chain oracle service as a raffle's
+//! `oracle_address`.  A test (playing the service's event watcher) records
+//! each `RandomnessRequested` with `request`, and later calls `fulfill`,
+//! which forwards a deterministic seed to the raffle's `provide_randomness`
+//! once the configured delay has passed.  Contracts cannot sign, so the
+//! caller supplies the Ed25519 signature over the raffle's VRF message for
+//! `seed_for(raffle, request_id)`.  `FailureMode` makes the oracle misbehave
+//! in the ways a real one can.
+
+use soroban_sdk::{
+    contract, contracterror, contractevent, contractimpl, contracttype, xdr::ToXdr, Address,
+    BytesN, Env, IntoVal, Symbol,
+};
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FailureMode {
+    /// Deliver the seed as requested.
+    None,
+    /// Never respond, so the raffle has to fall back.
+    Silent,
+    /// Deliver a different seed than the one signed.
+    WrongSeed,
+    /// Answer a request ID the raffle never made.
+    WrongRequest,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleConfig {
+    /// Ledgers that must pass between `request` and `fulfill`.
+    pub delay_ledgers: u32,
+    pub failure_mode: FailureMode,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingRequest {
+    pub request_id: u64,
+    pub requested_at: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Config,
+    /// Open `PendingRequest` of a raffle.
+    Request(Address),
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub enum Error {
+    NoRequest = 1,
+    TooEarly = 2,
+    Unavailable = 3,
+    /// The raffle refused the randomness.
+    Rejected = 4,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["mock_oracle", "fulfilled"])]
+pub struct Fulfilled {
+    #[topic]
+    pub raffle: Address,
+    pub request_id: u64,
+    pub seed: u64,
+}
+
+fn config(env: &Env) -> OracleConfig {
+    env.storage()
+        .instance()
+        .get(&DataKey::Config)
+        .unwrap_or(OracleConfig {
+            delay_ledgers: 0,
+            failure_mode: FailureMode::None,
+        })
+}
+
+#[contract]
+pub struct MockOracle;
+
+#[contractimpl]
+impl MockOracle {
+    /// Test-only contract: anyone may reconfigure it.
+    pub fn configure(env: Env, config: OracleConfig) {
+        env.storage().instance().set(&DataKey::Config, &config);
+    }
+
+    pub fn get_config(env: Env) -> OracleConfig {
+        config(&env)
+    }
+
+    /// Records that `raffle` asked for randomness under `request_id`.
+    pub fn request(env: Env, raffle: Address, request_id: u64) {
+        env.storage().persistent().set(
+            &DataKey::Request(raffle),
+            &PendingRequest {
+                request_id,
+                requested_at: env.ledger().sequence(),
+            },
+        );
+    }
+
+    pub fn get_request(env: Env, raffle: Address) -> Option<PendingRequest> {
+        env.storage().persistent().get(&DataKey::Request(raffle))
+    }
+
+    /// Seed the oracle will deliver for `request_id`; a pure function of
+    /// the raffle and request so tests can predict it.
+    pub fn seed_for(env: Env, raffle: Address, request_id: u64) -> u64 {
+        let digest = env.crypto().sha256(&(raffle, request_id).to_xdr(&env));
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest.to_array()[..8]);
+        u64::from_be_bytes(bytes)
+    }
+
+    /// Answers `raffle`'s open request through its `provide_randomness`.
+    pub fn fulfill(env: Env, raffle: Address, signature: BytesN<64>) -> Result<u64, Error> {
+        let pending = Self::get_request(env.clone(), raffle.clone()).ok_or(Error::NoRequest)?;
+        let config = config(&env);
+        if env.ledger().sequence() < pending.requested_at + config.delay_ledgers {
+            return Err(Error::TooEarly);
+        }
+
+        let mut request_id = pending.request_id;
+        let mut seed = Self::seed_for(env.clone(), raffle.clone(), request_id);
+        match config.failure_mode {
+            FailureMode::None => {}
+            FailureMode::Silent => return Err(Error::Unavailable),
+            FailureMode::WrongSeed => seed = seed.wrapping_add(1),
+            FailureMode::WrongRequest => request_id = request_id.wrapping_add(1),
+        }
+
+        let delivered = env.try_invoke_contract::<Address, soroban_sdk::Error>(
+            &raffle,
+            &Symbol::new(&env, "provide_randomness"),
+            (seed, signature, request_id).into_val(&env),
+        );
+        if !matches!(delivered, Ok(Ok(_))) {
+            return Err(Error::Rejected);
+        }
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Request(raffle.clone()));
+
+        Fulfilled {
+            raffle,
+            request_id,
+            seed,
+        }
+        .publish(&env);
+        Ok(seed)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,175 @@
+#![cfg(test)]
+
+extern crate std;
+
+use super::*;
+use ed25519_dalek::{Signer, SigningKey};
+use raffle_instance::{ContractClient as RaffleClient, DataKey as RaffleKey, MIN_TICKET_PRICE};
+use raffle_shared::constants::ORACLE_TIMEOUT_LEDGERS;
+use raffle_shared::{RaffleConfig, RaffleStatus, RandomnessSource};
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::token::StellarAssetClient;
+use soroban_sdk::{vec, String};
+
+struct Setup<'a> {
+    env: Env,
+    raffle: RaffleClient<'a>,
+    oracle: MockOracleClient<'a>,
+    creator: Address,
+    signing_key: SigningKey,
+}
+
+/// A sold-out external-randomness raffle waiting on the mock oracle.
+fn setup<'a>() -> Setup<'a> {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_sequence_number(100);
+
+    let signing_key = SigningKey::from_bytes(&[9u8; 32]);
+    let oracle_id = env.register(MockOracle, ());
+    let oracle = MockOracleClient::new(&env, &oracle_id);
+    let raffle_id = env.register(raffle_instance::Contract, ());
+    let raffle = RaffleClient::new(&env, &raffle_id);
+
+    let creator = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    StellarAssetClient::new(&env, &token).mint(&creator, &1_000_000);
+    StellarAssetClient::new(&env, &token).mint(&buyer, &1_000_000);
+
+    raffle.init(
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &creator,
+        &RaffleConfig {
+            description: String::from_str(&env, "Oracle raffle"),
+            end_time: 0,
+            no_deadline: true,
+            max_tickets: 3,
+            max_tickets_per_tx: 3,
+            min_tickets: 1,
+            allow_multiple: true,
+            ticket_price: MIN_TICKET_PRICE,
+            payment_token: token,
+            prize_amount: MIN_TICKET_PRICE * 3,
+            prizes: vec![&env, 10_000u32],
+            randomness_source: RandomnessSource::External,
+            oracle_address: Some(oracle_id),
+            oracle_public_key: Some(BytesN::from_array(
+                &env,
+                &signing_key.verifying_key().to_bytes(),
+            )),
+            auto_distribute: false,
+            protocol_fee_bp: 0,
+            treasury_address: None,
+            swap_router: None,
+            tikka_token: None,
+            metadata_hash: BytesN::from_array(&env, &[1u8; 32]),
+            claim_lockup_seconds: 0,
+            prize_vesting_seconds: 0,
+            proceeds_vesting_seconds: 0,
+            swap_deadline_seconds: 0,
+            early_bird_ticket_percentage: 0,
+            early_bird_discount_bp: 0,
+            nft_contract: None,
+            category: None,
+        },
+    );
+    raffle.deposit_prize();
+    // Selling out starts the draw and emits `RandomnessRequested`.
+    raffle.buy_tickets(&buyer, &3, &0);
+    assert_eq!(raffle.get_raffle().status, RaffleStatus::Drawing);
+
+    // Play the oracle service's event watcher.
+    let request_id: u64 = env.as_contract(&raffle.address, || {
+        env.storage()
+            .instance()
+            .get(&RaffleKey::RandomnessRequestId)
+            .unwrap()
+    });
+    oracle.request(&raffle.address, &request_id);
+
+    Setup {
+        env,
+        raffle,
+        oracle,
+        creator,
+        signing_key,
+    }
+}
+
+/// Signs the raffle's VRF message for the seed the oracle will deliver.
+fn sign(setup: &Setup) -> BytesN<64> {
+    let env = &setup.env;
+    let raffle = setup.raffle.address.clone();
+    let request_id = setup.oracle.get_request(&raffle).unwrap().request_id;
+    let seed = setup.oracle.seed_for(&raffle, &request_id);
+    let message = (raffle, request_id, seed).to_xdr(env);
+    let mut buf = std::vec![0u8; message.len() as usize];
+    message.copy_into_slice(&mut buf);
+    let signature = setup.signing_key.sign(&buf);
+    BytesN::from_array(env, &signature.to_bytes())
+}
+
+#[test]
+fn oracle_fulfils_after_its_delay_and_the_raffle_draws() {
+    let setup = setup();
+    setup.oracle.configure(&OracleConfig {
+        delay_ledgers: 5,
+        failure_mode: FailureMode::None,
+    });
+    let signature = sign(&setup);
+
+    assert_eq!(
+        setup.oracle.try_fulfill(&setup.raffle.address, &signature),
+        Err(Ok(Error::TooEarly))
+    );
+    setup.env.ledger().set_sequence_number(105);
+    setup.oracle.fulfill(&setup.raffle.address, &signature);
+
+    let raffle = setup.raffle.get_raffle();
+    assert_eq!(raffle.status, RaffleStatus::Finalized);
+    assert_eq!(raffle.winners.len(), 1);
+    assert_eq!(
+        setup.oracle.try_fulfill(&setup.raffle.address, &signature),
+        Err(Ok(Error::NoRequest))
+    );
+}
+
+#[test]
+fn misbehaving_oracle_is_rejected_and_the_raffle_falls_back() {
+    let setup = setup();
+    let signature = sign(&setup);
+
+    for failure_mode in [FailureMode::WrongSeed, FailureMode::WrongRequest] {
+        setup.oracle.configure(&OracleConfig {
+            delay_ledgers: 0,
+            failure_mode,
+        });
+        assert_eq!(
+            setup.oracle.try_fulfill(&setup.raffle.address, &signature),
+            Err(Ok(Error::Rejected))
+        );
+        assert_eq!(setup.raffle.get_raffle().status, RaffleStatus::Drawing);
+    }
+
+    setup.oracle.configure(&OracleConfig {
+        delay_ledgers: 0,
+        failure_mode: FailureMode::Silent,
+    });
+    assert_eq!(
+        setup.oracle.try_fulfill(&setup.raffle.address, &signature),
+        Err(Ok(Error::Unavailable))
+    );
+
+    setup
+        .env
+        .ledger()
+        .set_sequence_number(100 + ORACLE_TIMEOUT_LEDGERS + 1);
+    setup
+        .raffle
+        .trigger_randomness_fallback(&setup.creator, &false);
+    assert_eq!(setup.raffle.get_raffle().status, RaffleStatus::Finalized);
+}
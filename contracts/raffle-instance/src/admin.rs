@@ -1,15 +1,16 @@
-use soroban_sdk::{token, Address, Env};
+use soroban_sdk::{Address, BytesN, Env};
 
-use raffle_shared::CancelReason;
+use raffle_shared::{KeeperReward, RandomnessSource};
 
 use crate::events::{
-    ContractPaused, ContractUnpaused, EmergencyWithdrawn, FeesWithdrawn, OracleAddressUpdated,
-    ProtocolFeeUpdated, RaffleCancelled, SwapDeadlineUpdated, TicketSalesPaused, TicketSalesResumed,
-    TokensRescued,
+    AutoFinalizeUpdated, DisputeWindowUpdated, FeeOnTipsUpdated, KeeperRewardUpdated,
+    OracleAddressUpdated, OracleTimeoutUpdated, ProtocolFeeUpdated, SwapDeadlineUpdated,
+    YieldStrategyUpdated,
 };
 use crate::{
-    read_raffle, require_admin, write_raffle, DataKey, Error, RaffleStatus,
-    EMERGENCY_WITHDRAW_DELAY_SECONDS, MAX_PROTOCOL_FEE_BP, MAX_SWAP_DEADLINE_SECONDS,
+    oracle_timeout_ledgers, read_raffle, require_admin, require_approved_oracle, write_raffle,
+    DataKey, Error, RaffleStatus, YieldStrategy, MAX_DISPUTE_WINDOW_SECONDS, MAX_KEEPER_REWARD_BP,
+    MAX_ORACLE_TIMEOUT_LEDGERS, MAX_PROTOCOL_FEE_BP, MAX_SWAP_DEADLINE_SECONDS,
 };
 
 pub(crate) fn set_admin(env: Env, new_admin: Address) -> Result<(), Error> {
@@ -17,201 +18,244 @@ pub(crate) fn set_admin(env: Env, new_admin: Address) -> Result<(), Error> {
     if !new_admin.exists() || new_admin == env.current_contract_address() {
         return Err(Error::InvalidAdminAddress);
     }
-    env.storage().persistent().set(&DataKey::Admin, &new_admin);
+    env.storage().instance().set(&DataKey::Admin, &new_admin);
     Ok(())
 }
 
-pub(crate) fn update_oracle_address(env: Env, new_oracle: Address) -> Result<(), Error> {
+pub(crate) fn update_oracle_address(
+    env: Env,
+    new_oracle: Address,
+    new_public_key: BytesN<32>,
+) -> Result<(), Error> {
     let admin = require_admin(&env)?;
     let mut raffle = read_raffle(&env)?;
-    if raffle.randomness_source != raffle_shared::RandomnessSource::External { return Err(Error::InvalidParameters); }
-    if new_oracle == env.current_contract_address() { return Err(Error::InvalidParameters); }
-    if raffle.status == RaffleStatus::Finalized || raffle.status == RaffleStatus::Claimed || raffle.status == RaffleStatus::Cancelled {
+    if raffle.randomness_source != raffle_shared::RandomnessSource::External {
+        return Err(Error::InvalidParameters);
+    }
+    if new_oracle == env.current_contract_address() {
+        return Err(Error::InvalidParameters);
+    }
+    if let Some(factory) = env
+        .storage()
+        .instance()
+        .get::<_, Address>(&DataKey::Factory)
+    {
+        require_approved_oracle(&env, &factory, &new_oracle)?;
+    }
+    if raffle.status == RaffleStatus::Finalized
+        || raffle.status == RaffleStatus::Claimed
+        || raffle.status == RaffleStatus::Cancelled
+    {
         return Err(Error::InvalidStatus);
     }
     let old = raffle.oracle_address.clone();
     raffle.oracle_address = Some(new_oracle.clone());
+    raffle.oracle_public_key = Some(new_public_key.clone());
     write_raffle(&env, &raffle);
-    OracleAddressUpdated { old_oracle: old, new_oracle, updated_by: admin, timestamp: env.ledger().timestamp() }.publish(&env);
+    OracleAddressUpdated {
+        old_oracle: old,
+        new_oracle,
+        new_public_key,
+        updated_by: admin,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(&env);
     Ok(())
 }
 
 pub(crate) fn set_protocol_fee_bp(env: Env, new_fee_bp: u32) -> Result<(), Error> {
     let admin = require_admin(&env)?;
-    if new_fee_bp > MAX_PROTOCOL_FEE_BP { return Err(Error::InvalidParameters); }
+    if new_fee_bp > MAX_PROTOCOL_FEE_BP {
+        return Err(Error::InvalidFee);
+    }
     let mut raffle = read_raffle(&env)?;
-    if raffle.tickets_sold > 0 { return Err(Error::InvalidStatus); }
+    if raffle.tickets_sold > 0 {
+        return Err(Error::InvalidStatus);
+    }
     let old = raffle.protocol_fee_bp;
     raffle.protocol_fee_bp = new_fee_bp;
     write_raffle(&env, &raffle);
-    ProtocolFeeUpdated { old_fee_bp: old, new_fee_bp, updated_by: admin, timestamp: env.ledger().timestamp() }.publish(&env);
+    ProtocolFeeUpdated {
+        old_fee_bp: old,
+        new_fee_bp,
+        updated_by: admin,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(&env);
     Ok(())
 }
 
-pub(crate) fn set_swap_deadline(env: Env, new_deadline_seconds: u64) -> Result<(), Error> {
+pub(crate) fn set_oracle_timeout(env: Env, timeout_ledgers: u32) -> Result<(), Error> {
     let admin = require_admin(&env)?;
-    if new_deadline_seconds > MAX_SWAP_DEADLINE_SECONDS { return Err(Error::InvalidParameters); }
-    let mut raffle = read_raffle(&env)?;
-    if raffle.tickets_sold > 0 { return Err(Error::InvalidStatus); }
-    let old = raffle.swap_deadline_seconds;
-    raffle.swap_deadline_seconds = new_deadline_seconds;
-    write_raffle(&env, &raffle);
-    SwapDeadlineUpdated { old_deadline_seconds: old, new_deadline_seconds, updated_by: admin, timestamp: env.ledger().timestamp() }.publish(&env);
-    Ok(())
-}
-
-pub(crate) fn cancel_raffle(env: Env, reason: CancelReason) -> Result<(), Error> {
-    let mut raffle = read_raffle(&env)?;
-    match reason {
-        CancelReason::AdminCancelled => {
-            let admin: Address = env.storage().instance().get(&DataKey::Admin).ok_or(Error::NotAuthorized)?;
-            admin.require_auth();
-        }
-        _ => raffle.creator.require_auth(),
-    }
-    if raffle.status == RaffleStatus::Finalized || raffle.status == RaffleStatus::Cancelled || raffle.status == RaffleStatus::Claimed {
+    if timeout_ledgers == 0 || timeout_ledgers > MAX_ORACLE_TIMEOUT_LEDGERS {
+        return Err(Error::InvalidParameters);
+    }
+    let raffle = read_raffle(&env)?;
+    if raffle.tickets_sold > 0 {
         return Err(Error::InvalidStatus);
     }
-    raffle.status = RaffleStatus::Cancelled;
-    write_raffle(&env, &raffle);
-    RaffleCancelled { creator: raffle.creator.clone(), reason, tickets_sold: raffle.tickets_sold, prize_refunded: raffle.prize_deposited, timestamp: env.ledger().timestamp() }.publish(&env);
-    Ok(())
-}
-
-pub(crate) fn pause(env: Env) -> Result<(), Error> {
-    let f: Address = env.storage().instance().get(&DataKey::Factory).ok_or(Error::NotAuthorized)?;
-    f.require_auth();
-    env.storage().instance().set(&DataKey::Paused, &true);
-    ContractPaused { paused_by: f, timestamp: env.ledger().timestamp() }.publish(&env);
+    let old = oracle_timeout_ledgers(&env);
+    env.storage()
+        .instance()
+        .set(&DataKey::OracleTimeoutLedgers, &timeout_ledgers);
+    OracleTimeoutUpdated {
+        old_timeout_ledgers: old,
+        new_timeout_ledgers: timeout_ledgers,
+        updated_by: admin,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(&env);
     Ok(())
 }
 
-pub(crate) fn unpause(env: Env) -> Result<(), Error> {
-    let f: Address = env.storage().instance().get(&DataKey::Factory).ok_or(Error::NotAuthorized)?;
-    f.require_auth();
-    env.storage().instance().set(&DataKey::Paused, &false);
-    ContractUnpaused { unpaused_by: f, timestamp: env.ledger().timestamp() }.publish(&env);
+pub(crate) fn set_dispute_window(
+    env: Env,
+    arbiter: Address,
+    window_seconds: u64,
+) -> Result<(), Error> {
+    let admin = require_admin(&env)?;
+    if window_seconds > MAX_DISPUTE_WINDOW_SECONDS {
+        return Err(Error::InvalidParameters);
+    }
+    let raffle = read_raffle(&env)?;
+    if raffle.tickets_sold > 0 {
+        return Err(Error::InvalidStatus);
+    }
+    // Auto-distribution pays out at finalization, leaving nothing to hold back.
+    if raffle.auto_distribute && window_seconds > 0 {
+        return Err(Error::InvalidParameters);
+    }
+    env.storage().instance().set(&DataKey::Arbiter, &arbiter);
+    env.storage()
+        .instance()
+        .set(&DataKey::DisputeWindowSeconds, &window_seconds);
+    DisputeWindowUpdated {
+        arbiter,
+        window_seconds,
+        updated_by: admin,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(&env);
     Ok(())
 }
 
-pub(crate) fn pause_ticket_sales(env: Env, caller: Address) -> Result<(), Error> {
-    caller.require_auth();
-    let mut raffle = read_raffle(&env)?;
-    let admin: Address = env.storage().instance().get(&DataKey::Admin).ok_or(Error::NotAuthorized)?;
-    if caller != raffle.creator && caller != admin { return Err(Error::NotAuthorized); }
-    if raffle.status != RaffleStatus::Active { return Err(Error::InvalidStatus); }
-    raffle.ticket_sales_paused = true;
-    write_raffle(&env, &raffle);
-    TicketSalesPaused { paused_by: caller, timestamp: env.ledger().timestamp() }.publish(&env);
+pub(crate) fn set_keeper_reward(env: Env, reward: KeeperReward) -> Result<(), Error> {
+    let admin = require_admin(&env)?;
+    match reward {
+        KeeperReward::Flat(amount) if amount < 0 => return Err(Error::InvalidParameters),
+        KeeperReward::Bps(bp) if bp > MAX_KEEPER_REWARD_BP => return Err(Error::InvalidParameters),
+        _ => {}
+    }
+    let raffle = read_raffle(&env)?;
+    if raffle.tickets_sold > 0 {
+        return Err(Error::InvalidStatus);
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::KeeperReward, &reward);
+    KeeperRewardUpdated {
+        reward,
+        updated_by: admin,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(&env);
     Ok(())
 }
 
-pub(crate) fn resume_ticket_sales(env: Env, caller: Address) -> Result<(), Error> {
-    caller.require_auth();
-    let mut raffle = read_raffle(&env)?;
-    let admin: Address = env.storage().instance().get(&DataKey::Admin).ok_or(Error::NotAuthorized)?;
-    if caller != raffle.creator && caller != admin { return Err(Error::NotAuthorized); }
-    if raffle.status != RaffleStatus::Active { return Err(Error::InvalidStatus); }
-    raffle.ticket_sales_paused = false;
-    write_raffle(&env, &raffle);
-    TicketSalesResumed { resumed_by: caller, timestamp: env.ledger().timestamp() }.publish(&env);
+pub(crate) fn set_fee_on_tips(env: Env, enabled: bool) -> Result<(), Error> {
+    let admin = require_admin(&env)?;
+    let raffle = read_raffle(&env)?;
+    if raffle.tickets_sold > 0 {
+        return Err(Error::InvalidStatus);
+    }
+    env.storage().instance().set(&DataKey::FeeOnTips, &enabled);
+    FeeOnTipsUpdated {
+        enabled,
+        updated_by: admin,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(&env);
     Ok(())
 }
 
-pub(crate) fn withdraw_fees(env: Env, recipient: Address, amount: i128) -> Result<(), Error> {
-    let _admin = require_admin(&env)?;
+pub(crate) fn set_auto_finalize(env: Env, enabled: bool) -> Result<(), Error> {
+    let admin = require_admin(&env)?;
     let raffle = read_raffle(&env)?;
-    if raffle.status != RaffleStatus::Finalized && raffle.status != RaffleStatus::Claimed { return Err(Error::InvalidStatus); }
-    if amount <= 0 { return Err(Error::InvalidParameters); }
-    let acc: i128 = env.storage().instance().get(&DataKey::AccumulatedFees).unwrap_or(0);
-    if amount > acc { return Err(Error::InsufficientAccumulatedFees); }
-    let tc = token::Client::new(&env, &raffle.payment_token);
-    tc.transfer(&env.current_contract_address(), &recipient, &amount);
-    env.storage().instance().set(&DataKey::AccumulatedFees, &(acc - amount));
-    FeesWithdrawn { recipient, amount, token: raffle.payment_token.clone(), timestamp: env.ledger().timestamp() }.publish(&env);
+    if raffle.tickets_sold > 0 {
+        return Err(Error::InvalidStatus);
+    }
+    // Oracle-backed sources cannot be drawn inside the purchase.
+    if enabled && raffle.randomness_source != RandomnessSource::Internal {
+        return Err(Error::InvalidParameters);
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::AutoFinalize, &enabled);
+    AutoFinalizeUpdated {
+        enabled,
+        updated_by: admin,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(&env);
     Ok(())
 }
 
-pub(crate) fn rescue_tokens(env: Env, token: Address, recipient: Address, amount: i128) -> Result<(), Error> {
-    let admin: Address = env.storage().instance().get(&DataKey::Admin).ok_or(Error::NotAuthorized)?;
-    admin.require_auth();
-    if amount <= 0 { return Err(Error::InvalidParameters); }
-    if let Ok(raffle) = read_raffle(&env) {
-        if token == raffle.payment_token && raffle.prize_deposited { return Err(Error::InvalidParameters); }
-    }
-    let tc = token::Client::new(&env, &token);
-    let _ = tc.try_transfer(&env.current_contract_address(), &recipient, &amount).map_err(|_| Error::TokenTransferFailed)?;
-    TokensRescued { rescued_by: admin, token, recipient, amount, timestamp: env.ledger().timestamp() }.publish(&env);
+pub(crate) fn set_yield_strategy(env: Env, config: Option<YieldStrategy>) -> Result<(), Error> {
+    let admin = require_admin(&env)?;
+    let raffle = read_raffle(&env)?;
+    if raffle.prize_deposited {
+        return Err(Error::PrizeAlreadyDeposited);
+    }
+    match config.clone() {
+        Some(config) => env
+            .storage()
+            .instance()
+            .set(&DataKey::YieldStrategy, &config),
+        None => env.storage().instance().remove(&DataKey::YieldStrategy),
+    }
+    YieldStrategyUpdated {
+        config,
+        updated_by: admin,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(&env);
     Ok(())
 }
 
-pub(crate) fn wipe_storage(env: Env) -> Result<(), Error> {
-    let factory: Address = env.storage().instance().get(&DataKey::Factory).ok_or(Error::NotAuthorized)?;
-    factory.require_auth();
-    let raffle = read_raffle(&env)?;
-    if raffle.status != RaffleStatus::Cancelled && raffle.status != RaffleStatus::Claimed && raffle.status != RaffleStatus::Failed {
-        return Err(Error::InvalidStatus);
+/// Recalls the prize from the yield strategy ahead of time and stops using it.
+pub(crate) fn exit_yield_strategy(env: Env) -> Result<(), Error> {
+    let admin = require_admin(&env)?;
+    let mut raffle = read_raffle(&env)?;
+    crate::strategy::recall(&env, &mut raffle)?;
+    write_raffle(&env, &raffle);
+    env.storage().instance().remove(&DataKey::YieldStrategy);
+    YieldStrategyUpdated {
+        config: None,
+        updated_by: admin,
+        timestamp: env.ledger().timestamp(),
     }
-
-    for i in 1..=raffle.tickets_sold {
-        env.storage().persistent().remove(&DataKey::Ticket(i));
-        env.storage().persistent().remove(&DataKey::TicketRefunded(i));
-        env.storage().persistent().remove(&DataKey::CommitEntry(i));
-    }
-    let buyers: soroban_sdk::Vec<Address> = env.storage().persistent().get(&DataKey::TicketBuyers).unwrap_or_else(|| soroban_sdk::Vec::new(&env));
-    for b in buyers.iter() { env.storage().persistent().remove(&DataKey::TicketCount(b.clone())); }
-    env.storage().persistent().remove(&DataKey::TicketBuyers);
-
-    env.storage().instance().remove(&DataKey::Raffle);
-    env.storage().instance().remove(&DataKey::Factory);
-    env.storage().instance().remove(&DataKey::Admin);
-    env.storage().instance().remove(&DataKey::Paused);
-    env.storage().instance().remove(&DataKey::ReentrancyGuard);
-    env.storage().instance().remove(&DataKey::AccumulatedFees);
-    env.storage().instance().remove(&DataKey::RandomnessRequested);
-    env.storage().instance().remove(&DataKey::RandomnessRequestLedger);
-    env.storage().instance().remove(&DataKey::RandomnessRequestId);
-    env.storage().instance().remove(&DataKey::DrawingLock);
-    env.storage().instance().remove(&DataKey::FinishTime);
-    env.storage().persistent().remove(&DataKey::RandomnessSeed);
-    env.storage().persistent().remove(&DataKey::Admin);
-
+    .publish(&env);
     Ok(())
 }
 
-pub(crate) fn emergency_withdraw(env: Env, caller: Address) -> Result<(), Error> {
-    caller.require_auth();
+pub(crate) fn set_swap_deadline(env: Env, new_deadline_seconds: u64) -> Result<(), Error> {
+    let admin = require_admin(&env)?;
+    if new_deadline_seconds > MAX_SWAP_DEADLINE_SECONDS {
+        return Err(Error::InvalidParameters);
+    }
     let mut raffle = read_raffle(&env)?;
-    if !raffle.prize_deposited { return Err(Error::PrizeNotDeposited); }
-
-    let admin: Address = env.storage().instance().get(&DataKey::Admin).ok_or(Error::NotAuthorized)?;
-    if caller != raffle.creator && caller != admin { return Err(Error::NotAuthorized); }
-
-    let now = env.ledger().timestamp();
-    match raffle.status {
-        RaffleStatus::Finalized => match raffle.finalized_at {
-            Some(fa) if now >= fa + EMERGENCY_WITHDRAW_DELAY_SECONDS => {}
-            _ => return Err(Error::EmergencyTooEarly),
-        },
-        RaffleStatus::Drawing => {
-            if raffle.no_deadline {
-                let rl: u32 = env.storage().instance().get(&DataKey::RandomnessRequestLedger).unwrap_or(0);
-                let est = (env.ledger().sequence().saturating_sub(rl) as u64) * 5;
-                if est < EMERGENCY_WITHDRAW_DELAY_SECONDS { return Err(Error::EmergencyTooEarly); }
-            } else if now < raffle.end_time + EMERGENCY_WITHDRAW_DELAY_SECONDS {
-                return Err(Error::EmergencyTooEarly);
-            }
-        }
-        _ => return Err(Error::InvalidStatus),
+    if raffle.tickets_sold > 0 {
+        return Err(Error::InvalidStatus);
     }
-
-    raffle.prize_deposited = false;
-    raffle.status = RaffleStatus::Cancelled;
+    let old = raffle.swap_deadline_seconds;
+    raffle.swap_deadline_seconds = new_deadline_seconds;
     write_raffle(&env, &raffle);
-
-    let tc = token::Client::new(&env, &raffle.payment_token);
-    tc.transfer(&env.current_contract_address(), &raffle.creator, &raffle.prize_amount);
-
-    EmergencyWithdrawn { withdrawn_by: caller, to: raffle.creator.clone(), amount: raffle.prize_amount, token: raffle.payment_token.clone(), timestamp: now }.publish(&env);
+    SwapDeadlineUpdated {
+        old_deadline_seconds: old,
+        new_deadline_seconds,
+        updated_by: admin,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(&env);
     Ok(())
 }
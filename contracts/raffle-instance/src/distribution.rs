@@ -0,0 +1,255 @@
+//! Everyone-wins distribution.
+//!
+//! In `DistributionMode::Proportional` finalization draws no winners.
+//! Every ticket is instead owed an equal share of the prize pool, and
+//! anyone may call `distribute` to pay out a page of tickets at a time so
+//! large raffles stay inside the instruction budget.  The rounding dust is
+//! sent to the treasury at finalization, as tier dust is.
+//!
+//! Winner-draw raffles created with `auto_distribute` are paid out at
+//! finalization instead of waiting for each winner to claim, and remit
+//! their purchase fees to the treasury in the same call.
+
+use soroban_sdk::{token, Address, Env, Map};
+
+use raffle_shared::{effective_limit, Ticket};
+
+use crate::events::{
+    DistributionModeUpdated, PrizeClaimed, PrizeShareDistributed, RaffleStatusChanged,
+};
+use crate::{
+    assert_solvent, calculate_tier_prize, math, read_raffle, remit_accrued_fees,
+    report_claim_to_factory, report_status_to_factory, require_dispute_window_elapsed,
+    require_not_blocked, require_not_paused, tier_winner, write_raffle, DataKey, DistributionMode,
+    Error, Guard, NftTicketClient, Raffle, RaffleStatus,
+};
+
+pub(crate) fn mode(env: &Env) -> DistributionMode {
+    env.storage()
+        .instance()
+        .get(&DataKey::DistributionMode)
+        .unwrap_or(DistributionMode::Winners)
+}
+
+pub(crate) fn is_proportional(env: &Env) -> bool {
+    mode(env) == DistributionMode::Proportional
+}
+
+pub(crate) fn set_mode(env: &Env, creator: Address, mode: DistributionMode) -> Result<(), Error> {
+    let raffle = read_raffle(env)?;
+    if raffle.tickets_sold > 0 {
+        return Err(Error::InvalidStatus);
+    }
+    // Shares are paid in one go; there is no per-ticket vesting.
+    if mode == DistributionMode::Proportional && raffle.prize_vesting_seconds > 0 {
+        return Err(Error::InvalidParameters);
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::DistributionMode, &mode);
+
+    DistributionModeUpdated {
+        mode,
+        updated_by: creator,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+    Ok(())
+}
+
+/// Prize owed to each ticket.
+pub(crate) fn share(raffle: &Raffle) -> Result<i128, Error> {
+    if raffle.tickets_sold == 0 {
+        return Err(Error::NoTicketsSold);
+    }
+    Ok(raffle.prize_amount / raffle.tickets_sold as i128)
+}
+
+/// What is left of the pool once every ticket has its share.
+pub(crate) fn dust(raffle: &Raffle) -> Result<i128, Error> {
+    math::sub(
+        raffle.prize_amount,
+        math::total_price(share(raffle)?, raffle.tickets_sold)?,
+    )
+}
+
+/// Tickets whose share has been paid.
+pub(crate) fn distributed(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::DistributedTickets)
+        .unwrap_or(0)
+}
+
+/// Shares drawn but not yet paid out.
+pub(crate) fn outstanding(env: &Env, raffle: &Raffle) -> Result<i128, Error> {
+    if !is_proportional(env) || raffle.tickets_sold == 0 {
+        return Ok(0);
+    }
+    math::total_price(
+        share(raffle)?,
+        raffle.tickets_sold.saturating_sub(distributed(env)),
+    )
+}
+
+/// Whoever is paid for `ticket`: its NFT holder when tickets are NFTs.
+fn holder(env: &Env, raffle: &Raffle, ticket: Ticket) -> Address {
+    match raffle.nft_contract.as_ref() {
+        Some(nft) => NftTicketClient::new(env, nft)
+            .owner_of(&env.current_contract_address(), &ticket.id)
+            .unwrap_or(ticket.owner),
+        None => ticket.owner,
+    }
+}
+
+/// Pays the shares of tickets `offset + 1 ..= offset + limit` that are still
+/// unpaid, one transfer per holder.  Returns the amount paid.
+pub(crate) fn distribute(env: &Env, offset: u32, limit: u32) -> Result<i128, Error> {
+    require_not_paused(env)?;
+    let _guard = Guard::new(env)?;
+    let mut raffle = read_raffle(env)?;
+    if !is_proportional(env) {
+        return Err(Error::InvalidParameters);
+    }
+    if raffle.status != RaffleStatus::Finalized {
+        return Err(Error::InvalidStatus);
+    }
+    require_dispute_window_elapsed(env, &raffle)?;
+    if let Some(finalized_at) = raffle.finalized_at {
+        if env.ledger().timestamp() < finalized_at + raffle.claim_lockup_seconds {
+            return Err(Error::ClaimTooEarly);
+        }
+    }
+
+    let share = share(&raffle)?;
+    let end = offset
+        .saturating_add(effective_limit(limit))
+        .min(raffle.tickets_sold);
+    let mut tickets_by_holder: Map<Address, u32> = Map::new(env);
+    let mut paid_tickets = 0u32;
+    for ticket_id in offset.saturating_add(1)..=end {
+        let key = DataKey::TicketDistributed(ticket_id);
+        if env.storage().persistent().has(&key) {
+            continue;
+        }
+        let ticket: Ticket = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Ticket(ticket_id))
+            .ok_or(Error::TicketNotFound)?;
+        env.storage().persistent().set(&key, &true);
+        let holder = holder(env, &raffle, ticket);
+        let held = tickets_by_holder.get(holder.clone()).unwrap_or(0);
+        tickets_by_holder.set(holder, held + 1);
+        paid_tickets += 1;
+    }
+    if paid_tickets == 0 {
+        return Err(Error::NothingToWithdraw);
+    }
+
+    // Effects before the transfers, as in `claim_prize`.
+    let distributed = math::add_u32(distributed(env), paid_tickets)?;
+    env.storage()
+        .instance()
+        .set(&DataKey::DistributedTickets, &distributed);
+    if distributed == raffle.tickets_sold {
+        raffle.status = RaffleStatus::Claimed;
+        write_raffle(env, &raffle);
+        report_status_to_factory(env, &raffle);
+        RaffleStatusChanged {
+            old_status: RaffleStatus::Finalized,
+            new_status: RaffleStatus::Claimed,
+            timestamp: env.ledger().timestamp(),
+        }
+        .publish(env);
+    }
+
+    let prize_client = token::Client::new(env, &raffle.prize_token);
+    let this = env.current_contract_address();
+    let mut total = 0i128;
+    for (holder, tickets) in tickets_by_holder.iter() {
+        let amount = math::total_price(share, tickets)?;
+        if amount > 0 {
+            let _ = prize_client
+                .try_transfer(&this, &holder, &amount)
+                .map_err(|_| Error::TokenTransferFailed)?;
+        }
+        total = math::add(total, amount)?;
+        PrizeShareDistributed {
+            holder,
+            tickets,
+            amount,
+            timestamp: env.ledger().timestamp(),
+        }
+        .publish(env);
+    }
+    assert_solvent(env, &raffle.prize_token);
+    Ok(total)
+}
+
+/// Pays every drawn tier straight to its holder when the raffle opted into
+/// `auto_distribute`, and remits the purchase fees to the treasury.  A tier
+/// `claim_prize` would refuse, because the claim lockup has not elapsed or
+/// the winner is blocked, or whose transfer fails, stays unclaimed so its
+/// winner can still fall back to `claim_prize`.
+pub(crate) fn auto_distribute(env: &Env, mut raffle: Raffle) -> Result<(), Error> {
+    if let Some(treasury) = raffle.treasury_address.clone() {
+        remit_accrued_fees(env, treasury, raffle.payment_token.clone())?;
+    }
+    if raffle.claim_lockup_seconds > 0 {
+        return Ok(());
+    }
+
+    let prize_client = token::Client::new(env, &raffle.prize_token);
+    let this = env.current_contract_address();
+    for tier_index in 0..raffle.winners.len() {
+        let amount = calculate_tier_prize(&raffle, tier_index)?;
+        if amount <= 0 {
+            continue;
+        }
+        let winner = tier_winner(env, &raffle, tier_index)?;
+        if require_not_blocked(env, &winner).is_err() {
+            continue;
+        }
+
+        // Effects before the transfer, as in `claim_prize`; undone if the
+        // transfer fails.
+        raffle.claimed_winners.set(tier_index, true);
+        write_raffle(env, &raffle);
+        if !matches!(
+            prize_client.try_transfer(&this, &winner, &amount),
+            Ok(Ok(()))
+        ) {
+            raffle.claimed_winners.set(tier_index, false);
+            write_raffle(env, &raffle);
+            continue;
+        }
+
+        PrizeClaimed {
+            winner: winner.clone(),
+            recipient: winner.clone(),
+            tier_index,
+            payment_token: raffle.prize_token.clone(),
+            gross_amount: amount,
+            net_amount: amount,
+            platform_fee: 0,
+            claimed_at: env.ledger().timestamp(),
+        }
+        .publish(env);
+        report_claim_to_factory(env, &winner, tier_index);
+    }
+
+    if raffle.claimed_winners.iter().all(|claimed| claimed) {
+        raffle.status = RaffleStatus::Claimed;
+        write_raffle(env, &raffle);
+        report_status_to_factory(env, &raffle);
+        RaffleStatusChanged {
+            old_status: RaffleStatus::Finalized,
+            new_status: RaffleStatus::Claimed,
+            timestamp: env.ledger().timestamp(),
+        }
+        .publish(env);
+    }
+    assert_solvent(env, &raffle.prize_token);
+    Ok(())
+}
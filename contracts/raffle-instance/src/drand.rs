@@ -0,0 +1,130 @@
+use soroban_sdk::{
+    crypto::bls12_381::{G1Affine, G2Affine},
+    Bytes, BytesN, Env, Vec,
+};
+
+// ============================================================================
+// drand beacon verification
+// ============================================================================
+//
+// Raffles using `RandomnessSource::Drand` are drawn from the drand "quicknet"
+// network (League of Entropy, scheme `bls-unchained-g1-rfc9380`).  Every
+// `DRAND_PERIOD_SECONDS` the network publishes a BLS signature over the round
+// number; the signature is unpredictable before it is published and anyone
+// can check it against the network's group public key.
+//
+// When the draw starts, the contract commits to the first round that will be
+// published *after* that moment, so nobody can shop between beacons.  Anyone
+// may then submit that round's signature; it is verified here with the host's
+// BLS12-381 pairing check:
+//
+//     e(signature, G2) == e(H(sha256(round)), group_key)
+//
+// which is evaluated as a single product `e(sig, -G2) · e(H(m), pk) == 1`.
+//
+// Points are passed in Soroban's uncompressed encoding.  drand publishes
+// signatures compressed (48 bytes); clients must decompress them first.
+
+/// Unix timestamp of quicknet round 1.
+pub const DRAND_GENESIS_TIME: u64 = 1_692_803_367;
+
+/// Seconds between quicknet rounds.
+pub const DRAND_PERIOD_SECONDS: u64 = 3;
+
+/// Hash-to-curve domain separation tag used by quicknet.
+const DRAND_DST: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+
+/// Quicknet group public key (G2, uncompressed).
+const DRAND_PUBLIC_KEY: [u8; 192] = [
+    0x03, 0xcf, 0x0f, 0x28, 0x96, 0xad, 0xee, 0x7e, 0xb8, 0xb5, 0xf0, 0x1f, 0xca, 0xd3, 0x91, 0x22,
+    0x12, 0xc4, 0x37, 0xe0, 0x07, 0x3e, 0x91, 0x1f, 0xb9, 0x00, 0x22, 0xd3, 0xe7, 0x60, 0x18, 0x3c,
+    0x8c, 0x4b, 0x45, 0x0b, 0x6a, 0x0a, 0x6c, 0x3a, 0xc6, 0xa5, 0x77, 0x6a, 0x2d, 0x10, 0x64, 0x51,
+    0x0d, 0x1f, 0xec, 0x75, 0x8c, 0x92, 0x1c, 0xc2, 0x2b, 0x0e, 0x17, 0xe6, 0x3a, 0xaf, 0x4b, 0xcb,
+    0x5e, 0xd6, 0x63, 0x04, 0xde, 0x9c, 0xf8, 0x09, 0xbd, 0x27, 0x4c, 0xa7, 0x3b, 0xab, 0x4a, 0xf5,
+    0xa6, 0xe9, 0xc7, 0x6a, 0x4b, 0xc0, 0x9e, 0x76, 0xea, 0xe8, 0x99, 0x1e, 0xf5, 0xec, 0xe4, 0x5a,
+    0x01, 0xa7, 0x14, 0xf2, 0xed, 0xb7, 0x41, 0x19, 0xa2, 0xf2, 0xb0, 0xd5, 0xa7, 0xc7, 0x5b, 0xa9,
+    0x02, 0xd1, 0x63, 0x70, 0x0a, 0x61, 0xbc, 0x22, 0x4e, 0xde, 0xdd, 0x8e, 0x63, 0xae, 0xf7, 0xbe,
+    0x1a, 0xaf, 0x8e, 0x93, 0xd7, 0xa9, 0x71, 0x8b, 0x04, 0x7c, 0xcd, 0xdb, 0x3e, 0xb5, 0xd6, 0x8b,
+    0x0e, 0x5d, 0xb2, 0xb6, 0xbf, 0xbb, 0x01, 0xc8, 0x67, 0x74, 0x9c, 0xad, 0xff, 0xca, 0x88, 0xb3,
+    0x6c, 0x24, 0xf3, 0x01, 0x2b, 0xa0, 0x9f, 0xc4, 0xd3, 0x02, 0x2c, 0x5c, 0x37, 0xdc, 0xe0, 0xf9,
+    0x77, 0xd3, 0xad, 0xb5, 0xd1, 0x83, 0xc7, 0x47, 0x7c, 0x44, 0x2b, 0x1f, 0x04, 0x51, 0x52, 0x73,
+];
+
+/// Negated BLS12-381 G2 generator (uncompressed).
+const NEG_G2_GENERATOR: [u8; 192] = [
+    0x13, 0xe0, 0x2b, 0x60, 0x52, 0x71, 0x9f, 0x60, 0x7d, 0xac, 0xd3, 0xa0, 0x88, 0x27, 0x4f, 0x65,
+    0x59, 0x6b, 0xd0, 0xd0, 0x99, 0x20, 0xb6, 0x1a, 0xb5, 0xda, 0x61, 0xbb, 0xdc, 0x7f, 0x50, 0x49,
+    0x33, 0x4c, 0xf1, 0x12, 0x13, 0x94, 0x5d, 0x57, 0xe5, 0xac, 0x7d, 0x05, 0x5d, 0x04, 0x2b, 0x7e,
+    0x02, 0x4a, 0xa2, 0xb2, 0xf0, 0x8f, 0x0a, 0x91, 0x26, 0x08, 0x05, 0x27, 0x2d, 0xc5, 0x10, 0x51,
+    0xc6, 0xe4, 0x7a, 0xd4, 0xfa, 0x40, 0x3b, 0x02, 0xb4, 0x51, 0x0b, 0x64, 0x7a, 0xe3, 0xd1, 0x77,
+    0x0b, 0xac, 0x03, 0x26, 0xa8, 0x05, 0xbb, 0xef, 0xd4, 0x80, 0x56, 0xc8, 0xc1, 0x21, 0xbd, 0xb8,
+    0x13, 0xfa, 0x4d, 0x4a, 0x0a, 0xd8, 0xb1, 0xce, 0x18, 0x6e, 0xd5, 0x06, 0x17, 0x89, 0x21, 0x3d,
+    0x99, 0x39, 0x23, 0x06, 0x6d, 0xdd, 0xaf, 0x10, 0x40, 0xbc, 0x3f, 0xf5, 0x9f, 0x82, 0x5c, 0x78,
+    0xdf, 0x74, 0xf2, 0xd7, 0x54, 0x67, 0xe2, 0x5e, 0x0f, 0x55, 0xf8, 0xa0, 0x0f, 0xa0, 0x30, 0xed,
+    0x0d, 0x1b, 0x3c, 0xc2, 0xc7, 0x02, 0x78, 0x88, 0xbe, 0x51, 0xd9, 0xef, 0x69, 0x1d, 0x77, 0xbc,
+    0xb6, 0x79, 0xaf, 0xda, 0x66, 0xc7, 0x3f, 0x17, 0xf9, 0xee, 0x38, 0x37, 0xa5, 0x50, 0x24, 0xf7,
+    0x8c, 0x71, 0x36, 0x32, 0x75, 0xa7, 0x5d, 0x75, 0xd8, 0x6b, 0xab, 0x79, 0xf7, 0x47, 0x82, 0xaa,
+];
+
+/// `(p - 1) / 2` for the BLS12-381 base field, big-endian.  A y-coordinate
+/// above this is the "larger" root for point compression.
+const HALF_P: [u8; 48] = [
+    0x0d, 0x00, 0x88, 0xf5, 0x1c, 0xbf, 0xf3, 0x4d, 0x25, 0x8d, 0xd3, 0xdb, 0x21, 0xa5, 0xd6, 0x6b,
+    0xb2, 0x3b, 0xa5, 0xc2, 0x79, 0xc2, 0x89, 0x5f, 0xb3, 0x98, 0x69, 0x50, 0x7b, 0x58, 0x7b, 0x12,
+    0x0f, 0x55, 0xff, 0xff, 0x58, 0xa9, 0xff, 0xff, 0xdc, 0xff, 0x7f, 0xff, 0xff, 0xff, 0xd5, 0x55,
+];
+
+/// First round published strictly after `timestamp`.
+pub fn next_round(timestamp: u64) -> u64 {
+    if timestamp < DRAND_GENESIS_TIME {
+        return 1;
+    }
+    (timestamp - DRAND_GENESIS_TIME) / DRAND_PERIOD_SECONDS + 2
+}
+
+/// Verifies a quicknet beacon and returns its compressed signature.
+///
+/// # Arguments
+///
+/// * `round`     – beacon round number
+/// * `signature` – the round's BLS signature as an uncompressed G1 point
+///
+/// # Returns
+///
+/// `Some(compressed_signature)` — whose SHA-256 is the value drand publishes
+/// as the round's `randomness` — or `None` if the signature does not verify.
+pub fn verify_beacon(env: &Env, round: u64, signature: &BytesN<96>) -> Option<Bytes> {
+    let bls = env.crypto().bls12_381();
+
+    let message: BytesN<32> = env
+        .crypto()
+        .sha256(&Bytes::from_array(env, &round.to_be_bytes()))
+        .into();
+    let hashed = bls.hash_to_g1(&message.into(), &Bytes::from_slice(env, DRAND_DST));
+
+    let g1 = Vec::from_array(env, [G1Affine::from_bytes(signature.clone()), hashed]);
+    let g2 = Vec::from_array(
+        env,
+        [
+            G2Affine::from_array(env, &NEG_G2_GENERATOR),
+            G2Affine::from_array(env, &DRAND_PUBLIC_KEY),
+        ],
+    );
+    if !bls.pairing_check(g1, g2) {
+        return None;
+    }
+
+    Some(Bytes::from_array(env, &compress_g1(&signature.to_array())))
+}
+
+/// Converts an uncompressed G1 point to the 48-byte compressed form drand
+/// hashes to derive a round's randomness.
+fn compress_g1(point: &[u8; 96]) -> [u8; 48] {
+    let mut out = [0u8; 48];
+    out.copy_from_slice(&point[..48]);
+    out[0] |= 0x80;
+    if point[48..] > HALF_P[..] {
+        out[0] |= 0x20;
+    }
+    out
+}
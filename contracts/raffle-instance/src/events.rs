@@ -1,9 +1,16 @@
-use raffle_shared::{CancelReason, RandomnessSource, RandomnessType};
-use raffle_shared::{CancelReason, FailureReason, RandomnessSource, RandomnessType};
-use soroban_sdk::{contractevent, Address, BytesN, String, Vec};
+pub use raffle_shared::events::{ContractPaused, ContractUnpaused};
+use raffle_shared::{
+    CancelReason, DisputeResolution, FailureReason, KeeperReward, RandomnessSource, RandomnessType,
+};
+use soroban_sdk::{contractevent, Address, BytesN, String, Symbol, Vec};
+
+use crate::{
+    Compliance, DistributionMode, EntryGate, NewBuyerLimit, PricePeg, TicketWeighting,
+    UnclaimedPrizeRecipient, YieldStrategy,
+};
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "raffle_created"])]
 pub struct RaffleCreated {
     pub raffle_id: Address,
     pub creator: Address,
@@ -20,7 +27,7 @@ pub struct RaffleCreated {
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "prize_deposited"])]
 pub struct PrizeDeposited {
     pub creator: Address,
     pub amount: i128,
@@ -29,7 +36,7 @@ pub struct PrizeDeposited {
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "prize_refunded"])]
 pub struct PrizeRefunded {
     pub creator: Address,
     pub amount: i128,
@@ -38,7 +45,7 @@ pub struct PrizeRefunded {
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "ticket_purchased"])]
 pub struct TicketPurchased {
     pub buyer: Address,
     pub ticket_ids: Vec<u32>,
@@ -46,13 +53,15 @@ pub struct TicketPurchased {
     pub ticket_price: i128,
     pub effective_ticket_price: i128,
     pub total_paid: i128,
+    /// Tip paid to the creator on top of `total_paid`.
+    pub tip: i128,
     pub protocol_fee: i128,
     pub timestamp: u64,
 }
 
-#[allow(dead_code)]
+/// Emitted when a ticket changes owner on the resale market.
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "ticket_transferred"])]
 pub struct TicketTransferred {
     pub ticket_id: u32,
     pub from: Address,
@@ -61,7 +70,7 @@ pub struct TicketTransferred {
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "draw_triggered"])]
 pub struct DrawTriggered {
     pub caller: Address,
     pub total_tickets_sold: u32,
@@ -69,7 +78,7 @@ pub struct DrawTriggered {
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "randomness_requested"])]
 pub struct RandomnessRequested {
     pub oracle: Address,
     pub request_id: u64,
@@ -77,7 +86,7 @@ pub struct RandomnessRequested {
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "randomness_received"])]
 pub struct RandomnessReceived {
     pub oracle: Address,
     pub seed: u64,
@@ -86,11 +95,14 @@ pub struct RandomnessReceived {
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "raffle_finalized"])]
 pub struct RaffleFinalized {
     pub raffle_id: Address,
     pub winners: Vec<Address>,
+    /// `Ticket.id` of each tier's winning ticket.
     pub winning_ticket_ids: Vec<u32>,
+    /// `Ticket.ticket_number` of each tier's winning ticket.
+    pub winning_ticket_numbers: Vec<u32>,
     pub total_tickets_sold: u32,
     pub randomness_source: RandomnessSource,
     pub randomness_type: RandomnessType,
@@ -98,7 +110,7 @@ pub struct RaffleFinalized {
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "winner_drawn"])]
 pub struct WinnerDrawn {
     pub winner: Address,
     pub ticket_id: u32,
@@ -107,7 +119,7 @@ pub struct WinnerDrawn {
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "raffle_cancelled"])]
 pub struct RaffleCancelled {
     pub creator: Address,
     pub reason: CancelReason,
@@ -116,23 +128,8 @@ pub struct RaffleCancelled {
     pub timestamp: u64,
 }
 
-/// Emitted when an admin schedules a cancellation of a raffle that has already
-/// sold tickets. The actual cancel only executes via `execute_admin_cancel`
-/// once `cancel_at` has passed. Ticket holders may refund immediately as soon
-/// as this event is emitted (#406).
-#[derive(Clone)]
-#[contractevent]
-pub struct CancelScheduled {
-    pub creator: Address,
-    pub scheduled_by: Address,
-    pub tickets_sold: u32,
-    /// Unix timestamp at which the cancel becomes executable.
-    pub cancel_at: u64,
-    pub timestamp: u64,
-}
-
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "raffle_failed"])]
 pub struct RaffleFailed {
     pub creator: Address,
     pub reason: FailureReason,
@@ -141,7 +138,7 @@ pub struct RaffleFailed {
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "ticket_refunded"])]
 pub struct TicketRefunded {
     pub buyer: Address,
     pub ticket_number: u32,
@@ -150,9 +147,12 @@ pub struct TicketRefunded {
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "prize_claimed"])]
 pub struct PrizeClaimed {
     pub winner: Address,
+    /// Address the prize was paid to; equals `winner` unless claimed via
+    /// `claim_prize_to`.
+    pub recipient: Address,
     pub tier_index: u32,
     pub payment_token: Address,
     pub gross_amount: i128,
@@ -161,8 +161,30 @@ pub struct PrizeClaimed {
     pub claimed_at: u64,
 }
 
+/// Emitted when a prize tier is claimed on a raffle with a vesting period;
+/// the prize is then released through `withdraw_vested`.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "prize_vesting_started"])]
+pub struct PrizeVestingStarted {
+    pub tier_index: u32,
+    pub recipient: Address,
+    pub total: i128,
+    pub start: u64,
+    pub duration: u64,
+}
+
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "vested_prize_withdrawn"])]
+pub struct VestedPrizeWithdrawn {
+    pub tier_index: u32,
+    pub recipient: Address,
+    pub amount: i128,
+    pub remaining: i128,
+    pub timestamp: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "fees_withdrawn"])]
 pub struct FeesWithdrawn {
     pub recipient: Address,
     pub amount: i128,
@@ -170,8 +192,20 @@ pub struct FeesWithdrawn {
     pub timestamp: u64,
 }
 
+/// Emitted when accrued protocol fees are sent to the treasury; `insurance`
+/// is the part of `amount` routed to the factory's insurance pool.
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "fees_remitted"])]
+pub struct FeesRemitted {
+    pub token: Address,
+    pub treasury: Address,
+    pub amount: i128,
+    pub insurance: i128,
+    pub timestamp: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "randomness_fallback_triggered"])]
 pub struct RandomnessFallbackTriggered {
     pub triggered_by: Address,
     pub seed_used: u64,
@@ -181,7 +215,7 @@ pub struct RandomnessFallbackTriggered {
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "raffle_status_changed"])]
 pub struct RaffleStatusChanged {
     pub old_status: raffle_shared::RaffleStatus,
     pub new_status: raffle_shared::RaffleStatus,
@@ -189,35 +223,21 @@ pub struct RaffleStatusChanged {
 }
 
 #[derive(Clone)]
-#[contractevent]
-pub struct ContractPaused {
-    pub paused_by: Address,
-    pub timestamp: u64,
-}
-
-#[derive(Clone)]
-#[contractevent]
-pub struct ContractUnpaused {
-    pub unpaused_by: Address,
-    pub timestamp: u64,
-}
-
-#[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "ticket_sales_paused"])]
 pub struct TicketSalesPaused {
     pub paused_by: Address,
     pub timestamp: u64,
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "ticket_sales_resumed"])]
 pub struct TicketSalesResumed {
     pub resumed_by: Address,
     pub timestamp: u64,
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "tokens_rescued"])]
 pub struct TokensRescued {
     pub rescued_by: Address,
     pub token: Address,
@@ -227,16 +247,27 @@ pub struct TokensRescued {
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "oracle_address_updated"])]
 pub struct OracleAddressUpdated {
     pub old_oracle: Option<Address>,
     pub new_oracle: Address,
+    pub new_public_key: BytesN<32>,
     pub updated_by: Address,
     pub timestamp: u64,
 }
 
+/// Emitted when the factory pushes a new treasury address to the raffle.
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "treasury_updated"])]
+pub struct TreasuryUpdated {
+    pub old_treasury: Option<Address>,
+    pub new_treasury: Address,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "protocol_fee_updated"])]
 pub struct ProtocolFeeUpdated {
     pub old_fee_bp: u32,
     pub new_fee_bp: u32,
@@ -245,7 +276,7 @@ pub struct ProtocolFeeUpdated {
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "swap_deadline_updated"])]
 pub struct SwapDeadlineUpdated {
     pub old_deadline_seconds: u64,
     pub new_deadline_seconds: u64,
@@ -254,16 +285,39 @@ pub struct SwapDeadlineUpdated {
 }
 
 #[derive(Clone)]
-#[contractevent]
-pub struct EndTimeExtended {
-    pub old_end_time: u64,
-    pub new_end_time: u64,
-    pub extended_by: Address,
+#[contractevent(topics = ["raffle", "oracle_timeout_updated"])]
+pub struct OracleTimeoutUpdated {
+    pub old_timeout_ledgers: u32,
+    pub new_timeout_ledgers: u32,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when the creator edits a raffle's description, end time or
+/// ticket cap before sales start.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "raffle_updated"])]
+pub struct RaffleUpdated {
+    pub description: String,
+    pub end_time: u64,
+    pub max_tickets: u32,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when the creator changes a raffle's category, image or tags.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "raffle_metadata_updated"])]
+pub struct RaffleMetadataUpdated {
+    pub category: Symbol,
+    pub image_uri: String,
+    pub tags: Vec<Symbol>,
+    pub updated_by: Address,
     pub timestamp: u64,
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "emergency_withdrawn"])]
 pub struct EmergencyWithdrawn {
     pub withdrawn_by: Address,
     pub to: Address,
@@ -274,7 +328,7 @@ pub struct EmergencyWithdrawn {
 
 #[allow(dead_code)]
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "admin_changed"])]
 pub struct AdminChanged {
     pub old_admin: Address,
     pub new_admin: Address,
@@ -286,7 +340,7 @@ pub struct AdminChanged {
 /// Emitted once per ticket after an NFT receipt is successfully minted
 /// by the configured `nft_contract`.
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle", "ticket_nft_minted"])]
 pub struct TicketNftMinted {
     /// The address that received the NFT (the ticket buyer).
     pub recipient: Address,
@@ -298,3 +352,327 @@ pub struct TicketNftMinted {
     pub nft_contract: Address,
     pub timestamp: u64,
 }
+
+/// Emitted when the admin names the raffle's arbiter and dispute window.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "dispute_window_updated"])]
+pub struct DisputeWindowUpdated {
+    pub arbiter: Address,
+    pub window_seconds: u64,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when the admin sets how long winners have to claim.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "claim_window_updated"])]
+pub struct ClaimWindowUpdated {
+    pub window_seconds: u64,
+    pub unclaimed_to: UnclaimedPrizeRecipient,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when the prize tiers nobody claimed before the claim deadline
+/// go back to the creator.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "unclaimed_prizes_recovered"])]
+pub struct UnclaimedPrizesRecovered {
+    pub creator: Address,
+    pub tiers: u32,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+/// Emitted when the prize tiers nobody claimed before the claim deadline
+/// are escheated to the treasury.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "unclaimed_prizes_escheated"])]
+pub struct UnclaimedPrizesEscheated {
+    pub treasury: Address,
+    pub tiers: u32,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+/// Emitted when the creator splits the proceeds between co-owners.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "revenue_owners_updated"])]
+pub struct RevenueOwnersUpdated {
+    pub owners: Vec<(Address, u32)>,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted for each owner paid a share of the ticket proceeds.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "proceeds_withdrawn"])]
+pub struct ProceedsWithdrawn {
+    #[topic]
+    pub owner: Address,
+    pub amount: i128,
+    pub token: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when the creator appoints an operator.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "operator_added"])]
+pub struct OperatorAdded {
+    #[topic]
+    pub operator: Address,
+    pub added_by: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when the creator revokes an operator.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "operator_removed"])]
+pub struct OperatorRemoved {
+    #[topic]
+    pub operator: Address,
+    pub removed_by: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when the admin opts purchase tips in or out of the protocol
+/// fee.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "fee_on_tips_updated"])]
+pub struct FeeOnTipsUpdated {
+    pub enabled: bool,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when the admin turns drawing on sell-out on or off.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "auto_finalize_updated"])]
+pub struct AutoFinalizeUpdated {
+    pub enabled: bool,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when the admin sets the bounty paid to finalizing keepers.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "keeper_reward_updated"])]
+pub struct KeeperRewardUpdated {
+    pub reward: KeeperReward,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when a keeper is paid for drawing the raffle's winners.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "keeper_rewarded"])]
+pub struct KeeperRewarded {
+    #[topic]
+    pub keeper: Address,
+    pub amount: i128,
+    pub token: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when the creator sets the royalty taken on ticket resales.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "resale_royalty_updated"])]
+pub struct ResaleRoyaltyUpdated {
+    pub royalty_bp: u32,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when a ticket owner lists a ticket for resale.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "ticket_listed"])]
+pub struct TicketListed {
+    #[topic]
+    pub ticket_id: u32,
+    pub seller: Address,
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+/// Emitted when a seller withdraws a listing.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "listing_cancelled"])]
+pub struct ListingCancelled {
+    #[topic]
+    pub ticket_id: u32,
+    pub seller: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when a listed ticket is bought; `royalty` went to the creator
+/// and the rest of `price` to the seller.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "ticket_resold"])]
+pub struct TicketResold {
+    #[topic]
+    pub ticket_id: u32,
+    pub seller: Address,
+    pub buyer: Address,
+    pub price: i128,
+    pub royalty: i128,
+    pub timestamp: u64,
+}
+
+/// Emitted when the admin sets or clears the prize yield strategy.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "yield_strategy_updated"])]
+pub struct YieldStrategyUpdated {
+    pub config: Option<YieldStrategy>,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when the deposited prize is handed to the yield strategy.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "prize_deployed"])]
+pub struct PrizeDeployed {
+    pub strategy: Address,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+/// Emitted when the prize is recalled from the yield strategy.  The pot
+/// becomes `prize_amount`: yield not sent to the treasury is added to it, a
+/// shortfall is taken out of it.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "prize_recalled"])]
+pub struct PrizeRecalled {
+    pub strategy: Address,
+    pub principal: i128,
+    pub returned: i128,
+    pub to_treasury: i128,
+    pub prize_amount: i128,
+    pub timestamp: u64,
+}
+
+/// Emitted when the creator pegs the ticket price to a reference unit or
+/// removes the peg.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "price_peg_updated"])]
+pub struct PricePegUpdated {
+    pub peg: Option<PricePeg>,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when the creator sets or removes the entry gate.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "entry_gate_updated"])]
+pub struct EntryGateUpdated {
+    pub gate: Option<EntryGate>,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when the creator sets or clears the compliance metadata.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "compliance_updated"])]
+pub struct ComplianceUpdated {
+    pub compliance: Option<Compliance>,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when the creator turns the contract-buyer ban on or off.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "contract_buyers_policy_updated"])]
+pub struct ContractBuyersPolicyUpdated {
+    pub disallowed: bool,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when the creator sets or removes the new-buyer throttle.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "new_buyer_limit_updated"])]
+pub struct NewBuyerLimitUpdated {
+    pub limit: Option<NewBuyerLimit>,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted for each address the creator adds to or removes from the
+/// allowlist.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "allowlist_updated"])]
+pub struct AllowlistUpdated {
+    #[topic]
+    pub entrant: Address,
+    pub allowed: bool,
+    pub timestamp: u64,
+}
+
+/// Emitted when the creator switches between drawn winners and an
+/// everyone-wins distribution.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "distribution_mode_updated"])]
+pub struct DistributionModeUpdated {
+    pub mode: DistributionMode,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted for each holder paid by a `distribute` page.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "prize_share_distributed"])]
+pub struct PrizeShareDistributed {
+    #[topic]
+    pub holder: Address,
+    /// Tickets in this page the holder was paid for.
+    pub tickets: u32,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+/// Emitted when the creator changes how much draw weight tickets carry.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "ticket_weighting_updated"])]
+pub struct TicketWeightingUpdated {
+    pub weighting: TicketWeighting,
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+/// Emitted when the arbiter disputes a finalized draw; claims are frozen
+/// until `DisputeResolved`.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "dispute_opened"])]
+pub struct DisputeOpened {
+    pub arbiter: Address,
+    pub reason: String,
+    pub opened_at: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "dispute_resolved"])]
+pub struct DisputeResolved {
+    pub arbiter: Address,
+    pub resolution: DisputeResolution,
+    pub resolved_at: u64,
+}
+
+/// Emitted when the admin sweeps the ticket revenue buyers never reclaimed
+/// from a cancelled or failed raffle; refunds are closed from then on.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "unclaimed_refunds_swept"])]
+pub struct UnclaimedRefundsSwept {
+    pub recipient: Address,
+    pub tickets: u32,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+/// Emitted when a settled raffle is closed with `close_instance`; the
+/// contract accepts no further calls.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "instance_closed"])]
+pub struct InstanceClosed {
+    pub closed_by: Address,
+    pub status: raffle_shared::RaffleStatus,
+    pub timestamp: u64,
+}
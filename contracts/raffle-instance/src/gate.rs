@@ -0,0 +1,202 @@
+//! Entry gate.
+//!
+//! A creator may restrict who can hold tickets: either to an allowlist they
+//! maintain, or to holders of a minimum balance of some token.  The gate is
+//! checked on every purchase and resale, and is the main anti-spam control
+//! for free-entry raffles.  Independently of the gate, a creator may turn
+//! away buyers that are contracts rather than accounts, and may name an
+//! attestation contract whose `is_eligible(buyer) -> bool` every buyer must
+//! pass (e.g. a KYC or age check for the raffle's `Compliance` region).
+//! Raffles flagged `kyc_required` additionally need buyers verified in the
+//! KYC registry the factory points at.  Contract buyers are always turned
+//! away while auto-finalize is on: a contract buying the last ticket could
+//! revert its own purchase whenever the draw it triggers goes against it.
+
+use soroban_sdk::{address_payload::AddressPayload, token, Address, Env, IntoVal, Symbol, Vec};
+
+use crate::events::{
+    AllowlistUpdated, ComplianceUpdated, ContractBuyersPolicyUpdated, EntryGateUpdated,
+};
+use crate::{Compliance, DataKey, EntryGate, Error};
+
+pub(crate) fn gate(env: &Env) -> Option<EntryGate> {
+    env.storage().instance().get(&DataKey::EntryGate)
+}
+
+pub(crate) fn is_allowlisted(env: &Env, address: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Allowlisted(address.clone()))
+        .unwrap_or(false)
+}
+
+pub(crate) fn set_gate(env: &Env, creator: Address, gate: Option<EntryGate>) -> Result<(), Error> {
+    match gate.clone() {
+        Some(EntryGate::TokenHolders(_, min_balance)) if min_balance <= 0 => {
+            return Err(Error::InvalidParameters);
+        }
+        Some(gate) => env.storage().instance().set(&DataKey::EntryGate, &gate),
+        None => env.storage().instance().remove(&DataKey::EntryGate),
+    }
+    EntryGateUpdated {
+        gate,
+        updated_by: creator,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+    Ok(())
+}
+
+pub(crate) fn set_allowlisted(env: &Env, entrants: Vec<Address>, allowed: bool) {
+    for entrant in entrants.iter() {
+        let key = DataKey::Allowlisted(entrant.clone());
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+        AllowlistUpdated {
+            entrant,
+            allowed,
+            timestamp: env.ledger().timestamp(),
+        }
+        .publish(env);
+    }
+}
+
+pub(crate) fn contract_buyers_disallowed(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&DataKey::DisallowContractBuyers)
+        .unwrap_or(false)
+}
+
+pub(crate) fn set_contract_buyers_disallowed(env: &Env, creator: Address, disallowed: bool) {
+    if disallowed {
+        env.storage()
+            .instance()
+            .set(&DataKey::DisallowContractBuyers, &true);
+    } else {
+        env.storage()
+            .instance()
+            .remove(&DataKey::DisallowContractBuyers);
+    }
+    ContractBuyersPolicyUpdated {
+        disallowed,
+        updated_by: creator,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+}
+
+pub(crate) fn compliance(env: &Env) -> Option<Compliance> {
+    env.storage().instance().get(&DataKey::Compliance)
+}
+
+pub(crate) fn set_compliance(
+    env: &Env,
+    creator: Address,
+    compliance: Option<Compliance>,
+) -> Result<(), Error> {
+    match compliance.clone() {
+        Some(Compliance {
+            region: Some(region),
+            ..
+        }) if region.len() != 2 => {
+            return Err(Error::InvalidParameters);
+        }
+        Some(compliance) => env
+            .storage()
+            .instance()
+            .set(&DataKey::Compliance, &compliance),
+        None => env.storage().instance().remove(&DataKey::Compliance),
+    }
+    ComplianceUpdated {
+        compliance,
+        updated_by: creator,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+    Ok(())
+}
+
+fn ask(env: &Env, contract: &Address, fn_name: &str, address: &Address) -> bool {
+    matches!(
+        env.try_invoke_contract::<bool, soroban_sdk::Error>(
+            contract,
+            &Symbol::new(env, fn_name),
+            (address.clone(),).into_val(env),
+        ),
+        Ok(Ok(true))
+    )
+}
+
+/// The factory's KYC registry, if it has one.
+fn kyc_registry(env: &Env) -> Option<Address> {
+    let factory: Address = env.storage().instance().get(&DataKey::Factory)?;
+    match env.try_invoke_contract::<Option<Address>, soroban_sdk::Error>(
+        &factory,
+        &Symbol::new(env, "get_kyc_registry"),
+        Vec::new(env),
+    ) {
+        Ok(Ok(registry)) => registry,
+        _ => None,
+    }
+}
+
+/// Asks the configured attestation contract, and the KYC registry when the
+/// raffle requires KYC, about `address`.  A call that fails, or a missing
+/// registry, counts as a refusal.
+fn is_attested(env: &Env, address: &Address) -> bool {
+    let Some(compliance) = compliance(env) else {
+        return true;
+    };
+    if let Some(attestation) = compliance.attestation {
+        if !ask(env, &attestation, "is_eligible", address) {
+            return false;
+        }
+    }
+    if compliance.kyc_required {
+        return match kyc_registry(env) {
+            Some(registry) => ask(env, &registry, "is_verified", address),
+            None => false,
+        };
+    }
+    true
+}
+
+fn is_contract(address: &Address) -> bool {
+    matches!(
+        address.to_payload(),
+        Some(AddressPayload::ContractIdHash(_))
+    )
+}
+
+/// Whether `address` passes the gate and the attestation check.  A token
+/// that fails the balance call keeps everyone out rather than letting
+/// everyone in.
+pub(crate) fn is_eligible(env: &Env, address: &Address) -> bool {
+    if !is_attested(env, address) {
+        return false;
+    }
+    match gate(env) {
+        None => true,
+        Some(EntryGate::Allowlist) => is_allowlisted(env, address),
+        Some(EntryGate::TokenHolders(token, min_balance)) => matches!(
+            token::Client::new(env, &token).try_balance(address),
+            Ok(Ok(balance)) if balance >= min_balance
+        ),
+    }
+}
+
+pub(crate) fn require_eligible(env: &Env, address: &Address) -> Result<(), Error> {
+    if (contract_buyers_disallowed(env) || crate::auto_finalize_enabled(env))
+        && is_contract(address)
+    {
+        return Err(Error::ContractBuyerNotAllowed);
+    }
+    if !is_eligible(env, address) {
+        return Err(Error::NotEligible);
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+//! Timing rules of a raffle in one place.
+//!
+//! Clients read `get_lifecycle` instead of re-deriving the sale deadline,
+//! the expiry grace period, the claim lockup and dispute window, and the
+//! claim window, or without one the emergency-withdraw delay, that bounds
+//! claims.
+
+use soroban_sdk::Env;
+
+use raffle_shared::constants::{EMERGENCY_WITHDRAW_DELAY_SECONDS, RAFFLE_EXPIRY_WINDOW_SECONDS};
+use raffle_shared::{LifecyclePhase, RaffleLifecycle, RaffleStatus};
+
+use crate::{claim_deadline, dispute_window_seconds, read_raffle, DataKey, Error};
+
+pub(crate) fn lifecycle(env: &Env) -> Result<RaffleLifecycle, Error> {
+    let raffle = read_raffle(env)?;
+    let now = env.ledger().timestamp();
+
+    let end_condition = raffle.end_condition();
+    let deadline = end_condition.deadline();
+    let sale_time_remaining = deadline.map(|end_time| end_time.saturating_sub(now));
+    let grace_deadline =
+        deadline.map(|end_time| end_time.saturating_add(RAFFLE_EXPIRY_WINDOW_SECONDS));
+    let claims_open_at = raffle
+        .finalized_at
+        .map(|at| at.saturating_add(raffle.claim_lockup_seconds.max(dispute_window_seconds(env))));
+    let claim_deadline = claim_deadline(env, &raffle).or_else(|| {
+        raffle
+            .finalized_at
+            .map(|at| at.saturating_add(EMERGENCY_WITHDRAW_DELAY_SECONDS))
+    });
+
+    let phase = match raffle.status {
+        RaffleStatus::Draft => LifecyclePhase::Draft,
+        RaffleStatus::PendingPrize => LifecyclePhase::AwaitingPrize,
+        RaffleStatus::Active if !raffle.sales_closed_at(now) => LifecyclePhase::Selling,
+        RaffleStatus::Active if grace_deadline.is_some_and(|deadline| now >= deadline) => {
+            LifecyclePhase::Expirable
+        }
+        RaffleStatus::Active => LifecyclePhase::AwaitingFinalization,
+        RaffleStatus::Drawing => LifecyclePhase::Drawing,
+        RaffleStatus::Finalized if claim_deadline.is_some_and(|deadline| now >= deadline) => {
+            LifecyclePhase::ClaimDeadlinePassed
+        }
+        RaffleStatus::Finalized
+            if env.storage().instance().has(&DataKey::DisputeOpen)
+                || claims_open_at.is_some_and(|open| now < open) =>
+        {
+            LifecyclePhase::ClaimLocked
+        }
+        RaffleStatus::Finalized => LifecyclePhase::Claimable,
+        RaffleStatus::Claimed | RaffleStatus::Cancelled | RaffleStatus::Failed => {
+            LifecyclePhase::Closed
+        }
+    };
+
+    Ok(RaffleLifecycle {
+        phase,
+        end_condition,
+        now,
+        sale_time_remaining,
+        grace_deadline,
+        claims_open_at,
+        claim_deadline,
+    })
+}
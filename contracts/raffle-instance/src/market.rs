@@ -0,0 +1,254 @@
+//! Secondary market for tickets.
+//!
+//! While the raffle is `Active`, a ticket owner may list a ticket at a price
+//! in the payment token.  A buyer pays the contract, which forwards the price
+//! to the seller less the creator's resale royalty, and the ticket changes
+//! hands before the draw.  Listings are cleared once the winners are drawn.
+
+use soroban_sdk::{token, Address, Env, IntoVal, Vec};
+
+use raffle_shared::Ticket;
+
+use crate::events::{ListingCancelled, TicketListed, TicketResold, TicketTransferred};
+use crate::{
+    gate, math, notify_factory, participants, read_raffle, require_no_draw_in_progress,
+    require_not_blocked, require_not_paused, require_sales_open, throttle, DataKey, Error,
+    TicketListing,
+};
+
+pub(crate) fn listing(env: &Env, ticket_id: u32) -> Option<TicketListing> {
+    env.storage().persistent().get(&DataKey::Listing(ticket_id))
+}
+
+fn listed_tickets(env: &Env) -> Vec<u32> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::ListedTickets)
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+pub(crate) fn royalty_bp(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::ResaleRoyaltyBp)
+        .unwrap_or(0)
+}
+
+fn remove_listing(env: &Env, ticket_id: u32) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Listing(ticket_id));
+    let mut listed = listed_tickets(env);
+    if let Some(index) = listed.first_index_of(ticket_id) {
+        listed.remove(index);
+        env.storage()
+            .persistent()
+            .set(&DataKey::ListedTickets, &listed);
+    }
+}
+
+/// Resales go through the same checks as primary purchases, so a ticket
+/// cannot change hands while sales are paused or closed or a draw is under
+/// way.
+fn require_trading(env: &Env) -> Result<(), Error> {
+    require_not_paused(env)?;
+    require_no_draw_in_progress(env)?;
+    require_sales_open(env, &read_raffle(env)?)
+}
+
+fn read_ticket(env: &Env, ticket_id: u32) -> Result<Ticket, Error> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Ticket(ticket_id))
+        .ok_or(Error::TicketNotFound)
+}
+
+pub(crate) fn list(env: &Env, seller: Address, ticket_id: u32, price: i128) -> Result<(), Error> {
+    require_trading(env)?;
+    if price <= 0 {
+        return Err(Error::InvalidParameters);
+    }
+    // NFT-backed tickets trade on the NFT contract instead.
+    if read_raffle(env)?.nft_contract.is_some() {
+        return Err(Error::InvalidParameters);
+    }
+    if read_ticket(env, ticket_id)?.owner != seller {
+        return Err(Error::NotAuthorized);
+    }
+
+    let mut listed = listed_tickets(env);
+    if !listed.contains(ticket_id) {
+        listed.push_back(ticket_id);
+        env.storage()
+            .persistent()
+            .set(&DataKey::ListedTickets, &listed);
+    }
+    env.storage().persistent().set(
+        &DataKey::Listing(ticket_id),
+        &TicketListing {
+            seller: seller.clone(),
+            price,
+        },
+    );
+
+    TicketListed {
+        ticket_id,
+        seller,
+        price,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+    Ok(())
+}
+
+pub(crate) fn cancel(env: &Env, seller: Address, ticket_id: u32) -> Result<(), Error> {
+    let current = listing(env, ticket_id).ok_or(Error::TicketNotListed)?;
+    if current.seller != seller {
+        return Err(Error::NotAuthorized);
+    }
+    remove_listing(env, ticket_id);
+
+    ListingCancelled {
+        ticket_id,
+        seller,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+    Ok(())
+}
+
+/// Moves `ticket_id` from `from` to `to`, keeping the per-owner indexes in
+/// step.
+fn move_ticket(env: &Env, mut ticket: Ticket, from: &Address, to: &Address) -> Result<(), Error> {
+    let raffle = read_raffle(env)?;
+    let to_count: u32 = env
+        .storage()
+        .persistent()
+        .get(&DataKey::TicketCount(to.clone()))
+        .unwrap_or(0);
+    if !raffle.allow_multiple && to_count > 0 {
+        return Err(Error::MultipleTicketsNotAllowed);
+    }
+
+    let from_count: u32 = env
+        .storage()
+        .persistent()
+        .get(&DataKey::TicketCount(from.clone()))
+        .unwrap_or(0);
+    env.storage().persistent().set(
+        &DataKey::TicketCount(from.clone()),
+        &from_count.saturating_sub(1),
+    );
+    let mut from_tickets: Vec<u32> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::OwnerTickets(from.clone()))
+        .unwrap_or_else(|| Vec::new(env));
+    if let Some(index) = from_tickets.first_index_of(ticket.id) {
+        from_tickets.remove(index);
+    }
+    env.storage()
+        .persistent()
+        .set(&DataKey::OwnerTickets(from.clone()), &from_tickets);
+
+    let mut buyers = participants(env);
+    if !buyers.contains(to) {
+        throttle::admit_new_buyer(env)?;
+        buyers.push_back(to.clone());
+        env.storage()
+            .persistent()
+            .set(&DataKey::TicketBuyers, &buyers);
+        notify_factory(
+            env,
+            "record_entry",
+            (env.current_contract_address(), to.clone()).into_val(env),
+        );
+    }
+    env.storage().persistent().set(
+        &DataKey::TicketCount(to.clone()),
+        &math::add_u32(to_count, 1)?,
+    );
+    let mut to_tickets: Vec<u32> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::OwnerTickets(to.clone()))
+        .unwrap_or_else(|| Vec::new(env));
+    to_tickets.push_back(ticket.id);
+    env.storage()
+        .persistent()
+        .set(&DataKey::OwnerTickets(to.clone()), &to_tickets);
+
+    ticket.owner = to.clone();
+    env.storage()
+        .persistent()
+        .set(&DataKey::Ticket(ticket.id), &ticket);
+
+    TicketTransferred {
+        ticket_id: ticket.id,
+        from: from.clone(),
+        to: to.clone(),
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+    Ok(())
+}
+
+/// Sells a listed ticket to `buyer`.  Returns the price paid.
+pub(crate) fn buy(env: &Env, buyer: Address, ticket_id: u32) -> Result<i128, Error> {
+    require_trading(env)?;
+    require_not_blocked(env, &buyer)?;
+    gate::require_eligible(env, &buyer)?;
+    let current = listing(env, ticket_id).ok_or(Error::TicketNotListed)?;
+    let ticket = read_ticket(env, ticket_id)?;
+    if buyer == current.seller {
+        return Err(Error::InvalidParameters);
+    }
+    if ticket.owner != current.seller {
+        return Err(Error::TicketNotListed);
+    }
+
+    let raffle = read_raffle(env)?;
+    let royalty = math::bp_floor(current.price, royalty_bp(env))?;
+    let proceeds = math::sub(current.price, royalty)?;
+
+    remove_listing(env, ticket_id);
+    move_ticket(env, ticket, &current.seller, &buyer)?;
+
+    let token_client = token::Client::new(env, &raffle.payment_token);
+    let this = env.current_contract_address();
+    let _ = token_client
+        .try_transfer(&buyer, &this, &current.price)
+        .map_err(|_| Error::TokenTransferFailed)?;
+    if proceeds > 0 {
+        let _ = token_client
+            .try_transfer(&this, &current.seller, &proceeds)
+            .map_err(|_| Error::TokenTransferFailed)?;
+    }
+    if royalty > 0 {
+        let _ = token_client
+            .try_transfer(&this, &raffle.creator, &royalty)
+            .map_err(|_| Error::TokenTransferFailed)?;
+    }
+
+    TicketResold {
+        ticket_id,
+        seller: current.seller,
+        buyer,
+        price: current.price,
+        royalty,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+    Ok(current.price)
+}
+
+/// Drops every open listing.  Called once the winners are drawn and when
+/// the raffle's storage is cleaned up.
+pub(crate) fn clear(env: &Env) {
+    for ticket_id in listed_tickets(env).iter() {
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Listing(ticket_id));
+    }
+    env.storage().persistent().remove(&DataKey::ListedTickets);
+}
@@ -0,0 +1,141 @@
+use soroban_sdk::Vec;
+
+use crate::Error;
+
+// ============================================================================
+// Money math
+// ============================================================================
+//
+// Every i128 token amount the raffle computes goes through this module so the
+// rounding policy is applied the same way everywhere:
+//
+// * fees round **up** — truncation never shortchanges the protocol;
+// * payouts round **down** — the contract never promises more than it holds;
+// * the dust a pool leaves after its rounded-down payouts goes to the
+//   treasury.
+//
+// All operations are overflow-checked and fail with `ArithmeticOverflow`.
+
+/// Basis points in a whole (100 %).
+pub const BP_DENOMINATOR: i128 = 10_000;
+
+pub fn add(a: i128, b: i128) -> Result<i128, Error> {
+    a.checked_add(b).ok_or(Error::ArithmeticOverflow)
+}
+
+pub fn sub(a: i128, b: i128) -> Result<i128, Error> {
+    a.checked_sub(b).ok_or(Error::ArithmeticOverflow)
+}
+
+pub fn mul(a: i128, b: i128) -> Result<i128, Error> {
+    a.checked_mul(b).ok_or(Error::ArithmeticOverflow)
+}
+
+/// Counter arithmetic (ticket IDs, tickets sold, per-buyer counts).
+pub fn add_u32(a: u32, b: u32) -> Result<u32, Error> {
+    a.checked_add(b).ok_or(Error::ArithmeticOverflow)
+}
+
+pub fn sub_u32(a: u32, b: u32) -> Result<u32, Error> {
+    a.checked_sub(b).ok_or(Error::ArithmeticOverflow)
+}
+
+pub fn mul_u32(a: u32, b: u32) -> Result<u32, Error> {
+    a.checked_mul(b).ok_or(Error::ArithmeticOverflow)
+}
+
+/// `amount × bp / 10 000`, rounded down.
+pub fn bp_floor(amount: i128, bp: u32) -> Result<i128, Error> {
+    Ok(mul(amount, bp as i128)? / BP_DENOMINATOR)
+}
+
+/// `amount × bp / 10 000`, rounded up.
+pub fn bp_ceil(amount: i128, bp: u32) -> Result<i128, Error> {
+    let scaled = mul(amount, bp as i128)?;
+    let quotient = scaled / BP_DENOMINATOR;
+    if scaled % BP_DENOMINATOR > 0 {
+        add(quotient, 1)
+    } else {
+        Ok(quotient)
+    }
+}
+
+/// Protocol fee on `amount`; rounds up.
+pub fn fee(amount: i128, fee_bp: u32) -> Result<i128, Error> {
+    bp_ceil(amount, fee_bp)
+}
+
+/// Splits `amount` into `(fee, net)`.
+pub fn split_fee(amount: i128, fee_bp: u32) -> Result<(i128, i128), Error> {
+    let fee = fee(amount, fee_bp)?;
+    Ok((fee, sub(amount, fee)?))
+}
+
+/// Share of `pool` paid to a tier worth `bp`; rounds down.
+pub fn tier_payout(pool: i128, bp: u32) -> Result<i128, Error> {
+    bp_floor(pool, bp)
+}
+
+/// What `pool` leaves over once every tier is paid its rounded-down share.
+pub fn payout_dust(pool: i128, tiers_bp: &Vec<u32>) -> Result<i128, Error> {
+    let mut paid = 0i128;
+    for bp in tiers_bp.iter() {
+        paid = add(paid, tier_payout(pool, bp)?)?;
+    }
+    sub(pool, paid)
+}
+
+/// `price` less a `discount_bp` discount.  The discount is a payout to the
+/// buyer, so it rounds down and the charged price rounds up.
+pub fn discounted_price(price: i128, discount_bp: u32) -> Result<i128, Error> {
+    sub(price, bp_floor(price, discount_bp)?)
+}
+
+/// `a × b / c`, rounded up.  Used for charges, like fees.
+pub fn mul_div_ceil(a: i128, b: i128, c: i128) -> Result<i128, Error> {
+    let scaled = mul(a, b)?;
+    let quotient = scaled.checked_div(c).ok_or(Error::ArithmeticOverflow)?;
+    if scaled % c > 0 {
+        add(quotient, 1)
+    } else {
+        Ok(quotient)
+    }
+}
+
+/// `unit × quantity`.
+pub fn total_price(unit: i128, quantity: u32) -> Result<i128, Error> {
+    mul(unit, quantity as i128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::Env;
+
+    #[test]
+    fn fees_round_up_and_payouts_round_down() {
+        assert_eq!(fee(10_001, 250), Ok(251));
+        assert_eq!(fee(10_000, 250), Ok(250));
+        assert_eq!(tier_payout(10_001, 3_333), Ok(3_333));
+        assert_eq!(split_fee(999, 100), Ok((10, 989)));
+        assert_eq!(discounted_price(999, 1_000), Ok(900));
+    }
+
+    #[test]
+    fn dust_is_the_gap_left_by_rounded_down_tiers() {
+        let env = Env::default();
+        let tiers = Vec::from_array(&env, [3_334u32, 3_333, 3_333]);
+        assert_eq!(payout_dust(100, &tiers), Ok(1));
+        assert_eq!(payout_dust(1_000_000, &tiers), Ok(0));
+    }
+
+    #[test]
+    fn overflow_is_reported() {
+        assert_eq!(mul(i128::MAX, 2), Err(Error::ArithmeticOverflow));
+        assert_eq!(bp_ceil(i128::MAX, 2), Err(Error::ArithmeticOverflow));
+        assert_eq!(add(i128::MAX, 1), Err(Error::ArithmeticOverflow));
+        assert_eq!(add_u32(u32::MAX - 1, 1), Ok(u32::MAX));
+        assert_eq!(add_u32(u32::MAX, 1), Err(Error::ArithmeticOverflow));
+        assert_eq!(mul_u32(u32::MAX, 2), Err(Error::ArithmeticOverflow));
+    }
+}
@@ -1,3 +1,6 @@
+#[cfg(any(test, feature = "std"))]
+extern crate std;
+
 use soroban_sdk::{xdr::ToXdr, Address, Bytes, BytesN, Env, Vec};
 
 // ============================================================================
@@ -89,21 +92,13 @@ pub trait WinnerSelectionStrategy {
 /// **For low-stakes raffles only** — see [`build_internal_seed`] for the full
 /// security caveat.
 pub struct PrngWinnerSelection {
-    _timestamp: u64,
-    _sequence: u32,
     raffle_id: Address,
     tickets_sold: u32,
-    pub timestamp: u64,
-    pub sequence: u32,
-    pub raffle_id: Address,
-    pub tickets_sold: u32,
 }
 
 impl PrngWinnerSelection {
     pub fn new(raffle_id: Address, tickets_sold: u32) -> Self {
         Self {
-            _timestamp: timestamp,
-            _sequence: sequence,
             raffle_id,
             tickets_sold,
         }
@@ -191,7 +186,11 @@ impl OracleSeedWinnerSelection {
     }
 
     #[cfg(any(test, feature = "std"))]
-    pub fn select_winner_indices_pure(&self, total_tickets: u32, winner_count: u32) -> std::vec::Vec<u32> {
+    pub fn select_winner_indices_pure(
+        &self,
+        total_tickets: u32,
+        winner_count: u32,
+    ) -> std::vec::Vec<u32> {
         let mut indices = std::vec::Vec::new();
         if total_tickets == 0 || winner_count == 0 {
             return indices;
@@ -248,14 +247,7 @@ impl WinnerSelectionStrategy for OracleSeedWinnerSelection {
                         .wrapping_mul(6364136223846793005)
                         .wrapping_add(1442695040888963407);
                 };
-                let mut found = false;
-                for i in 0..indices.len() {
-                    if indices.get(i).unwrap() == candidate {
-                        found = true;
-                        break;
-                    }
-                }
-                if !found {
+                if !indices.contains(candidate) {
                     break candidate;
                 }
                 current_seed = current_seed
@@ -272,6 +264,80 @@ impl WinnerSelectionStrategy for OracleSeedWinnerSelection {
     }
 }
 
+/// Draw weight of ticket `id` (1-based) under linear decay: the first
+/// ticket weighs `first_weight` and each later ticket one less, down to a
+/// floor of 1.
+pub fn linear_decay_weight(id: u32, first_weight: u32) -> u32 {
+    first_weight.saturating_sub(id.saturating_sub(1)).max(1)
+}
+
+/// Total linear-decay weight of tickets `1..=count`, in closed form so a
+/// draw never has to load every ticket.
+pub fn linear_decay_cumulative(count: u32, first_weight: u32) -> u64 {
+    let decaying = count.min(first_weight) as u64;
+    let w = first_weight as u64;
+    decaying * w - decaying * (decaying.saturating_sub(1)) / 2 + (count as u64 - decaying)
+}
+
+/// Oracle-seeded selection where each ticket is chosen with probability
+/// proportional to its linear-decay weight.
+///
+/// A sample `r` in `[0, total_weight)` is drawn with the same rejection
+/// sampling as [`OracleSeedWinnerSelection`], and the winner is the first
+/// ticket whose cumulative weight exceeds `r`, found by binary search.
+pub struct WeightedWinnerSelection {
+    seed: u64,
+    first_weight: u32,
+}
+
+impl WeightedWinnerSelection {
+    pub fn new(seed: u64, first_weight: u32) -> Self {
+        Self { seed, first_weight }
+    }
+
+    /// Index (0-based) of the ticket holding cumulative position `r`.
+    fn ticket_at(&self, r: u64, total_tickets: u32) -> u32 {
+        let (mut low, mut high) = (1u32, total_tickets);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if linear_decay_cumulative(mid, self.first_weight) > r {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        low - 1
+    }
+}
+
+impl WinnerSelectionStrategy for WeightedWinnerSelection {
+    fn select_winner_indices(&self, env: &Env, total_tickets: u32, winner_count: u32) -> Vec<u32> {
+        let mut indices = Vec::new(env);
+        if total_tickets == 0 || winner_count == 0 {
+            return indices;
+        }
+
+        let n = linear_decay_cumulative(total_tickets, self.first_weight);
+        let largest_multiple = (u64::MAX / n) * n;
+
+        let effective_count = winner_count.min(total_tickets);
+        let mut current_seed = self.seed;
+        while indices.len() < effective_count {
+            if current_seed < largest_multiple {
+                let candidate = self.ticket_at(current_seed % n, total_tickets);
+                if !indices.contains(candidate) {
+                    indices.push_back(candidate);
+                }
+            }
+            current_seed = current_seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+        }
+
+        indices
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -380,12 +446,10 @@ mod tests {
             .register_stellar_asset_contract_v2(Address::generate(&env))
             .address();
         let first = env.as_contract(&contract_id, || {
-            PrngWinnerSelection::new(raffle_id.clone(), 17)
-                .select_winner_indices(&env, 17, 8)
+            PrngWinnerSelection::new(raffle_id.clone(), 17).select_winner_indices(&env, 17, 8)
         });
         let second = env.as_contract(&contract_id, || {
-            PrngWinnerSelection::new(raffle_id, 17)
-                .select_winner_indices(&env, 17, 8)
+            PrngWinnerSelection::new(raffle_id, 17).select_winner_indices(&env, 17, 8)
         });
 
         assert_eq!(
@@ -436,4 +500,42 @@ mod tests {
             "fingerprints must differ for different ticket counts"
         );
     }
+
+    /// Linear-decay weights fall by one per ticket to a floor of 1, and the
+    /// closed-form cumulative weight matches the running sum.
+    #[test]
+    fn linear_decay_weights_and_cumulative_agree() {
+        assert_eq!(linear_decay_weight(1, 5), 5);
+        assert_eq!(linear_decay_weight(5, 5), 1);
+        assert_eq!(linear_decay_weight(9, 5), 1);
+
+        let mut running = 0u64;
+        for id in 1..=12u32 {
+            running += linear_decay_weight(id, 5) as u64;
+            assert_eq!(linear_decay_cumulative(id, 5), running);
+        }
+    }
+
+    /// Weighted selection returns distinct in-range indices and favours
+    /// early tickets.
+    #[test]
+    fn weighted_selection_favours_early_tickets() {
+        let env = Env::default();
+        let mut first_ticket_wins = 0u32;
+        for seed in 0..200u64 {
+            let indices =
+                WeightedWinnerSelection::new(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15), 10)
+                    .select_winner_indices(&env, 10, 3);
+            assert_eq!(indices.len(), 3);
+            for idx in indices.iter() {
+                assert!(idx < 10);
+                assert_eq!(indices.first_index_of(idx), indices.last_index_of(idx));
+            }
+            if indices.get(0) == Some(0) {
+                first_ticket_wins += 1;
+            }
+        }
+        // Ticket 1 carries 10/55 of the weight versus 1/10 uniformly.
+        assert!(first_ticket_wins > 20);
+    }
 }
@@ -0,0 +1,104 @@
+//! Prize yield strategies.
+//!
+//! When the admin configures a `YieldStrategy`, the deposited prize is
+//! handed to the strategy contract while the raffle runs and recalled when
+//! the winners are drawn or the raffle is cancelled.  Yield is added to the
+//! prize pot or sent to the treasury; a shortfall is taken out of the pot,
+//! so winners are only ever promised what came back.
+
+use soroban_sdk::{token, Env};
+
+use raffle_shared::YieldStrategyClient;
+
+use crate::events::{PrizeDeployed, PrizeRecalled};
+use crate::{math, DataKey, Error, Raffle, YieldStrategy};
+
+pub(crate) fn config(env: &Env) -> Option<YieldStrategy> {
+    env.storage().instance().get(&DataKey::YieldStrategy)
+}
+
+/// Prize currently held by the strategy.
+pub(crate) fn deployed(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::YieldPrincipal)
+        .unwrap_or(0)
+}
+
+/// Hands the freshly deposited prize to the configured strategy.
+pub(crate) fn deploy(env: &Env, raffle: &Raffle) -> Result<(), Error> {
+    let Some(config) = config(env) else {
+        return Ok(());
+    };
+    if deployed(env) > 0 || raffle.prize_amount <= 0 {
+        return Ok(());
+    }
+
+    let this = env.current_contract_address();
+    let _ = token::Client::new(env, &raffle.prize_token)
+        .try_transfer(&this, &config.strategy, &raffle.prize_amount)
+        .map_err(|_| Error::TokenTransferFailed)?;
+    YieldStrategyClient::new(env, &config.strategy).deposit(
+        &this,
+        &raffle.prize_token,
+        &raffle.prize_amount,
+    );
+    env.storage()
+        .instance()
+        .set(&DataKey::YieldPrincipal, &raffle.prize_amount);
+
+    PrizeDeployed {
+        strategy: config.strategy,
+        amount: raffle.prize_amount,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+    Ok(())
+}
+
+/// Pulls everything back from the strategy and settles the prize pot:
+/// yield goes to the pot or the treasury, a shortfall comes out of the pot.
+/// The caller persists `raffle`.
+pub(crate) fn recall(env: &Env, raffle: &mut Raffle) -> Result<(), Error> {
+    let Some(config) = config(env) else {
+        return Ok(());
+    };
+    let principal = deployed(env);
+    if principal == 0 {
+        return Ok(());
+    }
+    env.storage().instance().remove(&DataKey::YieldPrincipal);
+
+    let this = env.current_contract_address();
+    let returned = YieldStrategyClient::new(env, &config.strategy)
+        .withdraw_all(&this, &raffle.prize_token)
+        .max(0);
+
+    let mut to_treasury = 0i128;
+    if returned < principal {
+        let shortfall = math::sub(principal, returned)?;
+        raffle.prize_amount = math::sub(raffle.prize_amount, shortfall)?.max(0);
+    } else {
+        let earned = math::sub(returned, principal)?;
+        match raffle.treasury_address.clone() {
+            Some(treasury) if config.yield_to_treasury && earned > 0 => {
+                let _ = token::Client::new(env, &raffle.prize_token)
+                    .try_transfer(&this, &treasury, &earned)
+                    .map_err(|_| Error::TokenTransferFailed)?;
+                to_treasury = earned;
+            }
+            _ => raffle.prize_amount = math::add(raffle.prize_amount, earned)?,
+        }
+    }
+
+    PrizeRecalled {
+        strategy: config.strategy,
+        principal,
+        returned,
+        to_treasury,
+        prize_amount: raffle.prize_amount,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+    Ok(())
+}
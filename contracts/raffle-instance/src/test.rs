@@ -1,14 +1,26 @@
 #![cfg(test)]
 
+extern crate std;
+
 use super::*;
+use crate::randomness::build_vrf_proof_message;
 use ed25519_dalek::{Signer, SigningKey};
+use raffle_shared::constants::NATIVE_DECIMALS;
+use raffle_shared::{PriceData, DEFAULT_CLAIM_LOCKUP_SECONDS, DEFAULT_SWAP_DEADLINE_SECONDS};
 use soroban_sdk::{
+    contract, contractimpl, symbol_short,
     testutils::{Address as _, Ledger},
     token::StellarAssetClient,
-    xdr::ToXdr,
-    Address, Bytes, BytesN, Env, String,
+    vec, Address, Bytes, BytesN, Env, String, Symbol,
 };
-use raffle_shared::{DEFAULT_CLAIM_LOCKUP_SECONDS, DEFAULT_SWAP_DEADLINE_SECONDS};
+
+fn create_token<'a>(env: &Env, admin: &Address) -> (Address, StellarAssetClient<'a>) {
+    let token_addr = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    let token_mint = StellarAssetClient::new(env, &token_addr);
+    (token_addr, token_mint)
+}
 
 fn assert_drawing_lock_cleared(env: &Env, contract_id: &Address) {
     let is_set: bool = env.as_contract(contract_id, || {
@@ -20,246 +32,357 @@ fn assert_drawing_lock_cleared(env: &Env, contract_id: &Address) {
     assert!(!is_set, "DrawingLock must be cleared");
 }
 
-#[test]
-fn test_oracle_fallback_with_ledger_delays() {
-    let env = Env::default();
-    env.mock_all_auths();
+/// The oracle's Ed25519 signature over the VRF proof message for
+/// `request_id` and `seed`, as `provide_randomness` verifies it.
+fn oracle_signature(
+    env: &Env,
+    signing_key: &SigningKey,
+    contract_id: &Address,
+    request_id: u64,
+    seed: u64,
+) -> BytesN<64> {
+    let message = env.as_contract(contract_id, || {
+        build_vrf_proof_message(env, request_id, seed)
+    });
+    let mut buf = std::vec![0u8; message.len() as usize];
+    message.copy_into_slice(&mut buf);
+    BytesN::from_array(env, &signing_key.sign(&buf).to_bytes())
+}
 
-    // 1. Setup factory, admin, creator
-    let factory = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
-    let oracle = Address::generate(&env);
-    let token_admin = Address::generate(&env);
-    let payment_token = env
-        .register_stellar_asset_contract_v2(token_admin.clone())
-        .address();
-    let token_client = StellarAssetClient::new(&env, &payment_token);
-    token_client.mint(&creator, &100_000_000);
+/// Factory stand-in.  Its blocklist is any address stored as a key of its
+/// instance storage; oracles stored under `("unapproved", oracle)` are off
+/// its oracle allowlist.
+#[contract]
+pub struct MockFactory;
 
-    let contract_id = env.register(RaffleInstance, ());
-    let client = RaffleInstanceClient::new(&env, &contract_id);
+#[contractimpl]
+impl MockFactory {
+    pub fn record_volume(_env: Env, _token: Address, _amount: i128) {}
+    pub fn track_participant(_env: Env, _participant: Address) {}
+    pub fn set_raffle_category(_env: Env, _raffle: Address, _category: Symbol) {}
+    pub fn is_oracle_approved(env: Env, oracle: Address) -> bool {
+        !env.storage()
+            .instance()
+            .has(&(Symbol::new(&env, "unapproved"), oracle))
+    }
+    pub fn is_blocked(env: Env, address: Address) -> bool {
+        env.storage().instance().has(&address)
+    }
+    pub fn set_kyc_registry(env: Env, registry: Address) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("kyc"), &registry);
+    }
+    pub fn get_kyc_registry(env: Env) -> Option<Address> {
+        env.storage().instance().get(&symbol_short!("kyc"))
+    }
+    pub fn set_insurance_route(env: Env, route: InsuranceRoute) {
+        env.storage().instance().set(&symbol_short!("ins"), &route);
+    }
+    pub fn get_insurance_route(env: Env) -> Option<InsuranceRoute> {
+        env.storage().instance().get(&symbol_short!("ins"))
+    }
+    pub fn set_guardian(env: Env, guardian: Address) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("guardian"), &guardian);
+    }
+    pub fn get_guardian(env: Env) -> Option<Address> {
+        env.storage().instance().get(&symbol_short!("guardian"))
+    }
+}
 
-    // 2. Initialize Raffle with External Randomness
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Test Raffle"),
+fn test_raffle_config(env: &Env, payment_token: &Address) -> RaffleConfig {
+    RaffleConfig {
+        description: String::from_str(env, "Test Raffle"),
         end_time: 0,
         no_deadline: true,
         max_tickets: 10,
         max_tickets_per_tx: 10,
         min_tickets: 1,
         allow_multiple: true,
-        ticket_price: 10_000,
+        ticket_price: MIN_TICKET_PRICE,
         payment_token: payment_token.clone(),
-        prize_amount: 10_000,
-        prizes: soroban_sdk::vec![&env, 10000],
-        randomness_source: RandomnessSource::External,
-        oracle_address: Some(oracle.clone()),
+        prize_amount: MIN_TICKET_PRICE * 10,
+        prizes: vec![env, 10000u32],
+        randomness_source: RandomnessSource::Internal,
+        oracle_address: None,
+        oracle_public_key: None,
+        auto_distribute: false,
         protocol_fee_bp: 0,
         treasury_address: None,
         swap_router: None,
         tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[1; 32]),
+        metadata_hash: BytesN::from_array(env, &[1u8; 32]),
         claim_lockup_seconds: 0,
+        prize_vesting_seconds: 0,
+        proceeds_vesting_seconds: 0,
         swap_deadline_seconds: 0,
         early_bird_ticket_percentage: 0,
         early_bird_discount_bp: 0,
-    };
+        nft_contract: None,
+        category: None,
+    }
+}
+
+/// `test_raffle_config` drawn by an external oracle.
+fn external_config(env: &Env, payment_token: &Address, oracle: &Address) -> RaffleConfig {
+    let mut config = test_raffle_config(env, payment_token);
+    config.randomness_source = RandomnessSource::External;
+    config.oracle_address = Some(oracle.clone());
+    config.oracle_public_key = Some(BytesN::from_array(env, &[7u8; 32]));
+    config
+}
+
+/// A raffle initialized with `config`, with the creator funded for its
+/// prize.  Returns the contract id, its client and the admin and creator.
+fn init_raffle<'a>(
+    env: &Env,
+    config: &RaffleConfig,
+) -> (Address, ContractClient<'a>, Address, Address) {
+    let contract_id = env.register(Contract, ());
+    let client = ContractClient::new(env, &contract_id);
+    let factory = env.register(MockFactory, ());
+    let admin = Address::generate(env);
+    let creator = Address::generate(env);
+    StellarAssetClient::new(env, &config.payment_token).mint(&creator, &10_000_000);
+
+    client.init(&factory, &admin, &creator, config);
+    (contract_id, client, admin, creator)
+}
+
+fn setup_active_raffle(
+    env: &Env,
+) -> (
+    ContractClient<'_>,
+    Address,
+    Address,
+    Address,
+    Address,
+    token::StellarAssetClient<'_>,
+) {
+    setup_active_raffle_with(env, |config| {
+        config.description = String::from_str(env, "ticket sales pause");
+        config.max_tickets = 1;
+        config.max_tickets_per_tx = 1;
+        config.prize_amount = MIN_TICKET_PRICE * 100;
+        config.metadata_hash = BytesN::from_array(env, &[7u8; 32]);
+    })
+}
+
+/// A raffle with its prize deposited, initialised from `test_raffle_config`
+/// after `configure` has adjusted it.  The creator and buyer are funded.
+/// Returns the client, admin, creator, buyer, factory and token minter.
+fn setup_active_raffle_with(
+    env: &Env,
+    configure: impl FnOnce(&mut RaffleConfig),
+) -> (
+    ContractClient<'_>,
+    Address,
+    Address,
+    Address,
+    Address,
+    token::StellarAssetClient<'_>,
+) {
+    let contract_id = env.register(Contract, ());
+    let client = ContractClient::new(env, &contract_id);
+
+    let factory = env.register(MockFactory, ());
+    let admin = Address::generate(env);
+    let creator = Address::generate(env);
+    let buyer = Address::generate(env);
+
+    let token_admin = Address::generate(env);
+    let (token_addr, token_mint) = create_token(env, &token_admin);
+    token_mint.mint(&creator, &1_000_000);
+    token_mint.mint(&buyer, &1_000_000);
+
+    let mut config = test_raffle_config(env, &token_addr);
+    configure(&mut config);
 
     client.init(&factory, &admin, &creator, &config);
+    client.deposit_prize();
 
-    // Verify that defaults were resolved (0 values replaced with defaults)
-    let raffle = client.get_raffle();
-    assert_eq!(raffle.claim_lockup_seconds, DEFAULT_CLAIM_LOCKUP_SECONDS);
-    assert_eq!(raffle.swap_deadline_seconds, DEFAULT_SWAP_DEADLINE_SECONDS);
+    (client, admin, creator, buyer, factory, token_mint)
+}
 
-    // Remove factory from storage so buy_tickets skips the factory code path
-    env.as_contract(&contract_id, || {
-        env.storage().instance().remove(&DataKey::Factory);
-    });
+/// An external-randomness raffle that sold its only ticket and is waiting
+/// for the oracle.  Returns the contract id, its client, the creator and
+/// the ledger sequence the randomness was requested at.
+fn setup_external_drawing_raffle(env: &Env) -> (Address, ContractClient<'_>, Address, u32) {
+    let (token_addr, _) = create_token(env, &Address::generate(env));
+    let mut config = external_config(env, &token_addr, &Address::generate(env));
+    config.max_tickets = 1;
+    config.max_tickets_per_tx = 1;
+    let (contract_id, client, _admin, creator) = init_raffle(env, &config);
 
-    // 3. Deposit prize and buy ticket
     client.deposit_prize();
-    client.buy_tickets(&creator, &10);
+    let buyer = Address::generate(env);
+    StellarAssetClient::new(env, &token_addr).mint(&buyer, &MIN_TICKET_PRICE);
+    client.buy_tickets(&buyer, &1, &0);
+    assert_eq!(client.get_raffle().status, RaffleStatus::Drawing);
 
-    // 4. Finalize raffle (requests randomness)
-    client.finalize_raffle();
+    (contract_id, client, creator, env.ledger().sequence())
+}
+
+#[test]
+fn test_oracle_fallback_with_ledger_delays() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (token_addr, _) = create_token(&env, &Address::generate(&env));
+    let config = external_config(&env, &token_addr, &Address::generate(&env));
+    let (_contract_id, client, _admin, creator) = init_raffle(&env, &config);
 
-    // 5. Ensure it's in Drawing state and requested randomness
+    // Defaults are resolved at init.
     let raffle = client.get_raffle();
-    assert_eq!(raffle.status, RaffleStatus::Drawing);
+    assert_eq!(raffle.claim_lockup_seconds, DEFAULT_CLAIM_LOCKUP_SECONDS);
+    assert_eq!(raffle.swap_deadline_seconds, DEFAULT_SWAP_DEADLINE_SECONDS);
+
+    client.deposit_prize();
+    client.buy_tickets(&creator, &10, &0);
+    assert_eq!(client.get_raffle().status, RaffleStatus::Drawing);
 
-    // 6. Attempt fallback too early
-    let result = client.try_trigger_randomness_fallback(&creator, &false);
-    assert_eq!(result.err(), Some(Ok(Error::FallbackTooEarly)));
+    assert_eq!(
+        client.try_trigger_randomness_fallback(&creator, &false),
+        Err(Ok(Error::FallbackTooEarly))
+    );
 
-    // 7. Simulate ledger delays
     env.ledger().with_mut(|l| {
         l.sequence_number += ORACLE_TIMEOUT_LEDGERS + 1;
-        l.timestamp += 86400; // 1 day
+        l.timestamp += 86_400;
     });
-
-    // 8. Trigger fallback successfully (no refund — finalize)
     client.trigger_randomness_fallback(&creator, &false);
 
-    // 9. Verify finalized state
-    let raffle_after = client.get_raffle();
-    assert_eq!(raffle_after.status, RaffleStatus::Finalized);
-
-    // We can also verify the fairness data
+    assert_eq!(client.get_raffle().status, RaffleStatus::Finalized);
     let fairness = client.get_fairness_data();
     assert_eq!(fairness.randomness_source, RandomnessSource::External);
 }
 
 #[test]
-fn test_admin_updates_oracle_address() {
+fn init_accepts_only_oracles_the_factory_approves() {
     let env = Env::default();
     env.mock_all_auths();
 
-    let factory = Address::generate(&env);
+    let (token_addr, _) = create_token(&env, &Address::generate(&env));
+    let factory = env.register(MockFactory, ());
+    let approved = Address::generate(&env);
+    let unapproved = Address::generate(&env);
+    env.as_contract(&factory, || {
+        env.storage().instance().set(
+            &(Symbol::new(&env, "unapproved"), unapproved.clone()),
+            &true,
+        );
+    });
     let admin = Address::generate(&env);
     let creator = Address::generate(&env);
-    let oracle = Address::generate(&env);
-    let new_oracle = Address::generate(&env);
-
-    let contract_id = env.register(RaffleInstance, ());
-    let client = RaffleInstanceClient::new(&env, &contract_id);
-
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Oracle migration"),
-        end_time: 0,
-        no_deadline: true,
-        max_tickets: 5,
-        max_tickets_per_tx: 5,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: env
-            .register_stellar_asset_contract_v2(Address::generate(&env))
-            .address(),
-        prize_amount: MIN_TICKET_PRICE * 5,
-        prizes: soroban_sdk::vec![&env, 10000],
-        randomness_source: RandomnessSource::External,
-        oracle_address: Some(oracle.clone()),
-        protocol_fee_bp: 100,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[2; 32]),
-        claim_lockup_seconds: 0,
-        swap_deadline_seconds: 0,
-        early_bird_ticket_percentage: 0,
-        early_bird_discount_bp: 0,
-    };
-
-    client.init(&factory, &admin, &creator, &config);
 
-    // Verify that defaults were resolved
-    let raffle = client.get_raffle();
-    assert_eq!(raffle.claim_lockup_seconds, DEFAULT_CLAIM_LOCKUP_SECONDS);
-    assert_eq!(raffle.swap_deadline_seconds, DEFAULT_SWAP_DEADLINE_SECONDS);
+    let rejected = ContractClient::new(&env, &env.register(Contract, ()));
+    let config = external_config(&env, &token_addr, &unapproved);
+    assert_eq!(
+        rejected.try_init(&factory, &admin, &creator, &config),
+        Err(Ok(Error::OracleNotApproved))
+    );
 
-#[contractimpl]
-impl MockFactory {
-    pub fn record_volume(_env: Env, _token: Address, _amount: i128) {}
-    pub fn track_participant(_env: Env, _participant: Address) {}
+    let accepted = ContractClient::new(&env, &env.register(Contract, ()));
+    let config = external_config(&env, &token_addr, &approved);
+    accepted.init(&factory, &admin, &creator, &config);
+    assert_eq!(accepted.get_raffle().oracle_address, Some(approved));
+    assert_eq!(
+        accepted.try_update_oracle_address(&unapproved, &BytesN::from_array(&env, &[8u8; 32])),
+        Err(Ok(Error::OracleNotApproved))
+    );
 }
 
 #[test]
-fn non_winner_cannot_claim() {
+fn test_admin_rotates_the_oracle_and_its_key() {
     let env = Env::default();
     env.mock_all_auths();
-    env.ledger().set_timestamp(1_000);
-
-    let contract_id = env.register(RaffleInstance, ());
-    let client = RaffleInstanceClient::new(&env, &contract_id);
 
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Fee update"),
-        end_time: 0,
-        no_deadline: true,
-        max_tickets: 5,
-        max_tickets_per_tx: 5,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: env
-            .register_stellar_asset_contract_v2(Address::generate(&env))
-            .address(),
-        prize_amount: MIN_TICKET_PRICE * 5,
-        prizes: soroban_sdk::vec![&env, 10000],
-        randomness_source: RandomnessSource::Internal,
-        oracle_address: None,
-        protocol_fee_bp: 100,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[3; 32]),
-        claim_lockup_seconds: 0,
-        swap_deadline_seconds: 0,
-        early_bird_ticket_percentage: 0,
-        early_bird_discount_bp: 0,
-    };
+    let old_key = SigningKey::from_bytes(&[5u8; 32]);
+    let new_key = SigningKey::from_bytes(&[6u8; 32]);
+    let (token_addr, _) = create_token(&env, &Address::generate(&env));
+    let mut config = external_config(&env, &token_addr, &Address::generate(&env));
+    config.max_tickets = 1;
+    config.max_tickets_per_tx = 1;
+    config.oracle_public_key = Some(BytesN::from_array(
+        &env,
+        &old_key.verifying_key().to_bytes(),
+    ));
+    let (contract_id, client, _admin, creator) = init_raffle(&env, &config);
 
-    client.init(&factory, &admin, &creator, &config);
+    client.deposit_prize();
+    client.buy_tickets(&creator, &1, &0);
+    let request_id = client.get_randomness_request().unwrap().request_id;
 
-    // Verify that defaults were resolved
+    let new_oracle = Address::generate(&env);
+    let new_public_key = BytesN::from_array(&env, &new_key.verifying_key().to_bytes());
+    client.update_oracle_address(&new_oracle, &new_public_key);
     let raffle = client.get_raffle();
-    assert_eq!(raffle.claim_lockup_seconds, DEFAULT_CLAIM_LOCKUP_SECONDS);
-    assert_eq!(raffle.swap_deadline_seconds, DEFAULT_SWAP_DEADLINE_SECONDS);
+    assert_eq!(raffle.oracle_address, Some(new_oracle));
+    assert_eq!(raffle.oracle_public_key, Some(new_public_key));
+
+    let stale = oracle_signature(&env, &old_key, &contract_id, request_id, 424_242);
+    assert!(client
+        .try_provide_randomness(&424_242, &stale, &request_id)
+        .is_err());
+    let proof = oracle_signature(&env, &new_key, &contract_id, request_id, 424_242);
+    client.provide_randomness(&424_242, &proof, &request_id);
+    assert_eq!(client.get_raffle().status, RaffleStatus::Finalized);
+
+    // The seed of a VRF draw must be the one in the signed oracle message.
+    let proof = client.get_draw_proof().unwrap();
+    assert_eq!(proof.randomness_type, RandomnessType::Vrf);
+    assert!(client.verify_draw(&proof));
+    let mut forged = proof;
+    forged.seed = forged.seed.wrapping_add(1);
+    assert!(!client.verify_draw(&forged));
+}
+
+#[test]
+fn test_admin_updates_protocol_fee_before_sales() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (token_addr, _) = create_token(&env, &Address::generate(&env));
+    let mut config = test_raffle_config(&env, &token_addr);
+    config.protocol_fee_bp = 100;
+    let (_contract_id, client, _admin, creator) = init_raffle(&env, &config);
 
     client.set_protocol_fee_bp(&500);
+    assert_eq!(client.get_raffle().protocol_fee_bp, 500);
+    assert_eq!(
+        client.try_set_protocol_fee_bp(&(MAX_PROTOCOL_FEE_BP + 1)),
+        Err(Ok(Error::InvalidFee))
+    );
 
-    let raffle = client.get_raffle();
-    assert_eq!(raffle.protocol_fee_bp, 500);
+    client.deposit_prize();
+    client.buy_tickets(&creator, &1, &0);
+    assert_eq!(
+        client.try_set_protocol_fee_bp(&100),
+        Err(Ok(Error::InvalidStatus))
+    );
 }
 
 #[test]
-fn test_admin_withdraws_accumulated_fees() {
+fn non_winner_cannot_claim() {
     let env = Env::default();
     env.mock_all_auths();
     env.ledger().set_timestamp(1_000);
 
-    let factory = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
+    let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+    let mut config = test_raffle_config(&env, &token_addr);
+    config.end_time = 2_000;
+    config.no_deadline = false;
+    config.max_tickets = 2;
+    config.max_tickets_per_tx = 2;
+    let (_contract_id, client, _admin, _creator) = init_raffle(&env, &config);
+
     let buyer = Address::generate(&env);
     let attacker = Address::generate(&env);
-
-    let token_admin = Address::generate(&env);
-    let (token_addr, token_mint) = create_token(&env, &token_admin);
-    token_mint.mint(&creator, &1_000_000);
-    token_mint.mint(&buyer, &1_000_000);
-
-    let config = RaffleConfig {
-        description: String::from_str(&env, "test raffle"),
-        end_time: 2_000,
-        no_deadline: false,
-        max_tickets: 2,
-        max_tickets_per_tx: 2,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: token_addr.clone(),
-        prize_amount: MIN_TICKET_PRICE * 10,
-        prizes: vec![&env, 10000u32],
-        randomness_source: RandomnessSource::Internal,
-        oracle_address: None,
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[1u8; 32]),
-        claim_lockup_seconds: 0,
-        swap_deadline_seconds: 0,
-        early_bird_ticket_percentage: 0,
-        early_bird_discount_bp: 0,
-    };
-
-    client.init(&factory, &admin, &creator, &config);
+    token_mint.mint(&buyer, &MIN_TICKET_PRICE);
     client.deposit_prize();
-    client.buy_tickets(&buyer, &1);
-    env.ledger().set_timestamp(2_000);
+    client.buy_tickets(&buyer, &1, &0);
     env.ledger().set_timestamp(2_000);
     client.finalize_raffle();
 
@@ -267,10 +390,12 @@ fn test_admin_withdraws_accumulated_fees() {
     assert_eq!(raffle.winners.len(), 1);
     assert!(raffle.winners.get(0).unwrap() != attacker);
 
-    env.ledger().set_timestamp(2_000 + DEFAULT_CLAIM_LOCKUP_SECONDS + 1);
-
-    let result = client.try_claim_prize(&attacker, &0u32);
-    assert_eq!(result, Err(Ok(Error::NotWinner)));
+    env.ledger()
+        .set_timestamp(2_000 + DEFAULT_CLAIM_LOCKUP_SECONDS + 1);
+    assert_eq!(
+        client.try_claim_prize(&attacker, &0u32),
+        Err(Ok(Error::NotWinner))
+    );
 }
 
 #[test]
@@ -279,106 +404,22 @@ fn buy_tickets_rejects_quantity_above_per_tx_cap() {
     env.mock_all_auths();
     env.ledger().set_timestamp(1_000);
 
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(&env, &contract_id);
+    let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+    let mut config = test_raffle_config(&env, &token_addr);
+    config.max_tickets = 100;
+    config.max_tickets_per_tx = 5;
+    config.prize_amount = MIN_TICKET_PRICE * 100;
+    let (_contract_id, client, _admin, _creator) = init_raffle(&env, &config);
 
-    let factory = env.register(MockFactory, ());
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
     let buyer = Address::generate(&env);
-
-    let token_admin = Address::generate(&env);
-    let (token_addr, token_mint) = create_token(&env, &token_admin);
-    token_mint.mint(&creator, &1_000_000);
     token_mint.mint(&buyer, &1_000_000);
-
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Per-tx cap"),
-        end_time: 0,
-        no_deadline: true,
-        max_tickets: 100,
-        max_tickets_per_tx: 5,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: token_addr.clone(),
-        prize_amount: MIN_TICKET_PRICE * 100,
-        prizes: vec![&env, 10000u32],
-        randomness_source: RandomnessSource::Internal,
-        oracle_address: None,
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[5u8; 32]),
-        claim_lockup_seconds: 0,
-        swap_deadline_seconds: 0,
-        early_bird_ticket_percentage: 0,
-        early_bird_discount_bp: 0,
-    };
-
-    client.init(&factory, &admin, &creator, &config);
     client.deposit_prize();
 
     assert_eq!(
-        client.try_buy_tickets(&buyer, &6),
+        client.try_buy_tickets(&buyer, &6, &0),
         Err(Ok(Error::ExceedsMaxTicketsPerTx))
     );
-    assert_eq!(client.buy_tickets(&buyer, &5), 5);
-}
-
-fn setup_active_raffle(
-    env: &Env,
-) -> (
-    ContractClient<'_>,
-    Address,
-    Address,
-    Address,
-    Address,
-    token::StellarAssetClient<'_>,
-) {
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(env, &contract_id);
-
-    let factory = env.register(MockFactory, ());
-    let admin = Address::generate(env);
-    let creator = Address::generate(env);
-    let buyer = Address::generate(env);
-
-    let token_admin = Address::generate(env);
-    let (token_addr, token_mint) = create_token(env, &token_admin);
-    token_mint.mint(&creator, &1_000_000);
-    token_mint.mint(&buyer, &1_000_000);
-
-    let config = RaffleConfig {
-        description: String::from_str(env, "ticket sales pause"),
-        end_time: 0,
-        no_deadline: true,
-        max_tickets: 1,
-        max_tickets_per_tx: 1,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: token_addr,
-        prize_amount: MIN_TICKET_PRICE * 100,
-        prizes: vec![env, 10000u32],
-        randomness_source: RandomnessSource::Internal,
-        oracle_address: None,
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(env, &[7u8; 32]),
-        claim_lockup_seconds: 0,
-        swap_deadline_seconds: 0,
-        early_bird_ticket_percentage: 0,
-        early_bird_discount_bp: 0,
-    };
-
-    client.init(&factory, &admin, &creator, &config);
-    client.deposit_prize();
-
-    (client, admin, creator, buyer, factory, token_mint)
+    assert_eq!(client.buy_tickets(&buyer, &5, &0), 5);
 }
 
 #[test]
@@ -392,37 +433,17 @@ fn pause_resume_ticket_sales_controls_buy_tickets() {
     assert_eq!(client.get_raffle().status, RaffleStatus::Active);
     assert!(!client.is_ticket_sales_paused());
 
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Rollback test"),
-        end_time: 0,
-        no_deadline: true,
-        max_tickets: 1,
-        max_tickets_per_tx: 1,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: payment_token.clone(),
-        prize_amount: MIN_TICKET_PRICE,
-        prizes: soroban_sdk::vec![&env, 10000],
-        randomness_source: RandomnessSource::External,
-        oracle_address: Some(Address::generate(&env)),
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[8; 32]),
-        claim_lockup_seconds: 0,
-        swap_deadline_seconds: 0,
-        early_bird_ticket_percentage: 0,
-        early_bird_discount_bp: 0,
-    };
-
-    client.init(&factory, &admin, &creator, &config);
+    client.pause_ticket_sales(&creator);
+    assert!(client.is_ticket_sales_paused());
+    assert_eq!(
+        client.try_buy_tickets(&buyer, &1, &0),
+        Err(Ok(Error::ContractPaused))
+    );
 
     client.resume_ticket_sales(&creator);
     assert!(!client.is_ticket_sales_paused());
     assert_eq!(client.get_raffle().status, RaffleStatus::Active);
-    assert_eq!(client.buy_tickets(&buyer, &1), 1);
+    assert_eq!(client.buy_tickets(&buyer, &1, &0), 1);
 }
 
 #[test]
@@ -436,13 +457,13 @@ fn admin_can_pause_and_resume_ticket_sales() {
     client.pause_ticket_sales(&admin);
     assert!(client.is_ticket_sales_paused());
     assert_eq!(
-        client.try_buy_tickets(&buyer, &1),
+        client.try_buy_tickets(&buyer, &1, &0),
         Err(Ok(Error::ContractPaused))
     );
 
     client.resume_ticket_sales(&admin);
     assert!(!client.is_ticket_sales_paused());
-    assert_eq!(client.buy_tickets(&buyer, &1), 1);
+    assert_eq!(client.buy_tickets(&buyer, &1, &0), 1);
 }
 
 #[test]
@@ -451,53 +472,18 @@ fn test_wipe_storage_removes_all_keys() {
     env.mock_all_auths();
     env.ledger().set_timestamp(1_000);
 
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(&env, &contract_id);
-
-    let factory = env.register(MockFactory, ());
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
+    let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+    let (contract_id, client, _admin, _creator) =
+        init_raffle(&env, &test_raffle_config(&env, &token_addr));
     let buyer_a = Address::generate(&env);
     let buyer_b = Address::generate(&env);
-
-    let token_admin = Address::generate(&env);
-    let (token_addr, token_mint) = create_token(&env, &token_admin);
-    token_mint.mint(&creator, &1_000_000);
     token_mint.mint(&buyer_a, &1_000_000);
     token_mint.mint(&buyer_b, &1_000_000);
 
-    let config = RaffleConfig {
-        description: String::from_str(&env, "wipe test"),
-        end_time: 0,
-        no_deadline: true,
-        max_tickets: 10,
-        max_tickets_per_tx: 10,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: token_addr,
-        prize_amount: MIN_TICKET_PRICE * 10,
-        prizes: vec![&env, 10000u32],
-        randomness_source: RandomnessSource::Internal,
-        oracle_address: None,
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[9u8; 32]),
-        claim_lockup_seconds: 0,
-        swap_deadline_seconds: 0,
-        early_bird_ticket_percentage: 0,
-        early_bird_discount_bp: 0,
-    };
-
-    client.init(&factory, &admin, &creator, &config);
     client.deposit_prize();
-    client.buy_tickets(&buyer_a, &3);
-    client.buy_tickets(&buyer_b, &2);
-
+    client.buy_tickets(&buyer_a, &3, &0);
+    client.buy_tickets(&buyer_b, &2, &0);
     client.cancel_raffle(&CancelReason::AdminCancelled);
-
     assert_eq!(client.get_raffle().status, RaffleStatus::Cancelled);
 
     client.wipe_storage();
@@ -508,17 +494,25 @@ fn test_wipe_storage_removes_all_keys() {
             assert!(!env.storage().persistent().has(&DataKey::TicketRefunded(i)));
             assert!(!env.storage().persistent().has(&DataKey::CommitEntry(i)));
         }
-        assert!(!env.storage().persistent().has(&DataKey::TicketCount(buyer_a.clone())));
-        assert!(!env.storage().persistent().has(&DataKey::TicketCount(buyer_b.clone())));
+        assert!(!env
+            .storage()
+            .persistent()
+            .has(&DataKey::TicketCount(buyer_a.clone())));
+        assert!(!env
+            .storage()
+            .persistent()
+            .has(&DataKey::TicketCount(buyer_b.clone())));
         assert!(!env.storage().persistent().has(&DataKey::TicketBuyers));
         assert!(!env.storage().instance().has(&DataKey::Raffle));
         assert!(!env.storage().instance().has(&DataKey::Factory));
         assert!(!env.storage().instance().has(&DataKey::Admin));
         assert!(!env.storage().instance().has(&DataKey::Paused));
         assert!(!env.storage().instance().has(&DataKey::ReentrancyGuard));
-        assert!(!env.storage().instance().has(&DataKey::AccumulatedFees));
         assert!(!env.storage().instance().has(&DataKey::RandomnessRequested));
-        assert!(!env.storage().instance().has(&DataKey::RandomnessRequestLedger));
+        assert!(!env
+            .storage()
+            .instance()
+            .has(&DataKey::RandomnessRequestLedger));
         assert!(!env.storage().instance().has(&DataKey::RandomnessRequestId));
         assert!(!env.storage().instance().has(&DataKey::DrawingLock));
         assert!(!env.storage().instance().has(&DataKey::FinishTime));
@@ -528,104 +522,102 @@ fn test_wipe_storage_removes_all_keys() {
 }
 
 #[test]
-fn emergency_withdraw_fails_before_delay_in_finalized_state() {
 fn test_refund_ticket_after_cancel() {
     let env = Env::default();
     env.mock_all_auths();
     env.ledger().set_timestamp(1_000);
 
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(&env, &contract_id);
-
-    let factory = env.register(MockFactory, ());
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
-    let token_admin = Address::generate(&env);
-    let payment_token = env
-        .register_stellar_asset_contract_v2(token_admin.clone())
-        .address();
-    let token_client = StellarAssetClient::new(&env, &payment_token);
-    token_client.mint(&creator, &1_000_000);
-
-    let contract_id = env.register(RaffleInstance, ());
-    let client = RaffleInstanceClient::new(&env, &contract_id);
-
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Test"),
-        end_time: 2_000,
-        no_deadline: false,
-        max_tickets: 1,
-        max_tickets_per_tx: 1,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: payment_token.clone(),
-        prize_amount: MIN_TICKET_PRICE * 10,
-        prizes: soroban_sdk::vec![&env, 10000],
-        randomness_source: RandomnessSource::Internal,
-        oracle_address: None,
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[9; 32]),
-        claim_lockup_seconds: 0,
-    };
+    let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+    let (_contract_id, client, _admin, _creator) =
+        init_raffle(&env, &test_raffle_config(&env, &token_addr));
+    let buyer = Address::generate(&env);
+    token_mint.mint(&buyer, &1_000_000);
 
-    client.init(&factory, &admin, &creator, &config);
     client.deposit_prize();
-    client.buy_tickets(&creator, &1);
-    client.finalize_raffle();
+    client.buy_tickets(&buyer, &1, &0);
 
-    let result = client.try_emergency_withdraw(&creator);
-    assert_eq!(result.err(), Some(Ok(Error::EmergencyTooEarly)));
+    let token = token::Client::new(&env, &token_addr);
+    let balance_before = token.balance(&buyer);
+    client.cancel_raffle(&CancelReason::CreatorCancelled);
+
+    assert_eq!(client.refund_ticket(&1), MIN_TICKET_PRICE);
+    assert_eq!(token.balance(&buyer), balance_before + MIN_TICKET_PRICE);
+    assert_eq!(
+        client.try_refund_ticket(&1),
+        Err(Ok(Error::PrizeAlreadyClaimed))
+    );
 }
 
 #[test]
-fn emergency_withdraw_succeeds_after_delay_in_finalized_state() {
+fn test_refund_guard_released_after_success() {
     let env = Env::default();
     env.mock_all_auths();
     env.ledger().set_timestamp(1_000);
 
-    let factory = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
+    let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+    let (contract_id, client, _admin, _creator) =
+        init_raffle(&env, &test_raffle_config(&env, &token_addr));
     let buyer = Address::generate(&env);
+    token_mint.mint(&buyer, &1_000_000);
 
-    let token_admin = Address::generate(&env);
-    let (token_addr, token_mint) = create_token(&env, &token_admin);
-    token_mint.mint(&creator, &10_000_000);
-
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Test"),
-        end_time: 2_000,
-        no_deadline: false,
-        max_tickets: 1,
-        max_tickets_per_tx: 1,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: payment_token.clone(),
-        prize_amount: MIN_TICKET_PRICE * 10,
-        prizes: soroban_sdk::vec![&env, 10000],
-        randomness_source: RandomnessSource::Internal,
-        oracle_address: None,
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[10; 32]),
-        claim_lockup_seconds: 0,
-    };
+    client.deposit_prize();
+    client.buy_tickets(&buyer, &2, &0);
+    client.cancel_raffle(&CancelReason::CreatorCancelled);
+
+    client.refund_ticket(&1);
+    assert_eq!(client.refund_ticket(&2), MIN_TICKET_PRICE);
+
+    let guard_set: bool = env.as_contract(&contract_id, || {
+        env.storage()
+            .instance()
+            .get(&DataKey::ReentrancyGuard)
+            .unwrap_or(false)
+    });
+    assert!(!guard_set);
+}
+
+/// A deadline raffle that sold its only ticket and was drawn at 2_000.
+fn setup_finalized_raffle(env: &Env) -> (ContractClient<'_>, Address, Address) {
+    env.ledger().set_timestamp(1_000);
+    let (token_addr, _) = create_token(env, &Address::generate(env));
+    let mut config = test_raffle_config(env, &token_addr);
+    config.end_time = 2_000;
+    config.no_deadline = false;
+    config.max_tickets = 1;
+    config.max_tickets_per_tx = 1;
+    let (_contract_id, client, admin, creator) = init_raffle(env, &config);
 
-    client.init(&factory, &admin, &creator, &config);
     client.deposit_prize();
-    client.buy_tickets(&creator, &1);
+    client.buy_tickets(&creator, &1, &0);
+    env.ledger().set_timestamp(2_000);
     client.finalize_raffle();
+    assert_eq!(client.get_raffle().status, RaffleStatus::Finalized);
+
+    (client, admin, creator)
+}
+
+#[test]
+fn emergency_withdraw_fails_before_delay_in_finalized_state() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, creator) = setup_finalized_raffle(&env);
+
+    assert_eq!(
+        client.try_emergency_withdraw(&creator),
+        Err(Ok(Error::EmergencyTooEarly))
+    );
+}
 
-    env.ledger().set_timestamp(1_000 + EMERGENCY_WITHDRAW_DELAY_SECONDS + 1);
+#[test]
+fn emergency_withdraw_succeeds_after_delay_in_finalized_state() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, creator) = setup_finalized_raffle(&env);
 
+    env.ledger()
+        .set_timestamp(2_000 + EMERGENCY_WITHDRAW_DELAY_SECONDS + 1);
     client.emergency_withdraw(&creator);
+
     let raffle = client.get_raffle();
     assert_eq!(raffle.status, RaffleStatus::Cancelled);
     assert!(!raffle.prize_deposited);
@@ -635,112 +627,26 @@ fn emergency_withdraw_succeeds_after_delay_in_finalized_state() {
 fn emergency_withdraw_fails_for_no_deadline_raffle_before_timeout() {
     let env = Env::default();
     env.mock_all_auths();
-    env.ledger().set_timestamp(1_000);
-
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(&env, &contract_id);
-
-    let factory = env.register(MockFactory, ());
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
-    let token_admin = Address::generate(&env);
-    let payment_token = env
-        .register_stellar_asset_contract_v2(token_admin.clone())
-        .address();
-    let token_client = StellarAssetClient::new(&env, &payment_token);
-    token_client.mint(&creator, &1_000_000);
-
-    let contract_id = env.register(RaffleInstance, ());
-    let client = RaffleInstanceClient::new(&env, &contract_id);
-
-    let end_time = 5_000u64;
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Test"),
-        end_time: 0,
-        no_deadline: true,
-        max_tickets: 1,
-        max_tickets_per_tx: 1,
-        description: String::from_str(&env, "Refund test"),
-        end_time: 0,
-        no_deadline: true,
-        max_tickets: 5,
-        max_tickets_per_tx: 5,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: payment_token.clone(),
-        prize_amount: MIN_TICKET_PRICE * 10,
-        prize_amount: MIN_TICKET_PRICE * 5,
-        prizes: vec![&env, 10000u32],
-        randomness_source: RandomnessSource::External,
-        oracle_address: Some(oracle.clone()),
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[11; 32]),
-        claim_lockup_seconds: 0,
-    };
+    let (_contract_id, client, creator, _requested_at) = setup_external_drawing_raffle(&env);
 
-    client.init(&factory, &admin, &creator, &config);
-    client.deposit_prize();
-    client.buy_tickets(&creator, &1);
-    client.finalize_raffle();
-
-    let result = client.try_emergency_withdraw(&creator);
-    assert_eq!(result.err(), Some(Ok(Error::EmergencyTooEarly)));
+    assert_eq!(
+        client.try_emergency_withdraw(&creator),
+        Err(Ok(Error::EmergencyTooEarly))
+    );
 }
 
 #[test]
 fn emergency_withdraw_succeeds_for_no_deadline_drawing_raffle_after_timeout() {
     let env = Env::default();
     env.mock_all_auths();
-    env.ledger().set_timestamp(1_000);
-
-    let factory = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
-    let oracle = Address::generate(&env);
-    let token_admin = Address::generate(&env);
-    let payment_token = env
-        .register_stellar_asset_contract_v2(token_admin.clone())
-        .address();
-    let token_client = StellarAssetClient::new(&env, &payment_token);
-    token_client.mint(&creator, &1_000_000);
-
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(&env, &contract_id);
-
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Test"),
-        end_time: 2_000,
-        no_deadline: false,
-        max_tickets: 1,
-        max_tickets_per_tx: 1,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: payment_token.clone(),
-        prize_amount: MIN_TICKET_PRICE * 10,
-        prizes: soroban_sdk::vec![&env, 10000],
-        randomness_source: RandomnessSource::External,
-        oracle_address: Some(oracle),
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[12; 32]),
-        claim_lockup_seconds: 0,
-    };
-
-    client.init(&factory, &admin, &creator, &config);
-    client.deposit_prize();
-    client.buy_tickets(&creator, &1);
-    client.finalize_raffle();
-
-    env.ledger().set_timestamp(2_000 + EMERGENCY_WITHDRAW_DELAY_SECONDS + 1);
+    let (_contract_id, client, creator, requested_at) = setup_external_drawing_raffle(&env);
 
+    // Drawing time is estimated at five seconds a ledger.
+    env.ledger().with_mut(|l| {
+        l.sequence_number = requested_at + (EMERGENCY_WITHDRAW_DELAY_SECONDS / 5) as u32 + 1;
+    });
     client.emergency_withdraw(&creator);
+
     let raffle = client.get_raffle();
     assert_eq!(raffle.status, RaffleStatus::Cancelled);
     assert!(!raffle.prize_deposited);
@@ -750,277 +656,73 @@ fn emergency_withdraw_succeeds_for_no_deadline_drawing_raffle_after_timeout() {
 fn emergency_withdraw_fails_in_active_state() {
     let env = Env::default();
     env.mock_all_auths();
-    env.ledger().set_timestamp(1_000);
+    let (client, _admin, creator, _buyer, _factory, _token_mint) = setup_active_raffle(&env);
 
-    let factory = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
-    let token_admin = Address::generate(&env);
-    let payment_token = env
-        .register_stellar_asset_contract_v2(token_admin.clone())
-        .address();
-    let token_client = StellarAssetClient::new(&env, &payment_token);
-    token_client.mint(&creator, &1_000_000);
+    assert_eq!(
+        client.try_emergency_withdraw(&creator),
+        Err(Ok(Error::InvalidStatus))
+    );
+}
 
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(&env, &contract_id);
+#[test]
+fn emergency_withdraw_fails_in_cancelled_state() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, creator, _buyer, _factory, _token_mint) = setup_active_raffle(&env);
 
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Test"),
-        end_time: 10_000,
-        no_deadline: false,
-        max_tickets: 1,
-        max_tickets_per_tx: 1,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: payment_token.clone(),
-        prize_amount: MIN_TICKET_PRICE * 10,
-        prizes: soroban_sdk::vec![&env, 10000],
-        randomness_source: RandomnessSource::Internal,
-        oracle_address: None,
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[13; 32]),
-        claim_lockup_seconds: 0,
-    };
-
-    client.init(&factory, &admin, &creator, &config);
-    client.deposit_prize();
-
-    let result = client.try_emergency_withdraw(&creator);
-    assert_eq!(result.err(), Some(Ok(Error::InvalidStatus)));
-}
-
-#[test]
-fn emergency_withdraw_fails_in_cancelled_state() {
-    let env = Env::default();
-    env.mock_all_auths();
-    env.ledger().set_timestamp(1_000);
-
-    let factory = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
-    let token_admin = Address::generate(&env);
-    let payment_token = env
-        .register_stellar_asset_contract_v2(token_admin.clone())
-        .address();
-    let token_client = StellarAssetClient::new(&env, &payment_token);
-    token_client.mint(&creator, &1_000_000);
-
-fn setup_external_drawing_raffle(
-    env: &Env,
-) -> (Address, ContractClient<'_>, Address, Address, Address, u64) {
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(env, &contract_id);
-
-    let factory = env.register(MockFactory, ());
-    let admin = Address::generate(env);
-    let creator = Address::generate(env);
-    let oracle = Address::generate(env);
-
-    let token_admin = Address::generate(env);
-    let (token_addr, token_mint) = create_token(env, &token_admin);
-    token_mint.mint(&creator, &10_000_000);
-
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Test"),
-        end_time: 10_000,
-        no_deadline: false,
-        max_tickets: 1,
-        max_tickets_per_tx: 1,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: payment_token.clone(),
-        prize_amount: MIN_TICKET_PRICE * 10,
-        prizes: soroban_sdk::vec![&env, 10000],
-        randomness_source: RandomnessSource::Internal,
-        oracle_address: None,
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[14; 32]),
-        claim_lockup_seconds: 0,
-    };
-
-    client.init(&factory, &admin, &creator, &config);
-    client.deposit_prize();
-    client.cancel(&creator, &CancelReason::Other);
-
-    let result = client.try_emergency_withdraw(&creator);
-    assert_eq!(result.err(), Some(Ok(Error::InvalidStatus)));
-}
-
-#[test]
-fn emergency_withdraw_fails_if_prize_not_deposited() {
-        metadata_hash: BytesN::from_array(&env, &[5; 32]),
-        claim_lockup_seconds: 0,
-        swap_deadline_seconds: 0,
-        prize_token: None,
-        nft_contract: None,
-    };
-
-    client.init(&factory, &admin, &creator, &config);
-    env.as_contract(&contract_id, || {
-        env.storage().instance().remove(&DataKey::Factory);
-    });
-
-    client.deposit_prize();
-    client.buy_tickets(&buyer, &1);
-
-    let balance_before = soroban_sdk::token::Client::new(&env, &payment_token).balance(&buyer);
     client.cancel_raffle(&CancelReason::CreatorCancelled);
-
-    let refunded = client.refund_ticket(&1);
-    assert_eq!(refunded, MIN_TICKET_PRICE);
-
-    let balance_after = soroban_sdk::token::Client::new(&env, &payment_token).balance(&buyer);
-    assert_eq!(balance_after, balance_before + MIN_TICKET_PRICE);
-
-    let second_refund = client.try_refund_ticket(&1);
-    assert_eq!(second_refund.err(), Some(Ok(Error::PrizeAlreadyClaimed)));
+    assert_eq!(
+        client.try_emergency_withdraw(&creator),
+        Err(Ok(Error::InvalidStatus))
+    );
 }
 
 #[test]
-fn test_refund_guard_released_after_success() {
+fn emergency_withdraw_fails_if_prize_not_deposited() {
     let env = Env::default();
     env.mock_all_auths();
-    env.ledger().set_timestamp(1_000);
-
-    let factory = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
-    let token_admin = Address::generate(&env);
-    let payment_token = env
-        .register_stellar_asset_contract_v2(token_admin.clone())
-        .address();
-
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(&env, &contract_id);
-
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Test"),
-        end_time: 10_000,
-        no_deadline: false,
-        max_tickets: 1,
-        max_tickets_per_tx: 1,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: payment_token.clone(),
-        prize_amount: MIN_TICKET_PRICE * 10,
-        prizes: soroban_sdk::vec![&env, 10000],
-        randomness_source: RandomnessSource::Internal,
-        oracle_address: None,
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[15; 32]),
-        claim_lockup_seconds: 0,
-    };
 
-    client.init(&factory, &admin, &creator, &config);
+    let (token_addr, _) = create_token(&env, &Address::generate(&env));
+    let (_contract_id, client, _admin, creator) =
+        init_raffle(&env, &test_raffle_config(&env, &token_addr));
 
-    let result = client.try_emergency_withdraw(&creator);
-    assert_eq!(result.err(), Some(Ok(Error::PrizeNotDeposited)));
+    assert_eq!(
+        client.try_emergency_withdraw(&creator),
+        Err(Ok(Error::PrizeNotDeposited))
+    );
 }
 
 #[test]
 fn emergency_withdraw_only_callable_by_creator_or_admin() {
     let env = Env::default();
     env.mock_all_auths();
-    env.ledger().set_timestamp(1_000);
-
-    let factory = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
-    let stranger = Address::generate(&env);
-    let buyer = Address::generate(&env);
-
-    let token_admin = Address::generate(&env);
-    let payment_token = env
-        .register_stellar_asset_contract_v2(token_admin.clone())
-        .address();
-    let token_client = StellarAssetClient::new(&env, &payment_token);
-    token_client.mint(&creator, &1_000_000);
-    token_client.mint(&buyer, &1_000_000);
-
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(&env, &contract_id);
-
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Test"),
-        end_time: 2_000,
-        no_deadline: false,
-        max_tickets: 1,
-        max_tickets_per_tx: 1,
-        description: String::from_str(&env, "Guard release"),
-        end_time: 0,
-        no_deadline: true,
-        max_tickets: 5,
-        max_tickets_per_tx: 5,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: payment_token.clone(),
-        prize_amount: MIN_TICKET_PRICE * 10,
-        prize_amount: MIN_TICKET_PRICE * 5,
-        prizes: soroban_sdk::vec![&env, 10000],
-        randomness_source: RandomnessSource::Internal,
-        oracle_address: None,
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[16; 32]),
-        claim_lockup_seconds: 0,
-    };
-
-    client.init(&factory, &admin, &creator, &config);
-    client.deposit_prize();
-    client.buy_tickets(&creator, &1);
-    client.finalize_raffle();
-
-    env.ledger().set_timestamp(1_000 + EMERGENCY_WITHDRAW_DELAY_SECONDS + 1);
-
-    let stranger_result = client.try_emergency_withdraw(&stranger);
-    assert_eq!(stranger_result.err(), Some(Ok(Error::NotAuthorized)));
+    let (client, admin, _creator) = setup_finalized_raffle(&env);
 
+    env.ledger()
+        .set_timestamp(2_000 + EMERGENCY_WITHDRAW_DELAY_SECONDS + 1);
+    assert_eq!(
+        client.try_emergency_withdraw(&Address::generate(&env)),
+        Err(Ok(Error::NotAuthorized))
+    );
     client.emergency_withdraw(&admin);
 }
 
 #[test]
 fn emergency_withdraw_sets_status_to_cancelled_and_clears_prize_deposited() {
-        metadata_hash: BytesN::from_array(&env, &[6; 32]),
-        claim_lockup_seconds: 0,
-        swap_deadline_seconds: 0,
-    };
-
-    client.init(&factory, &admin, &creator, &config);
-    env.as_contract(&contract_id, || {
-        env.storage().instance().remove(&DataKey::Factory);
-    });
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, creator) = setup_finalized_raffle(&env);
 
-    client.deposit_prize();
-    client.buy_tickets(&buyer, &2);
-    client.cancel_raffle(&CancelReason::CreatorCancelled);
+    let before = client.get_raffle();
+    assert!(before.prize_deposited);
 
-    client.refund_ticket(&1);
-    let second = client.refund_ticket(&2);
-    assert_eq!(second, MIN_TICKET_PRICE);
+    env.ledger()
+        .set_timestamp(2_000 + EMERGENCY_WITHDRAW_DELAY_SECONDS + 1);
+    client.emergency_withdraw(&creator);
 
-    let guard_set: bool = env.as_contract(&contract_id, || {
-        env.storage()
-            .instance()
-            .get(&DataKey::ReentrancyGuard)
-            .unwrap_or(false)
-    });
-    assert!(!guard_set);
+    let after = client.get_raffle();
+    assert_eq!(after.status, RaffleStatus::Cancelled);
+    assert!(!after.prize_deposited);
 }
 
 #[test]
@@ -1029,83 +731,28 @@ fn test_claim_prize_pays_full_gross_with_protocol_fee() {
     env.mock_all_auths();
     env.ledger().set_timestamp(1_000);
 
-    let factory = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
+    let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+    let mut config = test_raffle_config(&env, &token_addr);
+    config.max_tickets = 1;
+    config.max_tickets_per_tx = 1;
+    config.protocol_fee_bp = 1_000;
+    let (_contract_id, client, _admin, _creator) = init_raffle(&env, &config);
     let buyer = Address::generate(&env);
+    token_mint.mint(&buyer, &1_000_000);
 
-    let token_admin = Address::generate(&env);
-    let payment_token = env
-        .register_stellar_asset_contract_v2(token_admin.clone())
-        .address();
-    let token_client = StellarAssetClient::new(&env, &payment_token);
-    token_client.mint(&creator, &1_000_000);
-    token_client.mint(&buyer, &1_000_000);
-
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(&env, &contract_id);
-
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Test"),
-        end_time: 2_000,
-        no_deadline: false,
-        description: String::from_str(&env, "Claim gross"),
-        end_time: 0,
-        no_deadline: true,
-        max_tickets: 1,
-        max_tickets_per_tx: 1,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: payment_token.clone(),
-        prize_amount: MIN_TICKET_PRICE * 10,
-        prizes: soroban_sdk::vec![&env, 10000],
-        randomness_source: RandomnessSource::Internal,
-        oracle_address: None,
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[17; 32]),
-        claim_lockup_seconds: 0,
-        protocol_fee_bp: 1_000,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[7; 32]),
-        claim_lockup_seconds: 0,
-        swap_deadline_seconds: 0,
-    };
-
-    client.init(&factory, &admin, &creator, &config);
     client.deposit_prize();
-    client.buy_tickets(&creator, &1);
-    client.finalize_raffle();
-
-    let before = client.get_raffle();
-    assert_eq!(before.status, RaffleStatus::Finalized);
-    assert!(before.prize_deposited);
-
-    env.ledger().set_timestamp(1_000 + EMERGENCY_WITHDRAW_DELAY_SECONDS + 1);
-
-    client.emergency_withdraw(&creator);
-
-    let after = client.get_raffle();
-    assert_eq!(after.status, RaffleStatus::Cancelled);
-    assert!(!after.prize_deposited);
-    client.buy_tickets(&buyer, &1);
+    client.buy_tickets(&buyer, &1, &0);
     client.finalize_raffle();
 
-    env.ledger().set_timestamp(1_000 + DEFAULT_CLAIM_LOCKUP_SECONDS + 1);
+    env.ledger()
+        .set_timestamp(1_000 + DEFAULT_CLAIM_LOCKUP_SECONDS + 1);
     let winner = client.get_raffle().winners.get(0).unwrap();
-    let balance_before = soroban_sdk::token::Client::new(&env, &payment_token).balance(&winner);
+    let token = token::Client::new(&env, &token_addr);
+    let balance_before = token.balance(&winner);
 
-    let claimed = client.claim_prize(&winner, &0);
     let gross = MIN_TICKET_PRICE * 10;
-    assert_eq!(claimed, gross);
-
-    let balance_after = soroban_sdk::token::Client::new(&env, &payment_token).balance(&winner);
-    assert_eq!(balance_after, balance_before + gross);
+    assert_eq!(client.claim_prize(&winner, &0), gross);
+    assert_eq!(token.balance(&winner), balance_before + gross);
 
     let ticket_fee = MIN_TICKET_PRICE * 1_000 / 10_000;
     assert_eq!(client.get_accumulated_fees(), ticket_fee);
@@ -1113,207 +760,107 @@ fn test_claim_prize_pays_full_gross_with_protocol_fee() {
 
 #[test]
 fn prize_distribution_invariant_holds_for_multiple_tiers() {
-    let tier_configs: [[u32; 3]; 3] = [[10000, 0, 0], [5000, 5000, 0], [6000, 3000, 1000]];
-    let fee_bps = [0u32, 100, 250, 1000, 2000];
-
-    for tiers_raw in tier_configs {
-        let tiers_count = if tiers_raw[2] > 0 {
-            3
-        } else if tiers_raw[1] > 0 {
-            2
-        } else {
-            1
-        };
+    let tier_configs: [&[u32]; 3] = [&[10000], &[5000, 5000], &[6000, 3000, 1000]];
+    let fee_bps = [0u32, 100, 250, 1000];
 
+    for tiers in tier_configs {
         for fee_bp in fee_bps {
             let env = Env::default();
             env.mock_all_auths();
             env.ledger().set_timestamp(1_000);
 
-            let factory = Address::generate(&env);
-            let admin = Address::generate(&env);
-            let creator = Address::generate(&env);
+            let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
             let treasury = Address::generate(&env);
-            let buyer_a = Address::generate(&env);
-            let buyer_b = Address::generate(&env);
-            let buyer_c = Address::generate(&env);
-
-            let token_admin = Address::generate(&env);
-            let payment_token = env
-                .register_stellar_asset_contract_v2(token_admin.clone())
-                .address();
-            let token_client = StellarAssetClient::new(&env, &payment_token);
-            token_client.mint(&creator, &10_000_000);
-            token_client.mint(&buyer_a, &10_000_000);
-            token_client.mint(&buyer_b, &10_000_000);
-            token_client.mint(&buyer_c, &10_000_000);
-
-            let contract_id = env.register(Contract, ());
-            let client = ContractClient::new(&env, &contract_id);
-
+            let tickets_to_sell = tiers.len() as u32;
             let prize_amount: i128 = 1_000_000;
-            let ticket_price: i128 = MIN_TICKET_PRICE;
-            let tickets_to_sell: u32 = tiers_count;
-            let total_ticket_sales = ticket_price * tickets_to_sell as i128;
-            let expected_ticket_fees = total_ticket_sales * fee_bp as i128 / 10_000;
-
-            let prizes = match tiers_count {
-                1 => soroban_sdk::vec![&env, tiers_raw[0]],
-                2 => soroban_sdk::vec![&env, tiers_raw[0], tiers_raw[1]],
-                _ => soroban_sdk::vec![&env, tiers_raw[0], tiers_raw[1], tiers_raw[2]],
-            };
-
-            let config = RaffleConfig {
-                description: String::from_str(&env, "Prize invariant"),
-                end_time: 0,
-                no_deadline: true,
-                max_tickets: tickets_to_sell,
-                max_tickets_per_tx: tickets_to_sell,
-                min_tickets: 1,
-                allow_multiple: true,
-                ticket_price,
-                payment_token: payment_token.clone(),
-                prize_amount,
-                prizes,
-                randomness_source: RandomnessSource::Internal,
-                oracle_address: None,
-                protocol_fee_bp: fee_bp,
-                treasury_address: Some(treasury.clone()),
-                swap_router: None,
-                tikka_token: None,
-                metadata_hash: BytesN::from_array(&env, &[33; 32]),
-                claim_lockup_seconds: 0,
-                swap_deadline_seconds: 0,
-            };
-
-            client.init(&factory, &admin, &creator, &config);
-            client.deposit_prize();
 
-            client.buy_tickets(&buyer_a, &1);
-            if tickets_to_sell > 1 {
-                client.buy_tickets(&buyer_b, &1);
-            }
-            if tickets_to_sell > 2 {
-                client.buy_tickets(&buyer_c, &1);
-            }
+            let mut config = test_raffle_config(&env, &token_addr);
+            config.max_tickets = tickets_to_sell;
+            config.max_tickets_per_tx = tickets_to_sell;
+            config.prize_amount = prize_amount;
+            config.prizes = Vec::from_slice(&env, tiers);
+            config.protocol_fee_bp = fee_bp;
+            config.treasury_address = Some(treasury.clone());
+            let (contract_id, client, _admin, _creator) = init_raffle(&env, &config);
 
+            client.deposit_prize();
+            for _ in 0..tickets_to_sell {
+                let buyer = Address::generate(&env);
+                token_mint.mint(&buyer, &MIN_TICKET_PRICE);
+                client.buy_tickets(&buyer, &1, &0);
+            }
             client.finalize_raffle();
+            client.remit_fees(&token_addr);
 
-            let token = soroban_sdk::token::Client::new(&env, &payment_token);
-            let contract_balance_before_claims = token.balance(&contract_id);
+            let token = token::Client::new(&env, &token_addr);
+            let balance_before_claims = token.balance(&contract_id);
 
             env.ledger()
                 .set_timestamp(1_000 + DEFAULT_CLAIM_LOCKUP_SECONDS + 1);
-
             let raffle = client.get_raffle();
             let mut total_claimed = 0i128;
             for i in 0..raffle.winners.len() {
-                let winner = raffle.winners.get(i).unwrap();
-                let claimed = client.claim_prize(&winner, &i);
-                total_claimed += claimed;
+                total_claimed += client.claim_prize(&raffle.winners.get(i).unwrap(), &i);
             }
 
-            // Core invariant: all prize value is either claimed or explicitly
-            // accounted as protocol fee. Current implementation has zero
-            // protocol fee on prize claims, so this catches accidental
-            // underpayment/double-fee regressions.
-            let fee_from_prize = prize_amount - total_claimed;
-            assert_eq!(total_claimed + fee_from_prize, prize_amount);
-            assert_eq!(fee_from_prize, 0);
-
-            assert_eq!(token.balance(&treasury), expected_ticket_fees);
-
-            let contract_balance_after_claims = token.balance(&contract_id);
+            // Prizes are paid in full; the protocol fee comes out of ticket
+            // sales only.
+            assert_eq!(total_claimed, prize_amount);
+            let total_sales = MIN_TICKET_PRICE * tickets_to_sell as i128;
+            assert_eq!(
+                token.balance(&treasury),
+                total_sales * fee_bp as i128 / 10_000
+            );
             assert_eq!(
-                contract_balance_after_claims,
-                contract_balance_before_claims - prize_amount
+                token.balance(&contract_id),
+                balance_before_claims - prize_amount
             );
         }
     }
 }
 
+/// Seed a commit-reveal raffle derives from the concatenated commits.
+fn commit_reveal_seed(env: &Env, commits: &[[u8; 32]]) -> u64 {
+    let mut combined = Bytes::new(env);
+    for commit in commits {
+        combined.extend_from_array(commit);
+    }
+    seed_from_inputs(env, &combined)
+}
+
 #[test]
 fn commit_reveal_entropy_is_mixed_from_all_tickets() {
-    fn run_seed(commit_b: [u8; 32], metadata_byte: u8) -> u64 {
+    fn run_seed(commit_b: [u8; 32]) -> u64 {
         let env = Env::default();
         env.mock_all_auths();
 
-        let factory = Address::generate(&env);
-        let admin = Address::generate(&env);
-        let creator = Address::generate(&env);
-        let buyer_a = Address::generate(&env);
-        let buyer_b = Address::generate(&env);
-        let buyer_c = Address::generate(&env);
-
-        let token_admin = Address::generate(&env);
-        let payment_token = env
-            .register_stellar_asset_contract_v2(token_admin.clone())
-            .address();
-        let token_client = StellarAssetClient::new(&env, &payment_token);
-        token_client.mint(&creator, &1_000_000);
-        token_client.mint(&buyer_a, &1_000_000);
-        token_client.mint(&buyer_b, &1_000_000);
-        token_client.mint(&buyer_c, &1_000_000);
-
-        let contract_id = env.register(Contract, ());
-        let client = ContractClient::new(&env, &contract_id);
-
-        let config = RaffleConfig {
-            description: String::from_str(&env, "Commit reveal entropy"),
-            end_time: 0,
-            no_deadline: true,
-            max_tickets: 3,
-            max_tickets_per_tx: 3,
-            min_tickets: 1,
-            allow_multiple: true,
-            ticket_price: MIN_TICKET_PRICE,
-            payment_token: payment_token.clone(),
-            prize_amount: MIN_TICKET_PRICE * 10,
-            prizes: soroban_sdk::vec![&env, 6000, 3000, 1000],
-            randomness_source: RandomnessSource::CommitReveal,
-            oracle_address: None,
-            protocol_fee_bp: 0,
-            treasury_address: None,
-            swap_router: None,
-            tikka_token: None,
-            metadata_hash: BytesN::from_array(&env, &[metadata_byte; 32]),
-            claim_lockup_seconds: 0,
-            swap_deadline_seconds: 0,
-        };
-
-        client.init(&factory, &admin, &creator, &config);
-        client.deposit_prize();
-        client.buy_tickets(&buyer_a, &1);
-        client.buy_tickets(&buyer_b, &1);
-        client.buy_tickets(&buyer_c, &1);
+        let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+        let mut config = test_raffle_config(&env, &token_addr);
+        config.max_tickets = 3;
+        config.max_tickets_per_tx = 3;
+        config.prizes = vec![&env, 6000, 3000, 1000];
+        config.randomness_source = RandomnessSource::CommitReveal;
+        let (_contract_id, client, _admin, _creator) = init_raffle(&env, &config);
 
-        let commit_a = [1u8; 32];
-        let commit_c = [3u8; 32];
-        client.submit_commit(&1, &BytesN::from_array(&env, &commit_a));
-        client.submit_commit(&2, &BytesN::from_array(&env, &commit_b));
-        client.submit_commit(&3, &BytesN::from_array(&env, &commit_c));
+        client.deposit_prize();
+        for _ in 0..3 {
+            let buyer = Address::generate(&env);
+            token_mint.mint(&buyer, &MIN_TICKET_PRICE);
+            client.buy_tickets(&buyer, &1, &0);
+        }
 
+        let commits = [[1u8; 32], commit_b, [3u8; 32]];
+        for (ticket_id, commit) in (1u32..).zip(commits.iter()) {
+            client.submit_commit(&ticket_id, &BytesN::from_array(&env, commit));
+        }
         client.finalize_raffle();
 
-        let fairness = client.get_fairness_data();
-
-        let mut combined = Bytes::new(&env);
-        combined.extend_from_array(&commit_a);
-        combined.extend_from_array(&commit_b);
-        combined.extend_from_array(&commit_c);
-        let hash: BytesN<32> = env.crypto().sha256(&combined).into();
-        let arr = hash.to_array();
-        let expected_seed = u64::from_be_bytes([
-            arr[0], arr[1], arr[2], arr[3], arr[4], arr[5], arr[6], arr[7],
-        ]);
-
-        assert_eq!(fairness.seed, expected_seed);
-        fairness.seed
+        let seed = client.get_fairness_data().seed;
+        assert_eq!(seed, commit_reveal_seed(&env, &commits));
+        seed
     }
 
-    let seed_original = run_seed([2u8; 32], 44);
-    let seed_changed = run_seed([9u8; 32], 45);
-    assert_ne!(seed_original, seed_changed);
+    assert_ne!(run_seed([2u8; 32]), run_seed([9u8; 32]));
 }
 
 #[test]
@@ -1321,77 +868,33 @@ fn commit_reveal_preserves_entropy_after_ticket_transfer() {
     let env = Env::default();
     env.mock_all_auths();
 
-    let factory = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
+    let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+    let mut config = test_raffle_config(&env, &token_addr);
+    config.max_tickets = 1;
+    config.max_tickets_per_tx = 1;
+    config.randomness_source = RandomnessSource::CommitReveal;
+    let (contract_id, client, _admin, _creator) = init_raffle(&env, &config);
     let buyer_a = Address::generate(&env);
     let buyer_b = Address::generate(&env);
+    token_mint.mint(&buyer_a, &MIN_TICKET_PRICE);
 
-    let token_admin = Address::generate(&env);
-    let payment_token = env
-        .register_stellar_asset_contract_v2(token_admin.clone())
-        .address();
-    let token_client = StellarAssetClient::new(&env, &payment_token);
-    token_client.mint(&creator, &1_000_000);
-    token_client.mint(&buyer_a, &1_000_000);
-    token_client.mint(&buyer_b, &1_000_000);
-
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(&env, &contract_id);
-
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Commit survives transfer"),
-        end_time: 0,
-        no_deadline: true,
-        max_tickets: 1,
-        max_tickets_per_tx: 1,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: payment_token.clone(),
-        prize_amount: MIN_TICKET_PRICE * 5,
-        prizes: soroban_sdk::vec![&env, 10000],
-        randomness_source: RandomnessSource::CommitReveal,
-        oracle_address: None,
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[46; 32]),
-        claim_lockup_seconds: 0,
-        swap_deadline_seconds: 0,
-    };
-
-    client.init(&factory, &admin, &creator, &config);
     client.deposit_prize();
-    client.buy_tickets(&buyer_a, &1);
-
+    client.buy_tickets(&buyer_a, &1, &0);
     let commit = [7u8; 32];
     client.submit_commit(&1, &BytesN::from_array(&env, &commit));
 
-    // Simulate ownership transfer to validate commit persistence by ticket_id.
+    // The commit is keyed by ticket, so it survives a change of owner.
     env.as_contract(&contract_id, || {
-        let mut ticket: Ticket = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Ticket(1))
-            .unwrap();
+        let mut ticket: Ticket = env.storage().persistent().get(&DataKey::Ticket(1)).unwrap();
         ticket.owner = buyer_b.clone();
         env.storage().persistent().set(&DataKey::Ticket(1), &ticket);
     });
 
     client.finalize_raffle();
-    let fairness = client.get_fairness_data();
-
-    let mut combined = Bytes::new(&env);
-    combined.extend_from_array(&commit);
-    let hash: BytesN<32> = env.crypto().sha256(&combined).into();
-    let arr = hash.to_array();
-    let expected_seed = u64::from_be_bytes([
-        arr[0], arr[1], arr[2], arr[3], arr[4], arr[5], arr[6], arr[7],
-    ]);
-
-    assert_eq!(fairness.seed, expected_seed);
+    assert_eq!(
+        client.get_fairness_data().seed,
+        commit_reveal_seed(&env, &[commit])
+    );
 }
 
 #[test]
@@ -1399,76 +902,32 @@ fn commit_reveal_with_zero_commits_falls_back_to_prng() {
     let env = Env::default();
     env.mock_all_auths();
     env.ledger().set_timestamp(7_777);
-    env.ledger().with_mut(|l| {
-        l.sequence_number = 999;
-    });
-
-    let factory = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
-    let buyer_a = Address::generate(&env);
-    let buyer_b = Address::generate(&env);
-
-    let token_admin = Address::generate(&env);
-    let payment_token = env
-        .register_stellar_asset_contract_v2(token_admin.clone())
-        .address();
-    let token_client = StellarAssetClient::new(&env, &payment_token);
-    token_client.mint(&creator, &1_000_000);
-    token_client.mint(&buyer_a, &1_000_000);
-    token_client.mint(&buyer_b, &1_000_000);
-
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(&env, &contract_id);
+    env.ledger().with_mut(|l| l.sequence_number = 999);
 
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Commit reveal no commits"),
-        end_time: 0,
-        no_deadline: true,
-        max_tickets: 2,
-        max_tickets_per_tx: 2,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: payment_token.clone(),
-        prize_amount: MIN_TICKET_PRICE * 8,
-        prizes: soroban_sdk::vec![&env, 7000, 3000],
-        randomness_source: RandomnessSource::CommitReveal,
-        oracle_address: None,
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[47; 32]),
-        claim_lockup_seconds: 0,
-        swap_deadline_seconds: 0,
-    };
+    let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+    let mut config = test_raffle_config(&env, &token_addr);
+    config.max_tickets = 2;
+    config.max_tickets_per_tx = 2;
+    config.prizes = vec![&env, 7000, 3000];
+    config.randomness_source = RandomnessSource::CommitReveal;
+    let (contract_id, client, _admin, _creator) = init_raffle(&env, &config);
 
-    client.init(&factory, &admin, &creator, &config);
     client.deposit_prize();
-    client.buy_tickets(&buyer_a, &1);
-    client.buy_tickets(&buyer_b, &1);
+    for _ in 0..2 {
+        let buyer = Address::generate(&env);
+        token_mint.mint(&buyer, &MIN_TICKET_PRICE);
+        client.buy_tickets(&buyer, &1, &0);
+    }
     client.finalize_raffle();
 
     let raffle = client.get_raffle();
     assert_eq!(raffle.status, RaffleStatus::Finalized);
+    assert_eq!(raffle.winners.len(), 2);
 
-    let fairness = client.get_fairness_data();
     let expected_seed = env.as_contract(&contract_id, || {
-        let payload = (
-            env.ledger().timestamp(),
-            env.ledger().sequence(),
-            env.current_contract_address().to_xdr(&env),
-        )
-            .to_xdr(&env);
-        let hash: BytesN<32> = env.crypto().sha256(&payload).into();
-        let arr = hash.to_array();
-        u64::from_be_bytes([
-            arr[0], arr[1], arr[2], arr[3], arr[4], arr[5], arr[6], arr[7],
-        ])
+        seed_from_inputs(&env, &internal_seed_inputs(&env))
     });
-    assert_eq!(fairness.seed, expected_seed);
-    assert_eq!(raffle.winners.len(), 2);
+    assert_eq!(client.get_fairness_data().seed, expected_seed);
 }
 
 #[test]
@@ -1476,47 +935,14 @@ fn drawing_lock_cleared_after_internal_finalize() {
     let env = Env::default();
     env.mock_all_auths();
 
-    let factory = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
-
-    let token_admin = Address::generate(&env);
-    let payment_token = env
-        .register_stellar_asset_contract_v2(token_admin.clone())
-        .address();
-    let token_client = StellarAssetClient::new(&env, &payment_token);
-    token_client.mint(&creator, &1_000_000);
-
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(&env, &contract_id);
-
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Lock internal finalize"),
-        end_time: 0,
-        no_deadline: true,
-        max_tickets: 1,
-        max_tickets_per_tx: 1,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: payment_token.clone(),
-        prize_amount: MIN_TICKET_PRICE * 2,
-        prizes: soroban_sdk::vec![&env, 10000],
-        randomness_source: RandomnessSource::Internal,
-        oracle_address: None,
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[48; 32]),
-        claim_lockup_seconds: 0,
-        swap_deadline_seconds: 0,
-        bundles: soroban_sdk::vec![&env],
-    };
+    let (token_addr, _) = create_token(&env, &Address::generate(&env));
+    let mut config = test_raffle_config(&env, &token_addr);
+    config.max_tickets = 1;
+    config.max_tickets_per_tx = 1;
+    let (contract_id, client, _admin, creator) = init_raffle(&env, &config);
 
-    client.init(&factory, &admin, &creator, &config);
     client.deposit_prize();
-    client.buy_tickets(&creator, &1);
+    client.buy_tickets(&creator, &1, &0);
     client.finalize_raffle();
 
     assert_drawing_lock_cleared(&env, &contract_id);
@@ -1527,293 +953,2937 @@ fn drawing_lock_cleared_after_oracle_randomness() {
     let env = Env::default();
     env.mock_all_auths();
 
-    let factory = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
-    let oracle = Address::generate(&env);
+    let signing_key = SigningKey::from_bytes(&[5u8; 32]);
+    let (token_addr, _) = create_token(&env, &Address::generate(&env));
+    let mut config = external_config(&env, &token_addr, &Address::generate(&env));
+    config.max_tickets = 1;
+    config.max_tickets_per_tx = 1;
+    config.oracle_public_key = Some(BytesN::from_array(
+        &env,
+        &signing_key.verifying_key().to_bytes(),
+    ));
+    let (contract_id, client, _admin, creator) = init_raffle(&env, &config);
 
-    let token_admin = Address::generate(&env);
-    let payment_token = env
-        .register_stellar_asset_contract_v2(token_admin.clone())
-        .address();
-    let token_client = StellarAssetClient::new(&env, &payment_token);
-    token_client.mint(&creator, &1_000_000);
+    client.deposit_prize();
+    client.buy_tickets(&creator, &1, &0);
 
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(&env, &contract_id);
+    let request_id = client.get_randomness_request().unwrap().request_id;
+    let signature = oracle_signature(&env, &signing_key, &contract_id, request_id, 424_242);
+    client.provide_randomness(&424_242, &signature, &request_id);
 
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Lock oracle finalize"),
-        end_time: 0,
-        no_deadline: true,
-        max_tickets: 1,
-        max_tickets_per_tx: 1,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: payment_token.clone(),
-        prize_amount: MIN_TICKET_PRICE * 2,
-        prizes: soroban_sdk::vec![&env, 10000],
-        randomness_source: RandomnessSource::External,
-        oracle_address: Some(oracle),
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[49; 32]),
-        claim_lockup_seconds: 0,
-        swap_deadline_seconds: 0,
-    };
+    assert_drawing_lock_cleared(&env, &contract_id);
+}
 
-    client.init(&factory, &admin, &creator, &config);
-    client.deposit_prize();
-    client.buy_tickets(&creator, &1);
+#[test]
+fn drawing_lock_cleared_after_fallback_refund() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (contract_id, client, creator, _requested_at) = setup_external_drawing_raffle(&env);
 
-    let request_id: u64 = env.as_contract(&contract_id, || {
-        env.storage()
-            .instance()
-            .get(&DataKey::RandomnessRequestId)
-            .unwrap()
-    });
+    env.ledger()
+        .with_mut(|l| l.sequence_number += ORACLE_TIMEOUT_LEDGERS + 1);
+    client.trigger_randomness_fallback(&creator, &true);
 
-    let signing_key = SigningKey::from_bytes(&[5u8; 32]);
-    let verifying = signing_key.verifying_key();
-    let message = env.as_contract(&contract_id, || {
-        build_vrf_proof_message(&env, request_id, 424242)
-    });
-    let signature = signing_key.sign(message.as_slice());
+    assert_eq!(client.get_raffle().status, RaffleStatus::Cancelled);
+    assert_drawing_lock_cleared(&env, &contract_id);
+}
 
-    client.provide_randomness(
-        &424242,
-        &BytesN::from_array(&env, &verifying.to_bytes()),
-        &BytesN::from_array(&env, &signature.to_bytes()),
-        &request_id,
-    );
+#[test]
+fn drawing_lock_cleared_after_fallback_no_refund() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (contract_id, client, creator, _requested_at) = setup_external_drawing_raffle(&env);
+
+    env.ledger()
+        .with_mut(|l| l.sequence_number += ORACLE_TIMEOUT_LEDGERS + 1);
+    client.trigger_randomness_fallback(&creator, &false);
 
+    assert_eq!(client.get_raffle().status, RaffleStatus::Finalized);
     assert_drawing_lock_cleared(&env, &contract_id);
 }
 
 #[test]
-fn drawing_lock_cleared_after_fallback_refund() {
+fn drawing_lock_cleared_after_cancel_in_drawing_state() {
     let env = Env::default();
     env.mock_all_auths();
+    let (contract_id, client, _creator, _requested_at) = setup_external_drawing_raffle(&env);
 
-    let factory = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
-    let oracle = Address::generate(&env);
+    client.cancel_raffle(&CancelReason::CreatorCancelled);
 
-    let token_admin = Address::generate(&env);
-    let payment_token = env
-        .register_stellar_asset_contract_v2(token_admin.clone())
-        .address();
-    let token_client = StellarAssetClient::new(&env, &payment_token);
-    token_client.mint(&creator, &1_000_000);
+    assert_eq!(client.get_raffle().status, RaffleStatus::Cancelled);
+    assert_drawing_lock_cleared(&env, &contract_id);
+}
+
+#[test]
+fn draft_raffle_is_editable_until_published() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
 
     let contract_id = env.register(Contract, ());
     let client = ContractClient::new(&env, &contract_id);
+    let factory = env.register(MockFactory, ());
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
 
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Lock fallback refund"),
-        end_time: 0,
-        no_deadline: true,
-        max_tickets: 1,
-        max_tickets_per_tx: 1,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: payment_token.clone(),
-        prize_amount: MIN_TICKET_PRICE * 2,
-        prizes: soroban_sdk::vec![&env, 10000],
-        randomness_source: RandomnessSource::External,
-        oracle_address: Some(oracle),
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[50; 32]),
-        claim_lockup_seconds: 0,
-        swap_deadline_seconds: 0,
-    };
+    let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+    token_mint.mint(&creator, &1_000_000);
 
-    client.init(&factory, &admin, &creator, &config);
-    client.deposit_prize();
-    client.buy_tickets(&creator, &1);
+    client.init_draft(
+        &factory,
+        &admin,
+        &creator,
+        &test_raffle_config(&env, &token_addr),
+    );
+    assert_eq!(client.get_raffle().status, RaffleStatus::Draft);
+    assert_eq!(client.try_deposit_prize(), Err(Ok(Error::InvalidStatus)));
 
-    env.ledger().with_mut(|l| {
-        l.sequence_number += ORACLE_TIMEOUT_LEDGERS + 1;
-    });
-    client.trigger_randomness_fallback(&creator, &true);
+    client.update_raffle(&String::from_str(&env, "Edited draft"), &5_000u64, &20u32);
+    let raffle = client.get_raffle();
+    assert_eq!(raffle.description, String::from_str(&env, "Edited draft"));
+    assert_eq!(raffle.end_time, 5_000);
+    assert!(!raffle.no_deadline);
+    assert_eq!(raffle.max_tickets, 20);
 
-    assert_drawing_lock_cleared(&env, &contract_id);
+    client.publish_raffle();
+    assert_eq!(client.get_raffle().status, RaffleStatus::PendingPrize);
+    assert_eq!(
+        client.try_update_raffle(&String::from_str(&env, "Too late"), &6_000u64, &30u32),
+        Err(Ok(Error::InvalidStatus))
+    );
+
+    client.deposit_prize();
+    assert_eq!(client.get_raffle().status, RaffleStatus::Active);
 }
 
 #[test]
-fn drawing_lock_cleared_after_fallback_no_refund() {
+fn metadata_is_editable_only_before_first_sale() {
     let env = Env::default();
     env.mock_all_auths();
+    let (client, _admin, _creator, buyer, _factory, _token_mint) = setup_active_raffle(&env);
 
-    let factory = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
-    let oracle = Address::generate(&env);
+    assert_eq!(
+        client.get_raffle().category,
+        Symbol::new(&env, DEFAULT_CATEGORY)
+    );
 
-    let token_admin = Address::generate(&env);
-    let payment_token = env
-        .register_stellar_asset_contract_v2(token_admin.clone())
-        .address();
-    let token_client = StellarAssetClient::new(&env, &payment_token);
-    token_client.mint(&creator, &1_000_000);
+    let category = Symbol::new(&env, "art");
+    let image_uri = String::from_str(&env, "ipfs://bafy-raffle-image");
+    let tags = vec![&env, Symbol::new(&env, "nft"), Symbol::new(&env, "charity")];
+    client.set_metadata(&category, &image_uri, &tags);
 
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(&env, &contract_id);
+    let raffle = client.get_raffle();
+    assert_eq!(raffle.category, category);
+    assert_eq!(raffle.image_uri, image_uri);
+    assert_eq!(raffle.tags, tags);
+
+    let too_many_tags = vec![
+        &env,
+        Symbol::new(&env, "a"),
+        Symbol::new(&env, "b"),
+        Symbol::new(&env, "c"),
+        Symbol::new(&env, "d"),
+        Symbol::new(&env, "e"),
+        Symbol::new(&env, "f"),
+    ];
+    assert_eq!(
+        client.try_set_metadata(&category, &image_uri, &too_many_tags),
+        Err(Ok(Error::InvalidParameters))
+    );
 
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Lock fallback finalize"),
-        end_time: 0,
-        no_deadline: true,
-        max_tickets: 1,
-        max_tickets_per_tx: 1,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: payment_token.clone(),
-        prize_amount: MIN_TICKET_PRICE * 2,
-        prizes: soroban_sdk::vec![&env, 10000],
-        randomness_source: RandomnessSource::External,
-        oracle_address: Some(oracle),
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[51; 32]),
-        claim_lockup_seconds: 0,
-        swap_deadline_seconds: 0,
-    };
+    client.buy_tickets(&buyer, &1, &0);
+    assert_eq!(
+        client.try_set_metadata(&Symbol::new(&env, "games"), &image_uri, &tags),
+        Err(Ok(Error::InvalidStatus))
+    );
+}
 
-    client.init(&factory, &admin, &creator, &config);
-    client.deposit_prize();
-    client.buy_tickets(&creator, &1);
+#[test]
+fn factory_admin_cancel_refunds_prize_and_opens_ticket_refunds() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, creator, buyer, _factory, token_mint) = setup_active_raffle(&env);
+    let token = soroban_sdk::token::Client::new(&env, &token_mint.address);
 
-    env.ledger().with_mut(|l| {
-        l.sequence_number += ORACLE_TIMEOUT_LEDGERS + 1;
-    });
-    client.trigger_randomness_fallback(&creator, &false);
+    client.buy_tickets(&buyer, &1, &0);
+    let creator_before = token.balance(&creator);
 
-    assert_drawing_lock_cleared(&env, &contract_id);
+    client.admin_cancel();
+
+    let raffle = client.get_raffle();
+    assert_eq!(raffle.status, RaffleStatus::Cancelled);
+    assert!(!raffle.prize_deposited);
+    assert_eq!(
+        token.balance(&creator),
+        creator_before + raffle.prize_amount
+    );
+    assert_eq!(client.refund_ticket(&1), MIN_TICKET_PRICE);
+    assert_eq!(client.try_admin_cancel(), Err(Ok(Error::InvalidStatus)));
 }
 
 #[test]
-fn drawing_lock_cleared_after_cancel_in_drawing_state() {
+fn blocked_address_cannot_buy_tickets() {
     let env = Env::default();
     env.mock_all_auths();
+    let (client, _admin, _creator, buyer, factory, _token_mint) = setup_active_raffle(&env);
 
-    let factory = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
-    let oracle = Address::generate(&env);
+    env.as_contract(&factory, || {
+        env.storage().instance().set(&buyer, &true);
+    });
+    assert_eq!(
+        client.try_buy_tickets(&buyer, &1, &0),
+        Err(Ok(Error::AddressBlocked))
+    );
 
-    let token_admin = Address::generate(&env);
-    let payment_token = env
-        .register_stellar_asset_contract_v2(token_admin.clone())
-        .address();
-    let token_client = StellarAssetClient::new(&env, &payment_token);
-    token_client.mint(&creator, &1_000_000);
+    env.as_contract(&factory, || {
+        env.storage().instance().remove(&buyer);
+    });
+    client.buy_tickets(&buyer, &1, &0);
+}
 
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(&env, &contract_id);
+#[test]
+fn randomness_fulfillment_requires_matching_unused_request_id() {
+    let env = Env::default();
+    env.mock_all_auths();
 
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Lock cancel drawing"),
-        end_time: 0,
-        no_deadline: true,
-        max_tickets: 1,
-        max_tickets_per_tx: 1,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: MIN_TICKET_PRICE,
-        payment_token: payment_token.clone(),
-        prize_amount: MIN_TICKET_PRICE * 2,
-        prizes: soroban_sdk::vec![&env, 10000],
-        randomness_source: RandomnessSource::External,
-        oracle_address: Some(oracle),
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[52; 32]),
-        claim_lockup_seconds: 0,
-        swap_deadline_seconds: 0,
-    };
+    let signing_key = SigningKey::from_bytes(&[5u8; 32]);
+    let (token_addr, _) = create_token(&env, &Address::generate(&env));
+    let mut config = external_config(&env, &token_addr, &Address::generate(&env));
+    config.max_tickets = 1;
+    config.max_tickets_per_tx = 1;
+    config.oracle_public_key = Some(BytesN::from_array(
+        &env,
+        &signing_key.verifying_key().to_bytes(),
+    ));
+    let (contract_id, client, _admin, creator) = init_raffle(&env, &config);
 
-    client.init(&factory, &admin, &creator, &config);
     client.deposit_prize();
-    client.buy_tickets(&creator, &1);
-    client.cancel_raffle(&CancelReason::CreatorCancelled);
+    client.buy_tickets(&creator, &1, &0);
 
-    assert_drawing_lock_cleared(&env, &contract_id);
+    let request_id = client.get_randomness_request().unwrap().request_id;
+    let proof = oracle_signature(&env, &signing_key, &contract_id, request_id, 424_242);
+    assert_eq!(
+        client.try_provide_randomness(&424_242, &proof, &request_id.wrapping_add(1)),
+        Err(Ok(Error::RandomnessRequestMismatch))
+    );
+
+    client.provide_randomness(&424_242, &proof, &request_id);
+    assert_eq!(
+        client.try_provide_randomness(&424_242, &proof, &request_id),
+        Err(Ok(Error::RandomnessAlreadyFulfilled))
+    );
 }
 
+// BLS12-381 G1 generator: a valid curve point, but not a quicknet signature.
+const G1_GENERATOR: [u8; 96] = [
+    0x17, 0xf1, 0xd3, 0xa7, 0x31, 0x97, 0xd7, 0x94, 0x26, 0x95, 0x63, 0x8c, 0x4f, 0xa9, 0xac, 0x0f,
+    0xc3, 0x68, 0x8c, 0x4f, 0x97, 0x74, 0xb9, 0x05, 0xa1, 0x4e, 0x3a, 0x3f, 0x17, 0x1b, 0xac, 0x58,
+    0x6c, 0x55, 0xe8, 0x3f, 0xf9, 0x7a, 0x1a, 0xef, 0xfb, 0x3a, 0xf0, 0x0a, 0xdb, 0x22, 0xc6, 0xbb,
+    0x08, 0xb3, 0xf4, 0x81, 0xe3, 0xaa, 0xa0, 0xf1, 0xa0, 0x9e, 0x30, 0xed, 0x74, 0x1d, 0x8a, 0xe4,
+    0xfc, 0xf5, 0xe0, 0x95, 0xd5, 0xd0, 0x0a, 0xf6, 0x00, 0xdb, 0x18, 0xcb, 0x2c, 0x04, 0xb3, 0xed,
+    0xd0, 0x3c, 0xc7, 0x44, 0xa2, 0x88, 0x8a, 0xe4, 0x0c, 0xaa, 0x23, 0x29, 0x46, 0xc5, 0xe7, 0xe1,
+];
+
 #[test]
-fn test_bundle_pricing_applies() {
+fn drand_draw_commits_to_next_round_and_rejects_bad_beacons() {
     let env = Env::default();
     env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
 
-    let factory = Address::generate(&env);
-    let admin = Address::generate(&env);
-    let creator = Address::generate(&env);
-    let buyer = Address::generate(&env);
-
-    let token_admin = Address::generate(&env);
-    let payment_token = env
-        .register_stellar_asset_contract_v2(token_admin.clone())
-        .address();
-    let token_client = StellarAssetClient::new(&env, &payment_token);
-    token_client.mint(&creator, &100_000_000);
-    token_client.mint(&buyer, &100_000_000);
+    let (client, _admin, creator, _buyer, _factory, _token_mint) =
+        setup_active_raffle_with(&env, |config| {
+            config.max_tickets = 1;
+            config.max_tickets_per_tx = 1;
+            config.randomness_source = RandomnessSource::Drand;
+        });
+    client.buy_tickets(&creator, &1, &0);
 
-    let contract_id = env.register(Contract, ());
-    let client = ContractClient::new(&env, &contract_id);
+    assert_eq!(client.get_raffle().status, RaffleStatus::Drawing);
+    let round = crate::drand::next_round(1_700_000_000);
+    assert_eq!(client.get_drand_round(), Some(round));
 
-    let config = RaffleConfig {
-        description: String::from_str(&env, "Bundle test"),
-        end_time: 0,
-        no_deadline: true,
-        max_tickets: 50,
-        max_tickets_per_tx: 50,
-        min_tickets: 1,
-        allow_multiple: true,
-        ticket_price: 100_000,
-        payment_token: payment_token.clone(),
-        prize_amount: 100_000 * 50,
-        prizes: soroban_sdk::vec![&env, 10000],
-        randomness_source: RandomnessSource::Internal,
-        oracle_address: None,
-        protocol_fee_bp: 0,
-        treasury_address: None,
-        swap_router: None,
-        tikka_token: None,
-        metadata_hash: BytesN::from_array(&env, &[9; 32]),
-        claim_lockup_seconds: 0,
-        swap_deadline_seconds: 0,
-        bundles: soroban_sdk::vec![
+    let bogus = BytesN::from_array(&env, &G1_GENERATOR);
+    assert_eq!(
+        client.try_provide_drand_randomness(&(round - 1), &bogus),
+        Err(Ok(Error::RandomnessRequestMismatch))
+    );
+    assert_eq!(
+        client.try_provide_drand_randomness(&round, &bogus),
+        Err(Ok(Error::InvalidDrandSignature))
+    );
+}
+
+#[test]
+fn drand_raffle_finalized_by_time_commits_to_the_next_round() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_700_000_000);
+
+    let (client, _admin, creator, _buyer, _factory, _token_mint) =
+        setup_active_raffle_with(&env, |config| {
+            config.no_deadline = false;
+            config.end_time = 1_700_000_100;
+            config.randomness_source = RandomnessSource::Drand;
+        });
+    client.buy_tickets(&creator, &1, &0);
+    assert_eq!(client.get_raffle().status, RaffleStatus::Active);
+    assert_eq!(client.get_drand_round(), None);
+
+    env.ledger().set_timestamp(1_700_000_200);
+    client.finalize_raffle();
+    assert_eq!(client.get_raffle().status, RaffleStatus::Drawing);
+    let round = crate::drand::next_round(1_700_000_200);
+    assert_eq!(client.get_drand_round(), Some(round));
+
+    // The committed round reaches beacon verification; any other is refused.
+    let bogus = BytesN::from_array(&env, &G1_GENERATOR);
+    assert_eq!(
+        client.try_provide_drand_randomness(&(round + 1), &bogus),
+        Err(Ok(Error::RandomnessRequestMismatch))
+    );
+    assert_eq!(
+        client.try_provide_drand_randomness(&round, &bogus),
+        Err(Ok(Error::InvalidDrandSignature))
+    );
+}
+
+#[test]
+fn oracle_timeout_is_configurable_and_opens_refunds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let factory = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (payment_token, token_mint) = create_token(&env, &token_admin);
+    token_mint.mint(&creator, &1_000_000);
+
+    let contract_id = env.register(Contract, ());
+    let client = ContractClient::new(&env, &contract_id);
+
+    let mut config = test_raffle_config(&env, &payment_token);
+    config.max_tickets = 1;
+    config.max_tickets_per_tx = 1;
+    config.randomness_source = RandomnessSource::External;
+    config.oracle_address = Some(Address::generate(&env));
+    config.oracle_public_key = Some(BytesN::from_array(&env, &[7u8; 32]));
+    client.init(&factory, &admin, &creator, &config);
+
+    assert_eq!(client.get_oracle_timeout(), ORACLE_TIMEOUT_LEDGERS);
+    assert_eq!(
+        client.try_set_oracle_timeout(&(MAX_ORACLE_TIMEOUT_LEDGERS + 1)),
+        Err(Ok(Error::InvalidParameters))
+    );
+    client.set_oracle_timeout(&20);
+
+    client.deposit_prize();
+    client.buy_tickets(&creator, &1, &0);
+    assert_eq!(client.get_raffle().status, RaffleStatus::Drawing);
+    assert_eq!(
+        client.try_set_oracle_timeout(&50),
+        Err(Ok(Error::InvalidStatus))
+    );
+
+    env.ledger().with_mut(|l| l.sequence_number += 19);
+    assert_eq!(
+        client.try_trigger_randomness_fallback(&creator, &true),
+        Err(Ok(Error::FallbackTooEarly))
+    );
+
+    env.ledger().with_mut(|l| l.sequence_number += 1);
+    client.trigger_randomness_fallback(&creator, &true);
+    assert_eq!(client.get_raffle().status, RaffleStatus::Cancelled);
+}
+
+#[test]
+fn each_purchase_updates_participant_entropy() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _admin, creator, buyer, _factory, _token_mint) =
+        setup_active_raffle_with(&env, |_| {});
+    assert_eq!(client.get_participant_entropy(), None);
+
+    client.buy_tickets(&buyer, &1, &0);
+    let after_first = client.get_participant_entropy().unwrap();
+
+    env.ledger().with_mut(|l| l.sequence_number += 1);
+    client.buy_tickets(&creator, &1, &0);
+    let after_second = client.get_participant_entropy().unwrap();
+    assert_ne!(after_first, after_second);
+}
+
+#[test]
+fn draw_proof_reproduces_the_winners() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, _creator, buyer, _factory, _token_mint) = setup_active_raffle(&env);
+
+    assert_eq!(client.get_draw_proof(), None);
+    client.buy_tickets(&buyer, &1, &0);
+    client.finalize_raffle();
+    assert_eq!(client.get_raffle().status, RaffleStatus::Finalized);
+
+    let proof = client.get_draw_proof().unwrap();
+    assert_eq!(proof.randomness_type, RandomnessType::Prng);
+    assert_eq!(proof.tickets_sold, 1);
+    assert!(client.verify_draw(&proof));
+
+    let mut forged = proof.clone();
+    forged.seed = forged.seed.wrapping_add(1);
+    assert!(!client.verify_draw(&forged));
+
+    let mut forged = proof;
+    forged.seed_inputs = Bytes::from_array(&env, &[1, 2, 3]);
+    assert!(!client.verify_draw(&forged));
+}
+
+#[test]
+fn finalized_raffle_records_the_real_winning_ticket_id() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _admin, _creator, buyer, _factory, _token_mint) =
+        setup_active_raffle_with(&env, |config| {
+            config.max_tickets = 3;
+            config.max_tickets_per_tx = 3;
+        });
+    client.buy_tickets(&buyer, &3, &0);
+    client.finalize_raffle();
+
+    let raffle = client.get_raffle();
+    assert_eq!(raffle.winning_ticket_ids.len(), 1);
+    let winning_id = raffle.winning_ticket_ids.get(0).unwrap();
+    let proof = client.get_draw_proof().unwrap();
+    assert_eq!(winning_id, proof.winner_indices.get(0).unwrap() + 1);
+    assert!(client.get_my_tickets(&buyer).contains(winning_id));
+}
+
+#[test]
+fn winner_views_are_empty_until_drawn() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, _creator, buyer, _factory, _token_mint) = setup_active_raffle(&env);
+
+    assert_eq!(client.get_winner(), None);
+    assert!(client.get_winning_ticket().is_none());
+
+    client.buy_tickets(&buyer, &1, &0);
+    client.finalize_raffle();
+
+    assert_eq!(client.get_winner(), Some(buyer.clone()));
+    let ticket = client.get_winning_ticket().unwrap();
+    assert_eq!(ticket.owner, buyer);
+    assert_eq!(
+        ticket.id,
+        client.get_raffle().winning_ticket_ids.get(0).unwrap()
+    );
+}
+
+#[test]
+fn auto_distribute_pays_the_winner_on_finalize() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let treasury = Address::generate(&env);
+    let (client, _admin, _creator, buyer, _factory, token_mint) =
+        setup_active_raffle_with(&env, |config| {
+            config.max_tickets = 1;
+            config.max_tickets_per_tx = 1;
+            config.auto_distribute = true;
+            config.protocol_fee_bp = 1_000;
+            config.treasury_address = Some(treasury.clone());
+        });
+    let payment_token = token_mint.address.clone();
+    client.buy_tickets(&buyer, &1, &0);
+
+    let token = soroban_sdk::token::Client::new(&env, &payment_token);
+    let balance_before = token.balance(&buyer);
+    client.finalize_raffle();
+
+    let raffle = client.get_raffle();
+    assert_eq!(raffle.claim_lockup_seconds, 0);
+    assert_eq!(raffle.status, RaffleStatus::Claimed);
+    assert_eq!(raffle.claimed_winners.get(0), Some(true));
+    assert_eq!(token.balance(&buyer), balance_before + raffle.prize_amount);
+    assert_eq!(client.get_accrued_fees(&payment_token), 0);
+    assert_eq!(token.balance(&treasury), raffle.ticket_price / 10);
+    assert_eq!(
+        client.try_claim_prize(&buyer, &0u32),
+        Err(Ok(Error::InvalidStatus))
+    );
+}
+
+#[test]
+fn auto_distribute_leaves_locked_up_and_blocked_winners_to_claim() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    // An explicit lockup holds every tier back for `claim_prize`.
+    let (client, _admin, _creator, buyer, _factory, token_mint) =
+        setup_active_raffle_with(&env, |config| {
+            config.max_tickets = 1;
+            config.max_tickets_per_tx = 1;
+            config.auto_distribute = true;
+            config.claim_lockup_seconds = 60;
+        });
+    let token = soroban_sdk::token::Client::new(&env, &token_mint.address);
+    client.buy_tickets(&buyer, &1, &0);
+    client.finalize_raffle();
+    assert_eq!(client.get_raffle().status, RaffleStatus::Finalized);
+    assert_eq!(
+        client.try_claim_prize(&buyer, &0u32),
+        Err(Ok(Error::ClaimTooEarly))
+    );
+    env.ledger().set_timestamp(1_061);
+    let balance_before = token.balance(&buyer);
+    let prize = client.claim_prize(&buyer, &0u32);
+    assert_eq!(token.balance(&buyer), balance_before + prize);
+
+    // A blocked winner is not paid automatically either.
+    let (client, _admin, _creator, buyer, factory, token_mint) =
+        setup_active_raffle_with(&env, |config| {
+            config.max_tickets = 1;
+            config.max_tickets_per_tx = 1;
+            config.auto_distribute = true;
+        });
+    let token = soroban_sdk::token::Client::new(&env, &token_mint.address);
+    client.buy_tickets(&buyer, &1, &0);
+    env.as_contract(&factory, || {
+        env.storage().instance().set(&buyer, &true);
+    });
+    let balance_before = token.balance(&buyer);
+    client.finalize_raffle();
+    let raffle = client.get_raffle();
+    assert_eq!(raffle.status, RaffleStatus::Finalized);
+    assert_eq!(raffle.claimed_winners.get(0), Some(false));
+    assert_eq!(token.balance(&buyer), balance_before);
+    assert_eq!(
+        client.try_claim_prize(&buyer, &0u32),
+        Err(Ok(Error::AddressBlocked))
+    );
+}
+
+#[test]
+fn claim_prize_to_pays_the_designated_recipient() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, _creator, buyer, factory, _token_mint) = setup_active_raffle(&env);
+    let cold_wallet = Address::generate(&env);
+
+    client.buy_tickets(&buyer, &1, &0);
+    client.finalize_raffle();
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + DEFAULT_CLAIM_LOCKUP_SECONDS + 1);
+
+    let raffle = client.get_raffle();
+    let token = soroban_sdk::token::Client::new(&env, &raffle.prize_token);
+    let buyer_before = token.balance(&buyer);
+
+    assert_eq!(
+        client.try_claim_prize_to(&buyer, &0u32, &client.address),
+        Err(Ok(Error::InvalidParameters))
+    );
+
+    // A blocked address cannot be used to route the prize around the block.
+    env.as_contract(&factory, || {
+        env.storage().instance().set(&cold_wallet, &true);
+    });
+    assert_eq!(
+        client.try_claim_prize_to(&buyer, &0u32, &cold_wallet),
+        Err(Ok(Error::AddressBlocked))
+    );
+    env.as_contract(&factory, || {
+        env.storage().instance().remove(&cold_wallet);
+    });
+    let claimed = client.claim_prize_to(&buyer, &0u32, &cold_wallet);
+
+    assert_eq!(token.balance(&cold_wallet), claimed);
+    assert_eq!(token.balance(&buyer), buyer_before);
+    assert_eq!(
+        client.try_claim_prize(&buyer, &0u32),
+        Err(Ok(Error::InvalidStatus))
+    );
+}
+
+#[test]
+fn vesting_prize_streams_out_linearly() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _admin, _creator, buyer, factory, token_mint) =
+        setup_active_raffle_with(&env, |config| {
+            config.max_tickets = 1;
+            config.max_tickets_per_tx = 1;
+            config.prize_vesting_seconds = 1_000;
+        });
+    let payment_token = token_mint.address.clone();
+    client.buy_tickets(&buyer, &1, &0);
+    client.finalize_raffle();
+
+    let start = env.ledger().timestamp() + DEFAULT_CLAIM_LOCKUP_SECONDS + 1;
+    env.ledger().set_timestamp(start);
+    let token = soroban_sdk::token::Client::new(&env, &payment_token);
+    let balance_before = token.balance(&buyer);
+
+    let total = client.claim_prize(&buyer, &0u32);
+    assert_eq!(token.balance(&buyer), balance_before);
+    assert_eq!(
+        client.try_withdraw_vested(&0u32),
+        Err(Ok(Error::NothingToWithdraw))
+    );
+
+    env.ledger().set_timestamp(start + 250);
+    client.pause();
+    assert_eq!(
+        client.try_withdraw_vested(&0u32),
+        Err(Ok(Error::ContractPaused))
+    );
+    client.unpause();
+    env.as_contract(&factory, || {
+        env.storage().instance().set(&buyer, &true);
+    });
+    assert_eq!(
+        client.try_withdraw_vested(&0u32),
+        Err(Ok(Error::AddressBlocked))
+    );
+    env.as_contract(&factory, || {
+        env.storage().instance().remove(&buyer);
+    });
+    assert_eq!(client.withdraw_vested(&0u32), total / 4);
+
+    env.ledger().set_timestamp(start + 5_000);
+    assert_eq!(client.withdraw_vested(&0u32), total - total / 4);
+    assert_eq!(token.balance(&buyer), balance_before + total);
+
+    let schedule = client.get_vesting_schedule(&0u32).unwrap();
+    assert_eq!(schedule.withdrawn, schedule.total);
+    assert_eq!(
+        client.try_withdraw_vested(&1u32),
+        Err(Ok(Error::NoVestingSchedule))
+    );
+}
+
+#[test]
+fn dispute_window_holds_claims_and_arbiter_can_void_the_draw() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, _creator, buyer, _factory, _token_mint) = setup_active_raffle(&env);
+    let arbiter = Address::generate(&env);
+
+    client.set_dispute_window(&arbiter, &(DEFAULT_CLAIM_LOCKUP_SECONDS * 2));
+    client.buy_tickets(&buyer, &1, &0);
+    client.finalize_raffle();
+    let finalized_at = client.get_raffle().finalized_at.unwrap();
+
+    // Past the claim lockup but still inside the dispute window.
+    env.ledger()
+        .set_timestamp(finalized_at + DEFAULT_CLAIM_LOCKUP_SECONDS + 1);
+    assert_eq!(
+        client.try_claim_prize(&buyer, &0u32),
+        Err(Ok(Error::DisputeWindowOpen))
+    );
+
+    client.open_dispute(&String::from_str(&env, "seed reuse"));
+    assert!(client.is_disputed());
+    client.resolve_dispute(&DisputeResolution::Upheld);
+    assert!(!client.is_disputed());
+    assert_eq!(
+        client.try_resolve_dispute(&DisputeResolution::Refund),
+        Err(Ok(Error::NoOpenDispute))
+    );
+
+    client.open_dispute(&String::from_str(&env, "oracle collusion"));
+    client.resolve_dispute(&DisputeResolution::Refund);
+    assert_eq!(client.get_raffle().status, RaffleStatus::Cancelled);
+    assert_eq!(
+        client.refund_ticket(&1u32),
+        client.get_raffle().ticket_price
+    );
+
+    env.ledger()
+        .set_timestamp(finalized_at + DEFAULT_CLAIM_LOCKUP_SECONDS * 2);
+    assert_eq!(
+        client.try_open_dispute(&String::from_str(&env, "late")),
+        Err(Ok(Error::InvalidStatus))
+    );
+}
+
+#[test]
+fn redraw_voids_an_internal_draw_and_draws_again() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, _creator, buyer, _factory, _token_mint) = setup_active_raffle(&env);
+    let contract_id = client.address.clone();
+    let arbiter = Address::generate(&env);
+
+    client.set_dispute_window(&arbiter, &DEFAULT_CLAIM_LOCKUP_SECONDS);
+    client.buy_tickets(&buyer, &1, &0);
+    client.finalize_raffle();
+    assert_drawing_lock_cleared(&env, &contract_id);
+
+    env.ledger().with_mut(|l| l.sequence_number += 100);
+    client.open_dispute(&String::from_str(&env, "seed reuse"));
+    client.resolve_dispute(&DisputeResolution::Redraw);
+    let raffle = client.get_raffle();
+    assert_eq!(raffle.status, RaffleStatus::Drawing);
+    assert!(raffle.winners.is_empty());
+    assert!(client.get_randomness_request().is_none());
+    let request_ledger: u32 = env.as_contract(&contract_id, || {
+        env.storage()
+            .instance()
+            .get(&DataKey::RandomnessRequestLedger)
+            .unwrap()
+    });
+    assert_eq!(request_ledger, env.ledger().sequence());
+
+    client.finalize_raffle();
+    assert_eq!(client.get_raffle().status, RaffleStatus::Finalized);
+    assert_eq!(client.get_winner(), Some(buyer));
+    assert_drawing_lock_cleared(&env, &contract_id);
+}
+
+#[test]
+fn redraw_asks_the_oracle_for_a_fresh_request() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let signing_key = SigningKey::from_bytes(&[5u8; 32]);
+    let (token_addr, _) = create_token(&env, &Address::generate(&env));
+    let mut config = external_config(&env, &token_addr, &Address::generate(&env));
+    config.max_tickets = 1;
+    config.max_tickets_per_tx = 1;
+    config.oracle_public_key = Some(BytesN::from_array(
+        &env,
+        &signing_key.verifying_key().to_bytes(),
+    ));
+    let (contract_id, client, _admin, creator) = init_raffle(&env, &config);
+    let arbiter = Address::generate(&env);
+    client.set_dispute_window(&arbiter, &DEFAULT_CLAIM_LOCKUP_SECONDS);
+
+    client.deposit_prize();
+    client.buy_tickets(&creator, &1, &0);
+    let first_id = client.get_randomness_request().unwrap().request_id;
+    let proof = oracle_signature(&env, &signing_key, &contract_id, first_id, 424_242);
+    client.provide_randomness(&424_242, &proof, &first_id);
+
+    client.open_dispute(&String::from_str(&env, "oracle collusion"));
+    client.resolve_dispute(&DisputeResolution::Redraw);
+    assert!(client.get_randomness_request().is_none());
+    assert_eq!(
+        client.try_provide_randomness(&424_242, &proof, &first_id),
+        Err(Ok(Error::RandomnessAlreadyFulfilled))
+    );
+
+    env.ledger().with_mut(|l| l.sequence_number += 1);
+    client.finalize_raffle();
+    let second_id = client.get_randomness_request().unwrap().request_id;
+    assert_ne!(second_id, first_id);
+    let proof = oracle_signature(&env, &signing_key, &contract_id, second_id, 99);
+    client.provide_randomness(&99, &proof, &second_id);
+    assert_eq!(client.get_raffle().status, RaffleStatus::Finalized);
+    assert_drawing_lock_cleared(&env, &contract_id);
+}
+
+#[test]
+fn dispute_outcomes_leave_the_raffle_solvent() {
+    for resolution in [
+        DisputeResolution::Upheld,
+        DisputeResolution::Redraw,
+        DisputeResolution::Refund,
+    ] {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().set_timestamp(1_000);
+        let treasury = Address::generate(&env);
+        let (client, _admin, _creator, buyer, _factory, token_mint) =
+            setup_active_raffle_with(&env, |config| {
+                config.max_tickets = 2;
+                config.max_tickets_per_tx = 2;
+                // Two half-shares of an odd pool leave a unit of dust.
+                config.prize_amount = MIN_TICKET_PRICE * 10 + 1;
+                config.prizes = vec![&env, 5_000u32, 5_000];
+                config.protocol_fee_bp = 1_000;
+                config.treasury_address = Some(treasury.clone());
+            });
+        let token_addr = token_mint.address.clone();
+        let token = token::Client::new(&env, &token_addr);
+        let arbiter = Address::generate(&env);
+        let keeper = Address::generate(&env);
+        let assert_solvent = || assert_eq!(client.check_solvency(&token_addr).surplus, 0);
+        client.set_dispute_window(&arbiter, &DEFAULT_CLAIM_LOCKUP_SECONDS);
+        client.set_keeper_reward(&KeeperReward::Bps(100));
+        client.buy_tickets(&buyer, &2, &0);
+        assert_solvent();
+
+        // Dust, fees and the keeper reward are all held while the draw can
+        // still be disputed.
+        client.keeper_finalize(&keeper);
+        assert_eq!(token.balance(&keeper), 0);
+        assert_eq!(token.balance(&treasury), 0);
+        assert_eq!(
+            client.try_remit_fees(&token_addr),
+            Err(Ok(Error::DisputeWindowOpen))
+        );
+        assert_eq!(
+            client.try_release_keeper_reward(),
+            Err(Ok(Error::DisputeWindowOpen))
+        );
+        assert_solvent();
+
+        client.open_dispute(&String::from_str(&env, "seed reuse"));
+        client.resolve_dispute(&resolution);
+        assert_solvent();
+
+        let reward = MIN_TICKET_PRICE * 2 / 100;
+        let fees = MIN_TICKET_PRICE * 2 / 10;
+        match resolution {
+            DisputeResolution::Refund => {
+                assert_eq!(client.get_accrued_fees(&token_addr), 0);
+                client.batch_refund_tickets(&buyer, &client.get_my_tickets(&buyer));
+                assert_eq!(token.balance(&buyer), 1_000_000);
+                assert_eq!(
+                    client.try_release_keeper_reward(),
+                    Err(Ok(Error::InvalidStatus))
+                );
+            }
+            _ => {
+                if resolution == DisputeResolution::Redraw {
+                    assert_eq!(client.get_accrued_fees(&token_addr), fees);
+                    client.keeper_finalize(&keeper);
+                    assert_solvent();
+                }
+                env.ledger()
+                    .set_timestamp(env.ledger().timestamp() + DEFAULT_CLAIM_LOCKUP_SECONDS);
+                assert_eq!(client.release_keeper_reward(), reward);
+                assert_eq!(client.remit_fees(&token_addr), fees + 1);
+                assert_eq!(token.balance(&keeper), reward);
+                assert_eq!(token.balance(&treasury), fees + 1);
+            }
+        }
+        assert_solvent();
+    }
+}
+
+/// The host rejects a contract calling back into itself before the guard is
+/// reached, so the guard is taken directly, as a nested call would find it.
+#[test]
+fn guarded_entrypoints_refuse_to_run_while_the_guard_is_held() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, winner) = setup_finalized_raffle(&env);
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + DEFAULT_CLAIM_LOCKUP_SECONDS + 1);
+
+    env.as_contract(&client.address, || {
+        acquire_guard(&env).unwrap();
+        assert_eq!(Guard::new(&env).err(), Some(Error::Reentrancy));
+    });
+    assert_eq!(
+        client.try_claim_prize(&winner, &0u32),
+        Err(Ok(Error::Reentrancy))
+    );
+    assert_eq!(client.get_raffle().status, RaffleStatus::Finalized);
+
+    env.as_contract(&client.address, || release_guard(&env));
+    let claimed = client.claim_prize(&winner, &0u32);
+    assert_eq!(claimed, client.get_raffle().prize_amount);
+    assert_eq!(client.get_raffle().status, RaffleStatus::Claimed);
+    env.as_contract(&client.address, || {
+        assert!(!env.storage().instance().has(&DataKey::ReentrancyGuard));
+    });
+}
+
+#[test]
+fn check_solvency_tracks_obligations_and_flags_drift() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (other_token, _) = create_token(&env, &Address::generate(&env));
+
+    let (client, _admin, _creator, buyer, _factory, token_mint) =
+        setup_active_raffle_with(&env, |_| {});
+    let raffle = client.get_raffle();
+    let contract_id = client.address.clone();
+    let payment_token = token_mint.address.clone();
+    client.buy_tickets(&buyer, &2, &0);
+
+    let report = client.check_solvency(&payment_token);
+    assert_eq!(
+        report.balance,
+        raffle.prize_amount + 2 * raffle.ticket_price
+    );
+    assert_eq!(report.obligations, report.balance);
+    assert_eq!(report.surplus, 0);
+
+    let unrelated = client.check_solvency(&other_token);
+    assert_eq!(unrelated.balance, 0);
+    assert_eq!(unrelated.obligations, 0);
+
+    // Fees booked without the tokens to back them show up as a deficit.
+    env.as_contract(&contract_id, || {
+        env.storage()
+            .instance()
+            .set(&DataKey::AccruedFees(payment_token.clone()), &1i128);
+    });
+    assert_eq!(client.check_solvency(&payment_token).surplus, -1);
+}
+
+#[test]
+fn solvency_counts_ticket_revenue_and_fees_once() {
+    use crate::testutils::{fast_forward_and_finalize, mint_and_buy, setup_raffle_env_with};
+
+    let env = Env::default();
+    let treasury = Address::generate(&env);
+    let setup = setup_raffle_env_with(&env, |config| {
+        config.protocol_fee_bp = 1_000;
+        config.treasury_address = Some(treasury.clone());
+    });
+    let raffle = &setup.raffle;
+    let prize = raffle.get_raffle().prize_amount;
+
+    // Purchase fees are part of the ticket revenue, not owed on top of it.
+    mint_and_buy(&env, raffle, &Address::generate(&env), 2);
+    let report = raffle.check_solvency(&setup.token);
+    assert_eq!(report.obligations, prize + 2 * MIN_TICKET_PRICE);
+    assert_eq!(report.surplus, 0);
+
+    // Once drawn, the unpaid proceeds stay owed until they are withdrawn.
+    fast_forward_and_finalize(&env, raffle);
+    let fee = raffle.get_accrued_fees(&setup.token);
+    let report = raffle.check_solvency(&setup.token);
+    assert_eq!(report.obligations, prize + 2 * MIN_TICKET_PRICE);
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + DEFAULT_CLAIM_LOCKUP_SECONDS + 1);
+    let proceeds = raffle.withdraw_proceeds(&setup.creator);
+    assert_eq!(proceeds + fee, 2 * MIN_TICKET_PRICE);
+    assert_eq!(raffle.check_solvency(&setup.token).obligations, prize + fee);
+    raffle.remit_fees(&setup.token);
+    assert_eq!(raffle.check_solvency(&setup.token).surplus, 0);
+
+    // A cancelled raffle owes each ticket back once, whichever path pays it.
+    let cancelled = setup_raffle_env_with(&env, |config| {
+        config.protocol_fee_bp = 1_000;
+        config.treasury_address = Some(treasury.clone());
+    });
+    let buyer = Address::generate(&env);
+    mint_and_buy(&env, &cancelled.raffle, &buyer, 2);
+    cancelled
+        .raffle
+        .cancel_raffle(&CancelReason::CreatorCancelled);
+    let owed = cancelled
+        .raffle
+        .check_solvency(&cancelled.token)
+        .obligations;
+    cancelled.raffle.refund_ticket(&1u32);
+    assert_eq!(
+        cancelled
+            .raffle
+            .batch_refund_tickets(&buyer, &vec![&env, 1u32, 2u32]),
+        MIN_TICKET_PRICE
+    );
+    let report = cancelled.raffle.check_solvency(&cancelled.token);
+    assert_eq!(report.obligations, owed - 2 * MIN_TICKET_PRICE);
+    assert!(report.surplus >= 0);
+}
+
+#[test]
+fn ticket_counters_report_overflow_instead_of_wrapping() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, _creator, buyer, _factory, _token_mint) = setup_active_raffle(&env);
+
+    env.as_contract(&client.address, || {
+        let mut raffle: Raffle = env.storage().instance().get(&DataKey::Raffle).unwrap();
+        raffle.tickets_sold = u32::MAX;
+        raffle.max_tickets = u32::MAX;
+        raffle.max_tickets_per_tx = u32::MAX;
+        env.storage().instance().set(&DataKey::Raffle, &raffle);
+    });
+
+    assert_eq!(
+        client.try_buy_tickets(&buyer, &1, &0),
+        Err(Ok(Error::ArithmeticOverflow))
+    );
+}
+
+#[test]
+fn protocol_fee_above_the_cap_is_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let contract_id = env.register(Contract, ());
+    let client = ContractClient::new(&env, &contract_id);
+    let factory = env.register(MockFactory, ());
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+    token_mint.mint(&creator, &1_000_000);
+
+    let mut config = test_raffle_config(&env, &token_addr);
+    config.protocol_fee_bp = MAX_PROTOCOL_FEE_BP + 1;
+    assert_eq!(
+        client.try_init(&factory, &admin, &creator, &config),
+        Err(Ok(Error::InvalidFee))
+    );
+
+    config.protocol_fee_bp = MAX_PROTOCOL_FEE_BP;
+    client.init(&factory, &admin, &creator, &config);
+    assert_eq!(
+        client.try_set_protocol_fee_bp(&(MAX_PROTOCOL_FEE_BP + 1)),
+        Err(Ok(Error::InvalidFee))
+    );
+}
+
+#[test]
+fn participants_are_counted_once_in_purchase_order() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+
+    let (client, _admin, _creator, _buyer, _factory, token_mint) =
+        setup_active_raffle_with(&env, |_| {});
+    token_mint.mint(&alice, &1_000_000);
+    token_mint.mint(&bob, &1_000_000);
+
+    assert_eq!(client.get_participant_count(), 0);
+
+    client.buy_tickets(&alice, &2, &0);
+    client.buy_tickets(&bob, &1, &0);
+    client.buy_tickets(&alice, &1, &0);
+
+    assert_eq!(client.get_participant_count(), 2);
+    let stats = client.get_stats();
+    assert_eq!(stats.tickets_sold, 4);
+    assert_eq!(stats.unique_participants, 2);
+
+    let page = client.get_participants(&PaginationParams {
+        limit: 1,
+        offset: 0,
+    });
+    assert_eq!(page.items, Vec::from_array(&env, [alice.clone()]));
+    assert_eq!(page.total, 2);
+    assert!(page.has_more);
+
+    let page = client.get_participants(&PaginationParams {
+        limit: 0,
+        offset: 1,
+    });
+    assert_eq!(page.items, Vec::from_array(&env, [bob]));
+    assert!(!page.has_more);
+}
+
+#[test]
+fn sales_history_buckets_purchases_by_hour() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(10 * 3_600 + 5);
+
+    let (client, _admin, _creator, buyer, _factory, _token_mint) =
+        setup_active_raffle_with(&env, |_| {});
+    let raffle = client.get_raffle();
+
+    client.buy_tickets(&buyer, &2, &0);
+    client.buy_tickets(&buyer, &1, &0);
+    env.ledger().set_timestamp(11 * 3_600 + 59);
+    client.buy_tickets(&buyer, &3, &0);
+
+    let history = client.get_sales_history(&9, &11);
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.get(0).unwrap().tickets, 0);
+    assert_eq!(
+        history.get(1).unwrap(),
+        SalesBucket {
+            hour: 10,
+            tickets: 3,
+            revenue: 3 * raffle.ticket_price,
+        }
+    );
+    assert_eq!(history.get(2).unwrap().tickets, 3);
+
+    assert!(client.get_sales_history(&11, &10).is_empty());
+    assert_eq!(
+        client.get_sales_history(&0, &u64::MAX).len(),
+        MAX_PAGE_LIMIT
+    );
+}
+
+#[test]
+fn refund_owed_counts_unrefunded_tickets_once_cancelled() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let (client, _admin, _creator, buyer, _factory, _token_mint) =
+        setup_active_raffle_with(&env, |_| {});
+    let raffle = client.get_raffle();
+    client.buy_tickets(&buyer, &3, &0);
+    assert_eq!(client.get_refund_owed(&buyer), 0);
+
+    client.cancel_raffle(&CancelReason::CreatorCancelled);
+    assert_eq!(client.get_refund_owed(&buyer), 3 * raffle.ticket_price);
+
+    let first = client.get_my_tickets(&buyer).get(0).unwrap();
+    client.refund_ticket(&first);
+    assert_eq!(client.get_refund_owed(&buyer), 2 * raffle.ticket_price);
+}
+
+#[test]
+fn claim_all_collects_every_tier_in_one_transfer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let (client, _admin, _creator, buyer, _factory, token_mint) =
+        setup_active_raffle_with(&env, |config| {
+            config.max_tickets = 2;
+            config.max_tickets_per_tx = 2;
+            config.prizes = Vec::from_array(&env, [6_000u32, 4_000]);
+        });
+    let raffle = client.get_raffle();
+    let token_addr = token_mint.address.clone();
+    client.buy_tickets(&buyer, &2, &0);
+    client.finalize_raffle();
+
+    // Nothing is claimable until the lockup has passed.
+    assert_eq!(client.claim_all(&buyer), ClaimSummary::default());
+
+    env.ledger()
+        .with_mut(|l| l.timestamp += MAX_CLAIM_LOCKUP_SECONDS + MAX_DISPUTE_WINDOW_SECONDS);
+    let token = token::Client::new(&env, &token_addr);
+    let balance_before = token.balance(&buyer);
+
+    let summary = client.claim_all(&buyer);
+    assert_eq!(summary.prizes, raffle.prize_amount);
+    assert_eq!(summary.refunds, 0);
+    assert_eq!(token.balance(&buyer), balance_before + raffle.prize_amount);
+    assert_eq!(client.get_raffle().status, RaffleStatus::Claimed);
+
+    assert_eq!(client.claim_all(&buyer), ClaimSummary::default());
+}
+
+#[test]
+fn claim_all_refunds_every_ticket_of_a_cancelled_raffle() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, _creator, buyer, _factory, _token_mint) = setup_active_raffle(&env);
+    let raffle = client.get_raffle();
+
+    client.buy_tickets(&buyer, &1, &0);
+    client.cancel_raffle(&CancelReason::CreatorCancelled);
+
+    let summary = client.claim_all(&buyer);
+    assert_eq!(summary.refunds, raffle.ticket_price);
+    assert_eq!(client.get_refund_owed(&buyer), 0);
+    assert_eq!(
+        client.try_refund_ticket(&1),
+        Err(Ok(Error::PrizeAlreadyClaimed))
+    );
+}
+
+#[test]
+fn keeper_can_finalize_once_the_raffle_is_eligible() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, _creator, buyer, _factory, _token_mint) = setup_active_raffle(&env);
+    let keeper = Address::generate(&env);
+
+    assert_eq!(
+        client.try_keeper_finalize(&keeper),
+        Err(Ok(Error::InvalidStateTransition))
+    );
+
+    client.buy_tickets(&buyer, &1, &0);
+    client.keeper_finalize(&keeper);
+    assert_eq!(client.get_raffle().status, RaffleStatus::Finalized);
+    assert_eq!(client.get_winner(), Some(buyer));
+}
+
+#[test]
+fn keeper_is_paid_its_reward_when_it_draws_the_winners() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, _creator, buyer, _factory, token_mint) = setup_active_raffle(&env);
+    let keeper = Address::generate(&env);
+    let token = token::Client::new(&env, &token_mint.address);
+
+    assert_eq!(
+        client.try_set_keeper_reward(&KeeperReward::Bps(MAX_KEEPER_REWARD_BP + 1)),
+        Err(Ok(Error::InvalidParameters))
+    );
+    client.set_keeper_reward(&KeeperReward::Bps(100));
+    assert_eq!(client.get_keeper_reward(), KeeperReward::Bps(100));
+
+    client.buy_tickets(&buyer, &1, &0);
+    assert_eq!(
+        client.try_set_keeper_reward(&KeeperReward::Flat(1)),
+        Err(Ok(Error::InvalidStatus))
+    );
+
+    client.keeper_finalize(&keeper);
+    assert_eq!(token.balance(&keeper), MIN_TICKET_PRICE / 100);
+}
+
+#[test]
+fn auto_finalize_draws_the_winners_in_the_sell_out_purchase() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(Contract, ());
+    let client = ContractClient::new(&env, &contract_id);
+    let factory = env.register(MockFactory, ());
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let sac = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    let (token_addr, token_mint) = (sac.address(), StellarAssetClient::new(&env, &sac.address()));
+    token_mint.mint(&creator, &1_000_000);
+    let account = sac.issuer().address();
+    let wrapper = env.register(MockFactory, ());
+    token_mint.mint(&wrapper, &1_000_000);
+
+    let mut config = test_raffle_config(&env, &token_addr);
+    config.max_tickets = 1;
+    config.max_tickets_per_tx = 1;
+    client.init(&factory, &admin, &creator, &config);
+    client.deposit_prize();
+
+    assert!(!client.get_auto_finalize());
+    client.set_auto_finalize(&true);
+    assert!(client.get_auto_finalize());
+
+    // A contract could revert the purchase whenever the draw goes against it.
+    assert_eq!(
+        client.try_buy_tickets(&wrapper, &1, &0),
+        Err(Ok(Error::ContractBuyerNotAllowed))
+    );
+
+    client.buy_tickets(&account, &1, &0);
+    assert_eq!(client.get_raffle().status, RaffleStatus::Finalized);
+    assert_eq!(client.get_winner(), Some(account));
+    assert_eq!(
+        client.try_set_auto_finalize(&false),
+        Err(Ok(Error::InvalidStatus))
+    );
+}
+
+#[test]
+fn stale_raffle_can_be_expired_by_anyone_after_the_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let (client, _admin, creator, buyer, _factory, token_mint) =
+        setup_active_raffle_with(&env, |config| {
+            config.no_deadline = false;
+            config.end_time = 2_000;
+        });
+    let raffle = client.get_raffle();
+    let token_addr = token_mint.address.clone();
+    let token = token::Client::new(&env, &token_addr);
+
+    client.buy_tickets(&buyer, &2, &0);
+    let creator_before = token.balance(&creator);
+
+    env.ledger()
+        .set_timestamp(2_000 + RAFFLE_EXPIRY_WINDOW_SECONDS - 1);
+    assert_eq!(
+        client.try_expire_raffle(&buyer),
+        Err(Ok(Error::InvalidStateTransition))
+    );
+
+    env.ledger()
+        .set_timestamp(2_000 + RAFFLE_EXPIRY_WINDOW_SECONDS);
+    client.expire_raffle(&buyer);
+    assert_eq!(client.get_raffle().status, RaffleStatus::Cancelled);
+    assert_eq!(
+        token.balance(&creator),
+        creator_before + raffle.prize_amount
+    );
+    assert_eq!(client.get_refund_owed(&buyer), 2 * raffle.ticket_price);
+}
+
+#[test]
+fn paused_sales_still_allow_finalization_after_end_time() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let (client, _admin, creator, buyer, _factory, _token_mint) =
+        setup_active_raffle_with(&env, |config| {
+            config.no_deadline = false;
+            config.end_time = 2_000;
+        });
+    client.buy_tickets(&buyer, &1, &0);
+
+    client.pause_ticket_sales(&creator);
+    assert_eq!(
+        client.try_buy_tickets(&buyer, &1, &0),
+        Err(Ok(Error::ContractPaused))
+    );
+
+    env.ledger().set_timestamp(2_000);
+    client.finalize_raffle();
+    assert_eq!(client.get_raffle().status, RaffleStatus::Finalized);
+    assert_eq!(client.get_winner(), Some(buyer));
+}
+
+#[test]
+fn raffle_limits_only_move_in_buyers_favour_once_sales_start() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let (client, _admin, _creator, buyer, _factory, _token_mint) =
+        setup_active_raffle_with(&env, |config| {
+            config.no_deadline = false;
+            config.end_time = 5_000;
+        });
+
+    // Before any sale the limits may be tightened.
+    client.update_raffle_limits(&5, &4_000);
+    assert_eq!(client.get_raffle().max_tickets, 5);
+    assert_eq!(client.get_raffle().end_time, 4_000);
+
+    client.buy_tickets(&buyer, &1, &0);
+    assert_eq!(
+        client.try_update_raffle_limits(&4, &4_000),
+        Err(Ok(Error::InvalidParameters))
+    );
+    assert_eq!(
+        client.try_update_raffle_limits(&5, &3_000),
+        Err(Ok(Error::InvalidParameters))
+    );
+
+    client.update_raffle_limits(&8, &6_000);
+    let raffle = client.get_raffle();
+    assert_eq!(raffle.max_tickets, 8);
+    assert_eq!(raffle.end_time, 6_000);
+}
+
+#[test]
+fn operators_can_deposit_pause_and_finalize_for_the_creator() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let contract_id = env.register(Contract, ());
+    let client = ContractClient::new(&env, &contract_id);
+    let factory = env.register(MockFactory, ());
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let operator = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+    token_mint.mint(&operator, &1_000_000);
+    token_mint.mint(&buyer, &1_000_000);
+    let token = token::Client::new(&env, &token_addr);
+
+    let config = test_raffle_config(&env, &token_addr);
+    client.init(&factory, &admin, &creator, &config);
+
+    assert_eq!(
+        client.try_operator_deposit_prize(&operator),
+        Err(Ok(Error::NotAuthorized))
+    );
+    client.add_operator(&operator);
+    assert_eq!(client.get_operators(), vec![&env, operator.clone()]);
+    assert_eq!(
+        client.try_add_operator(&operator),
+        Err(Ok(Error::InvalidParameters))
+    );
+
+    client.operator_deposit_prize(&operator);
+    assert_eq!(client.get_raffle().status, RaffleStatus::Active);
+    assert_eq!(token.balance(&operator), 1_000_000 - config.prize_amount);
+
+    client.pause_ticket_sales(&operator);
+    assert_eq!(
+        client.try_pause_ticket_sales(&stranger),
+        Err(Ok(Error::NotAuthorized))
+    );
+    client.resume_ticket_sales(&operator);
+    client.buy_tickets(&buyer, &config.max_tickets, &0);
+
+    assert_eq!(
+        client.try_operator_finalize(&stranger),
+        Err(Ok(Error::NotAuthorized))
+    );
+    client.operator_finalize(&operator);
+    assert_eq!(client.get_raffle().status, RaffleStatus::Finalized);
+
+    client.remove_operator(&operator);
+    assert!(client.get_operators().is_empty());
+}
+
+#[test]
+fn proceeds_are_split_between_revenue_owners_by_share() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let contract_id = env.register(Contract, ());
+    let client = ContractClient::new(&env, &contract_id);
+    let factory = env.register(MockFactory, ());
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let partner = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+    token_mint.mint(&creator, &1_000_000);
+    token_mint.mint(&buyer, &1_000_000);
+    let token = token::Client::new(&env, &token_addr);
+
+    let config = test_raffle_config(&env, &token_addr);
+    client.init(&factory, &admin, &creator, &config);
+    client.deposit_prize();
+
+    assert_eq!(
+        client.try_set_revenue_owners(&vec![&env, (partner.clone(), 10_000u32)]),
+        Err(Ok(Error::InvalidParameters))
+    );
+    assert_eq!(
+        client.try_set_revenue_owners(&vec![
             &env,
-            raffle_shared::TicketBundle { quantity: 5, price_per_ticket: 90_000 },
-            raffle_shared::TicketBundle { quantity: 10, price_per_ticket: 80_000 },
-            raffle_shared::TicketBundle { quantity: 20, price_per_ticket: 70_000 },
-        ],
+            (creator.clone(), 7_000u32),
+            (partner.clone(), 2_000u32)
+        ]),
+        Err(Ok(Error::InvalidParameters))
+    );
+    client.set_revenue_owners(&vec![
+        &env,
+        (creator.clone(), 7_000u32),
+        (partner.clone(), 3_000u32),
+    ]);
+
+    client.buy_tickets(&buyer, &config.max_tickets, &0);
+    assert_eq!(
+        client.try_withdraw_proceeds(&partner),
+        Err(Ok(Error::InvalidStatus))
+    );
+    client.finalize_raffle();
+
+    let creator_before = token.balance(&creator);
+    let revenue = config.ticket_price * config.max_tickets as i128;
+    assert_eq!(client.withdraw_proceeds(&partner), revenue);
+    assert_eq!(token.balance(&partner), revenue * 3 / 10);
+    assert_eq!(token.balance(&creator), creator_before + revenue * 7 / 10);
+    assert_eq!(
+        client.try_withdraw_proceeds(&creator),
+        Err(Ok(Error::NothingToWithdraw))
+    );
+}
+
+#[test]
+fn listed_tickets_change_hands_with_a_creator_royalty() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let seller = Address::generate(&env);
+
+    let (client, _admin, creator, buyer, _factory, token_mint) =
+        setup_active_raffle_with(&env, |_| {});
+    let raffle = client.get_raffle();
+    let token_addr = token_mint.address.clone();
+    token_mint.mint(&seller, &1_000_000);
+    let token = token::Client::new(&env, &token_addr);
+
+    client.set_resale_royalty(&500);
+
+    client.buy_tickets(&seller, &2, &0);
+    assert_eq!(
+        client.try_list_ticket(&buyer, &1, &50_000),
+        Err(Ok(Error::NotAuthorized))
+    );
+    client.list_ticket(&seller, &1, &50_000);
+    assert_eq!(
+        client.try_buy_listed_ticket(&2, &buyer),
+        Err(Ok(Error::TicketNotListed))
+    );
+
+    let seller_before = token.balance(&seller);
+    let creator_before = token.balance(&creator);
+    assert_eq!(client.buy_listed_ticket(&1, &buyer), 50_000);
+    assert_eq!(token.balance(&seller), seller_before + 47_500);
+    assert_eq!(token.balance(&creator), creator_before + 2_500);
+    assert_eq!(client.get_my_tickets(&buyer), vec![&env, 1u32]);
+    assert_eq!(client.get_my_tickets(&seller), vec![&env, 2u32]);
+    assert_eq!(client.get_listing(&1), None);
+
+    client.list_ticket(&seller, &2, &50_000);
+    client.pause_ticket_sales(&creator);
+    assert_eq!(
+        client.try_buy_listed_ticket(&2, &buyer),
+        Err(Ok(Error::ContractPaused))
+    );
+    client.resume_ticket_sales(&creator);
+
+    // Selling out starts the draw; the listing cannot be bought mid-draw.
+    client.buy_tickets(&buyer, &(raffle.max_tickets - 2), &0);
+    assert_eq!(
+        client.try_buy_listed_ticket(&2, &buyer),
+        Err(Ok(Error::DrawingAlreadyInProgress))
+    );
+    client.finalize_raffle();
+    assert_eq!(client.get_listing(&2), None);
+    assert_eq!(
+        client.try_list_ticket(&seller, &2, &50_000),
+        Err(Ok(Error::RaffleInactive))
+    );
+}
+
+#[test]
+fn prize_goes_to_the_holder_of_the_winning_ticket_nft() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let nft = ticket_nft::TicketNftClient::new(&env, &env.register(ticket_nft::TicketNft, ()));
+    let collector = Address::generate(&env);
+
+    let (client, _admin, _creator, buyer, _factory, token_mint) =
+        setup_active_raffle_with(&env, |config| {
+            config.end_time = 2_000;
+            config.no_deadline = false;
+            config.nft_contract = Some(nft.address.clone());
+        });
+    let raffle = client.get_raffle();
+    let contract_id = client.address.clone();
+    let token_addr = token_mint.address.clone();
+    let token = token::Client::new(&env, &token_addr);
+
+    client.buy_tickets(&buyer, &1, &0);
+    assert_eq!(nft.owner_of(&contract_id, &1), Some(buyer.clone()));
+    assert_eq!(
+        client.try_list_ticket(&buyer, &1, &50_000),
+        Err(Ok(Error::InvalidParameters))
+    );
+
+    env.ledger().set_timestamp(2_000);
+    client.finalize_raffle();
+    assert_eq!(client.get_raffle().winners, vec![&env, buyer.clone()]);
+    nft.transfer(&buyer, &collector, &contract_id, &1);
+
+    env.ledger()
+        .set_timestamp(2_000 + DEFAULT_CLAIM_LOCKUP_SECONDS + 1);
+    assert_eq!(
+        client.try_claim_prize(&buyer, &0),
+        Err(Ok(Error::NotWinner))
+    );
+    client.claim_prize(&collector, &0);
+    assert_eq!(token.balance(&collector), raffle.prize_amount);
+}
+
+/// Yield strategy that simply holds what it is sent; tests add yield by
+/// minting to it and simulate losses with `lose`.
+#[contract]
+pub struct MockYieldStrategy;
+
+#[contractimpl]
+impl MockYieldStrategy {
+    pub fn deposit(_env: Env, _depositor: Address, _token: Address, _amount: i128) {}
+
+    pub fn withdraw_all(env: Env, depositor: Address, token: Address) -> i128 {
+        let token = token::Client::new(&env, &token);
+        let held = token.balance(&env.current_contract_address());
+        token.transfer(&env.current_contract_address(), &depositor, &held);
+        held
+    }
+
+    pub fn lose(env: Env, token: Address, amount: i128, sink: Address) {
+        token::Client::new(&env, &token).transfer(&env.current_contract_address(), &sink, &amount);
+    }
+}
+
+#[test]
+fn prize_earns_yield_while_deployed_and_absorbs_shortfalls() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let factory = env.register(MockFactory, ());
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+    token_mint.mint(&creator, &1_000_000);
+    token_mint.mint(&buyer, &1_000_000);
+    let token = token::Client::new(&env, &token_addr);
+    let mut config = test_raffle_config(&env, &token_addr);
+    config.max_tickets = 1;
+    config.max_tickets_per_tx = 1;
+
+    // Yield is added to the pot at finalization.
+    let strategy = env.register(MockYieldStrategy, ());
+    let client = ContractClient::new(&env, &env.register(Contract, ()));
+    client.init(&factory, &admin, &creator, &config);
+    client.set_yield_strategy(&Some(YieldStrategy {
+        strategy: strategy.clone(),
+        yield_to_treasury: false,
+    }));
+    client.deposit_prize();
+    assert_eq!(client.get_deployed_prize(), config.prize_amount);
+    assert_eq!(token.balance(&strategy), config.prize_amount);
+    assert_eq!(
+        client.try_set_yield_strategy(&None),
+        Err(Ok(Error::PrizeAlreadyDeposited))
+    );
+
+    token_mint.mint(&strategy, &1_000);
+    client.buy_tickets(&buyer, &1, &0);
+    client.finalize_raffle();
+    assert_eq!(client.get_deployed_prize(), 0);
+    assert_eq!(
+        client.get_raffle().prize_amount,
+        config.prize_amount + 1_000
+    );
+    env.ledger()
+        .set_timestamp(1_000 + DEFAULT_CLAIM_LOCKUP_SECONDS + 1);
+    assert_eq!(client.claim_prize(&buyer, &0), config.prize_amount + 1_000);
+
+    // Cancelling recalls the prize; a shortfall comes out of the pot.
+    let strategy = env.register(MockYieldStrategy, ());
+    let client = ContractClient::new(&env, &env.register(Contract, ()));
+    client.init(&factory, &admin, &creator, &config);
+    client.set_yield_strategy(&Some(YieldStrategy {
+        strategy: strategy.clone(),
+        yield_to_treasury: false,
+    }));
+    client.deposit_prize();
+    MockYieldStrategyClient::new(&env, &strategy).lose(
+        &token_addr,
+        &1_000,
+        &Address::generate(&env),
+    );
+
+    client.cancel_raffle(&CancelReason::CreatorCancelled);
+    assert_eq!(client.get_deployed_prize(), 0);
+    assert_eq!(
+        client.get_raffle().prize_amount,
+        config.prize_amount - 1_000
+    );
+    assert_eq!(token.balance(&client.address), config.prize_amount - 1_000);
+}
+
+#[contract]
+pub struct MockPriceOracle;
+
+#[contractimpl]
+impl MockPriceOracle {
+    pub fn set_price(env: Env, price: i128, timestamp: u64) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("price"), &PriceData { price, timestamp });
+    }
+
+    pub fn lastprice(env: Env, _asset: Asset) -> Option<PriceData> {
+        env.storage().instance().get(&symbol_short!("price"))
+    }
+}
+
+#[test]
+fn pegged_ticket_price_follows_the_oracle_until_it_goes_stale() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let contract_id = env.register(Contract, ());
+    let client = ContractClient::new(&env, &contract_id);
+    let factory = env.register(MockFactory, ());
+    let oracle = env.register(MockPriceOracle, ());
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+    token_mint.mint(&creator, &1_000_000);
+    token_mint.mint(&buyer, &1_000_000);
+    let token = token::Client::new(&env, &token_addr);
+
+    let config = test_raffle_config(&env, &token_addr);
+    client.init(&factory, &admin, &creator, &config);
+    client.deposit_prize();
+    // $1 a ticket; the 7-decimal token trades at $100 on a 14-decimal feed.
+    let one_dollar = 100_000_000_000_000i128;
+    client.set_price_peg(&Some(PricePeg {
+        oracle: oracle.clone(),
+        reference_price: one_dollar,
+        max_age_seconds: 600,
+    }));
+    MockPriceOracleClient::new(&env, &oracle).set_price(&(one_dollar * 100), &1_000);
+    assert_eq!(client.get_ticket_price(), 100_000);
+
+    client.buy_tickets(&buyer, &1, &0);
+    assert_eq!(token.balance(&buyer), 1_000_000 - 100_000);
+    assert_eq!(
+        client.try_set_price_peg(&None),
+        Err(Ok(Error::InvalidStatus))
+    );
+
+    env.ledger().set_timestamp(1_601);
+    assert_eq!(client.get_ticket_price(), config.ticket_price);
+    client.buy_tickets(&buyer, &1, &0);
+    assert_eq!(
+        token.balance(&buyer),
+        1_000_000 - 100_000 - config.ticket_price
+    );
+}
+
+#[test]
+fn pegged_tickets_are_refunded_at_the_price_paid() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let contract_id = env.register(Contract, ());
+    let client = ContractClient::new(&env, &contract_id);
+    let factory = env.register(MockFactory, ());
+    let oracle = env.register(MockPriceOracle, ());
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+    token_mint.mint(&creator, &1_000_000);
+    token_mint.mint(&buyer, &1_000_000);
+    let token = token::Client::new(&env, &token_addr);
+
+    let config = test_raffle_config(&env, &token_addr);
+    client.init(&factory, &admin, &creator, &config);
+    client.deposit_prize();
+    let one_dollar = 100_000_000_000_000i128;
+    client.set_price_peg(&Some(PricePeg {
+        oracle: oracle.clone(),
+        reference_price: one_dollar,
+        max_age_seconds: 600,
+    }));
+    MockPriceOracleClient::new(&env, &oracle).set_price(&(one_dollar * 100), &1_000);
+    assert!(client.get_ticket_price() > config.ticket_price);
+
+    // One ticket at the pegged price, one at the floor once the feed is stale.
+    client.buy_tickets(&buyer, &1, &0);
+    env.ledger().set_timestamp(1_601);
+    client.buy_tickets(&buyer, &1, &0);
+    let paid = 1_000_000 - token.balance(&buyer);
+    assert_eq!(paid, 100_000 + config.ticket_price);
+
+    let surplus = client.check_solvency(&token_addr).surplus;
+    client.cancel_raffle(&CancelReason::CreatorCancelled);
+    assert_eq!(client.get_refund_owed(&buyer), paid);
+    assert_eq!(client.check_solvency(&token_addr).surplus, surplus);
+
+    let tickets = client.get_my_tickets(&buyer);
+    assert_eq!(client.refund_ticket(&tickets.get(0).unwrap()), 100_000);
+    assert_eq!(
+        client.batch_refund_tickets(&buyer, &Vec::from_array(&env, [tickets.get(1).unwrap()])),
+        config.ticket_price
+    );
+    assert_eq!(token.balance(&buyer), 1_000_000);
+    assert_eq!(client.get_refund_owed(&buyer), 0);
+    assert_eq!(client.check_solvency(&token_addr).surplus, surplus);
+}
+
+#[test]
+fn purchase_quote_reports_price_fee_and_token_decimals() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _admin, _creator, _buyer, _factory, token_mint) =
+        setup_active_raffle_with(&env, |config| {
+            config.early_bird_ticket_percentage = 50;
+            config.early_bird_discount_bp = 1_000;
+        });
+    let raffle = client.get_raffle();
+    let token_addr = token_mint.address.clone();
+
+    let quote = client.get_purchase_quote(&3);
+    assert_eq!(quote.token, token_addr);
+    assert_eq!(quote.decimals, NATIVE_DECIMALS);
+    assert!(!quote.native);
+    assert_eq!(quote.unit_price, raffle.ticket_price * 9 / 10);
+    assert_eq!(quote.total, quote.unit_price * 3);
+    assert_eq!(quote.protocol_fee, 0);
+}
+
+#[test]
+fn free_entry_raffle_limits_each_address_and_honours_the_allowlist() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(Contract, ());
+    let client = ContractClient::new(&env, &contract_id);
+    let factory = env.register(MockFactory, ());
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+    token_mint.mint(&creator, &1_000_000);
+
+    let mut config = test_raffle_config(&env, &token_addr);
+    config.ticket_price = 0;
+    assert_eq!(
+        client.try_init(&factory, &admin, &creator, &config),
+        Err(Ok(Error::InvalidParameters))
+    );
+    config.allow_multiple = false;
+    config.max_tickets_per_tx = 1;
+    client.init(&factory, &admin, &creator, &config);
+    client.deposit_prize();
+
+    // Entrants hold no tokens at all.
+    let first = Address::generate(&env);
+    assert_eq!(client.buy_tickets(&first, &1, &0), 1);
+    assert_eq!(
+        client.try_buy_tickets(&first, &1, &0),
+        Err(Ok(Error::MultipleTicketsNotAllowed))
+    );
+    assert_eq!(client.get_purchase_quote(&1).total, 0);
+
+    client.set_entry_gate(&Some(EntryGate::Allowlist));
+    let second = Address::generate(&env);
+    assert!(!client.is_eligible(&second));
+    assert_eq!(
+        client.try_buy_tickets(&second, &1, &0),
+        Err(Ok(Error::NotEligible))
+    );
+
+    client.set_allowlisted(&soroban_sdk::vec![&env, second.clone()], &true);
+    assert!(client.is_eligible(&second));
+    assert_eq!(client.buy_tickets(&second, &1, &0), 2);
+}
+
+#[test]
+fn tips_go_to_the_creator_and_pay_the_fee_only_when_opted_in() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let treasury = Address::generate(&env);
+
+    let (client, _admin, creator, buyer, _factory, token_mint) =
+        setup_active_raffle_with(&env, |config| {
+            config.protocol_fee_bp = 1_000;
+            config.treasury_address = Some(treasury.clone());
+            config.max_tickets = 1;
+            config.max_tickets_per_tx = 1;
+        });
+    let token_addr = token_mint.address.clone();
+    let token_client = soroban_sdk::token::Client::new(&env, &token_addr);
+
+    assert!(!client.get_fee_on_tips());
+    client.set_fee_on_tips(&true);
+
+    assert_eq!(
+        client.try_buy_tickets(&buyer, &1, &-1),
+        Err(Ok(Error::InvalidParameters))
+    );
+
+    let creator_before = token_client.balance(&creator);
+    client.buy_tickets(&buyer, &1, &1_000);
+    assert_eq!(token_client.balance(&creator) - creator_before, 900);
+    crate::testutils::fast_forward_and_finalize(&env, &client);
+    client.remit_fees(&token_addr);
+    assert_eq!(token_client.balance(&treasury), MIN_TICKET_PRICE / 10 + 100);
+    assert_eq!(
+        token_client.balance(&buyer),
+        1_000_000 - MIN_TICKET_PRICE - 1_000
+    );
+    assert_eq!(client.get_stats().total_tips, 1_000);
+    assert_eq!(
+        client.try_set_fee_on_tips(&false),
+        Err(Ok(Error::InvalidStatus))
+    );
+}
+
+#[test]
+fn proportional_mode_pays_every_ticket_in_pages() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let contract_id = env.register(Contract, ());
+    let client = ContractClient::new(&env, &contract_id);
+    let factory = env.register(MockFactory, ());
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+    let token_client = soroban_sdk::token::Client::new(&env, &token_addr);
+    token_mint.mint(&creator, &1_000_000);
+    token_mint.mint(&alice, &1_000_000);
+    token_mint.mint(&bob, &1_000_000);
+
+    let mut config = test_raffle_config(&env, &token_addr);
+    config.end_time = 2_000;
+    config.no_deadline = false;
+    client.init(&factory, &admin, &creator, &config);
+    client.set_distribution_mode(&DistributionMode::Proportional);
+    client.deposit_prize();
+    client.buy_tickets(&alice, &2, &0);
+    client.buy_tickets(&bob, &1, &0);
+    env.ledger().set_timestamp(2_000);
+    client.finalize_raffle();
+
+    let raffle = client.get_raffle();
+    assert_eq!(raffle.status, RaffleStatus::Finalized);
+    assert!(raffle.winners.is_empty());
+    assert_eq!(client.try_distribute(&0, &2), Err(Ok(Error::ClaimTooEarly)));
+
+    env.ledger()
+        .set_timestamp(2_000 + DEFAULT_CLAIM_LOCKUP_SECONDS + 1);
+    let share = config.prize_amount / 3;
+    let alice_before = token_client.balance(&alice);
+    assert_eq!(client.distribute(&0, &2), share * 2);
+    assert_eq!(token_client.balance(&alice) - alice_before, share * 2);
+    assert_eq!(client.get_distributed_tickets(), 2);
+    assert_eq!(
+        client.try_distribute(&0, &2),
+        Err(Ok(Error::NothingToWithdraw))
+    );
+
+    let bob_before = token_client.balance(&bob);
+    assert_eq!(client.distribute(&2, &2), share);
+    assert_eq!(token_client.balance(&bob) - bob_before, share);
+    assert_eq!(client.get_raffle().status, RaffleStatus::Claimed);
+}
+
+#[test]
+fn linear_decay_weights_early_tickets_in_the_odds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(Contract, ());
+    let client = ContractClient::new(&env, &contract_id);
+    let factory = env.register(MockFactory, ());
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let early = Address::generate(&env);
+    let late = Address::generate(&env);
+    let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+    token_mint.mint(&creator, &1_000_000);
+    token_mint.mint(&early, &1_000_000);
+    token_mint.mint(&late, &1_000_000);
+
+    let mut config = test_raffle_config(&env, &token_addr);
+    config.max_tickets = 4;
+    config.max_tickets_per_tx = 4;
+    client.init(&factory, &admin, &creator, &config);
+    assert_eq!(
+        client.try_set_ticket_weighting(&TicketWeighting::LinearDecay(1)),
+        Err(Ok(Error::InvalidParameters))
+    );
+    client.set_ticket_weighting(&TicketWeighting::LinearDecay(3));
+    client.deposit_prize();
+
+    client.buy_tickets(&early, &1, &0);
+    client.buy_tickets(&late, &3, &0);
+
+    // Weights 3 | 2, 1, 1.
+    assert_eq!(
+        client.get_win_odds(&early),
+        WinOdds {
+            weight: 3,
+            total_weight: 7,
+        }
+    );
+    assert_eq!(client.get_win_odds(&late).weight, 4);
+    assert_eq!(
+        client.try_set_ticket_weighting(&TicketWeighting::Uniform),
+        Err(Ok(Error::InvalidStatus))
+    );
+
+    client.finalize_raffle();
+    let proof = client.get_draw_proof().unwrap();
+    assert!(client.verify_draw(&proof));
+}
+
+#[test]
+fn new_buyer_limit_throttles_first_time_buyers_per_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_sequence_number(100);
+
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+
+    let (client, _admin, _creator, _buyer, _factory, token_mint) =
+        setup_active_raffle_with(&env, |_| {});
+    token_mint.mint(&first, &1_000_000);
+    token_mint.mint(&second, &1_000_000);
+
+    client.set_new_buyer_limit(&Some(NewBuyerLimit {
+        max_new_buyers: 1,
+        window_ledgers: 1,
+    }));
+
+    client.buy_tickets(&first, &1, &0);
+    assert_eq!(
+        client.try_buy_tickets(&second, &1, &0),
+        Err(Ok(Error::RateLimited))
+    );
+    // Existing holders are not throttled.
+    client.buy_tickets(&first, &1, &0);
+
+    env.ledger().set_sequence_number(101);
+    client.buy_tickets(&second, &1, &0);
+}
+
+#[test]
+fn disallowing_contract_buyers_rejects_contract_addresses_only() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(Contract, ());
+    let client = ContractClient::new(&env, &contract_id);
+    let factory = env.register(MockFactory, ());
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let sac = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    let (token_addr, token_mint) = (sac.address(), StellarAssetClient::new(&env, &sac.address()));
+    token_mint.mint(&creator, &1_000_000);
+    // `Address::generate` makes contract addresses; the asset's issuer is an
+    // account, and can spend the asset without holding a balance.
+    let account = sac.issuer().address();
+    let wrapper = env.register(MockFactory, ());
+    token_mint.mint(&wrapper, &1_000_000);
+
+    client.init(
+        &factory,
+        &admin,
+        &creator,
+        &test_raffle_config(&env, &token_addr),
+    );
+    client.deposit_prize();
+    client.set_disallow_contract_buyers(&true);
+    assert!(client.get_disallow_contract_buyers());
+
+    assert_eq!(
+        client.try_buy_tickets(&wrapper, &1, &0),
+        Err(Ok(Error::ContractBuyerNotAllowed))
+    );
+    assert_eq!(client.buy_tickets(&account, &1, &0), 1);
+
+    client.set_disallow_contract_buyers(&false);
+    assert_eq!(client.buy_tickets(&wrapper, &1, &0), 2);
+}
+
+#[contract]
+pub struct MockAttestation;
+
+#[contractimpl]
+impl MockAttestation {
+    pub fn attest(env: Env, buyer: Address) {
+        env.storage().persistent().set(&buyer, &true);
+    }
+
+    pub fn is_eligible(env: Env, buyer: Address) -> bool {
+        env.storage().persistent().get(&buyer).unwrap_or(false)
+    }
+
+    pub fn is_verified(env: Env, buyer: Address) -> bool {
+        Self::is_eligible(env, buyer)
+    }
+}
+
+#[test]
+fn attestation_hook_gates_purchases() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let verified = Address::generate(&env);
+    let unverified = Address::generate(&env);
+    let attestation = env.register(MockAttestation, ());
+    MockAttestationClient::new(&env, &attestation).attest(&verified);
+
+    let (client, _admin, _creator, _buyer, _factory, token_mint) =
+        setup_active_raffle_with(&env, |_| {});
+    token_mint.mint(&verified, &1_000_000);
+    token_mint.mint(&unverified, &1_000_000);
+
+    let bad_region = Compliance {
+        region: Some(String::from_str(&env, "USA")),
+        min_age: 18,
+        attestation: None,
+        kyc_required: false,
+    };
+    assert_eq!(
+        client.try_set_compliance(&Some(bad_region)),
+        Err(Ok(Error::InvalidParameters))
+    );
+
+    let compliance = Compliance {
+        region: Some(String::from_str(&env, "GB")),
+        min_age: 18,
+        attestation: Some(attestation),
+        kyc_required: false,
+    };
+    client.set_compliance(&Some(compliance.clone()));
+    assert_eq!(client.get_compliance(), Some(compliance));
+
+    assert!(!client.is_eligible(&unverified));
+    assert_eq!(
+        client.try_buy_tickets(&unverified, &1, &0),
+        Err(Ok(Error::NotEligible))
+    );
+    assert_eq!(client.buy_tickets(&verified, &1, &0), 1);
+}
+
+#[test]
+fn kyc_required_raffles_sell_only_to_registry_verified_buyers() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let verified = Address::generate(&env);
+
+    let (client, _admin, _creator, _buyer, factory, token_mint) =
+        setup_active_raffle_with(&env, |_| {});
+    token_mint.mint(&verified, &1_000_000);
+
+    client.set_compliance(&Some(Compliance {
+        region: None,
+        min_age: 0,
+        attestation: None,
+        kyc_required: true,
+    }));
+
+    // Without a registry on the factory nobody can be verified.
+    assert_eq!(
+        client.try_buy_tickets(&verified, &1, &0),
+        Err(Ok(Error::NotEligible))
+    );
+
+    let registry = env.register(MockAttestation, ());
+    MockFactoryClient::new(&env, &factory).set_kyc_registry(&registry);
+    MockAttestationClient::new(&env, &registry).attest(&verified);
+    assert_eq!(client.buy_tickets(&verified, &1, &0), 1);
+}
+
+#[test]
+fn insurance_route_takes_its_slice_of_the_purchase_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let treasury = Address::generate(&env);
+    let pool = Address::generate(&env);
+
+    let (client, _admin, _creator, buyer, factory, token_mint) =
+        setup_active_raffle_with(&env, |config| {
+            config.protocol_fee_bp = 1_000;
+            config.treasury_address = Some(treasury.clone());
+            config.max_tickets = 1;
+            config.max_tickets_per_tx = 1;
+        });
+    let token_addr = token_mint.address.clone();
+    MockFactoryClient::new(&env, &factory).set_insurance_route(&InsuranceRoute {
+        pool: pool.clone(),
+        share_bp: 2_500,
+    });
+
+    client.buy_tickets(&buyer, &1, &0);
+    crate::testutils::fast_forward_and_finalize(&env, &client);
+    client.remit_fees(&token_addr);
+
+    // 10 % fee on one ticket, a quarter of it to the pool.
+    let fee = MIN_TICKET_PRICE / 10;
+    let token = soroban_sdk::token::Client::new(&env, &token_addr);
+    assert_eq!(token.balance(&pool), fee / 4);
+    assert_eq!(token.balance(&treasury), fee - fee / 4);
+}
+
+#[test]
+fn vesting_proceeds_stream_out_after_finalization() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let contract_id = env.register(Contract, ());
+    let client = ContractClient::new(&env, &contract_id);
+    let factory = env.register(MockFactory, ());
+    let admin = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let (token_addr, token_mint) = create_token(&env, &Address::generate(&env));
+    token_mint.mint(&creator, &1_000_000);
+    token_mint.mint(&buyer, &1_000_000);
+    let token = token::Client::new(&env, &token_addr);
+
+    let mut config = test_raffle_config(&env, &token_addr);
+    config.proceeds_vesting_seconds = 1_000;
+    client.init(&factory, &admin, &creator, &config);
+    client.deposit_prize();
+    client.buy_tickets(&buyer, &config.max_tickets, &0);
+    assert_eq!(client.get_proceeds_vesting(), None);
+    client.finalize_raffle();
+
+    let revenue = config.ticket_price * config.max_tickets as i128;
+    let schedule = client.get_proceeds_vesting().unwrap();
+    assert_eq!(schedule.total, revenue);
+    assert_eq!(schedule.start, 1_000);
+    assert_eq!(
+        client.try_withdraw_vested_proceeds(&creator),
+        Err(Ok(Error::NothingToWithdraw))
+    );
+
+    let before = token.balance(&creator);
+    env.ledger().set_timestamp(1_250);
+    assert_eq!(client.withdraw_vested_proceeds(&creator), revenue / 4);
+    env.ledger().set_timestamp(5_000);
+    assert_eq!(
+        client.withdraw_vested_proceeds(&creator),
+        revenue - revenue / 4
+    );
+    assert_eq!(token.balance(&creator), before + revenue);
+    assert_eq!(
+        client.try_withdraw_proceeds(&creator),
+        Err(Ok(Error::NothingToWithdraw))
+    );
+}
+
+#[test]
+fn testutils_walk_a_raffle_from_purchase_to_claim() {
+    use crate::testutils::{
+        events_named, fast_forward_and_finalize, fast_forward_to_claims, mint_and_buy,
+        setup_raffle_env,
     };
 
-    client.init(&factory, &admin, &creator, &config);
+    let env = Env::default();
+    let setup = setup_raffle_env(&env);
+    let buyer = Address::generate(&env);
+
+    assert_eq!(mint_and_buy(&env, &setup.raffle, &buyer, 3), 3);
+    let purchases = events_named(&env, &setup.raffle.address, "ticket_purchased");
+    assert_eq!(purchases.len(), 1);
+    assert_eq!(purchases[0].field::<Address>(&env, "buyer"), buyer);
+    assert_eq!(purchases[0].field::<u32>(&env, "quantity"), 3);
+
+    let raffle = fast_forward_and_finalize(&env, &setup.raffle);
+    assert_eq!(raffle.status, RaffleStatus::Finalized);
+    assert_eq!(raffle.winners.get(0), Some(buyer.clone()));
+
+    fast_forward_to_claims(&env, &setup.raffle);
+    assert!(setup.raffle.claim_prize(&buyer, &0) > 0);
+}
+
+#[test]
+fn export_pages_tickets_and_includes_winning_tickets() {
+    use crate::testutils::{fast_forward_and_finalize, mint_and_buy, setup_raffle_env_with};
+
+    let env = Env::default();
+    let setup = setup_raffle_env_with(&env, |config| {
+        config.max_tickets = 5;
+        config.max_tickets_per_tx = 5;
+    });
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    mint_and_buy(&env, &setup.raffle, &alice, 3);
+    mint_and_buy(&env, &setup.raffle, &bob, 2);
+
+    let first = setup.raffle.export(&0, &2);
+    assert_eq!(first.stats.tickets_sold, 5);
+    assert_eq!(first.stats.unique_participants, 2);
+    assert!(first.winning_tickets.is_empty());
+    assert_eq!(first.tickets.total, 5);
+    assert!(first.tickets.has_more);
+    assert_eq!(first.tickets.items.len(), 2);
+    assert_eq!(first.tickets.items.get(0).unwrap().id, 1);
+
+    let last = setup.raffle.export(&4, &2);
+    assert!(!last.tickets.has_more);
+    assert_eq!(last.tickets.items.len(), 1);
+    assert_eq!(last.tickets.items.get(0).unwrap().owner, bob);
+
+    let raffle = fast_forward_and_finalize(&env, &setup.raffle);
+    let drawn = setup.raffle.export(&0, &0);
+    assert_eq!(drawn.tickets.items.len(), 5);
+    assert_eq!(drawn.winning_tickets.len(), 1);
+    assert_eq!(
+        drawn.winning_tickets.get(0).unwrap().owner,
+        raffle.winners.get(0).unwrap()
+    );
+}
+
+#[test]
+fn lifecycle_follows_the_raffle_through_its_phases() {
+    use crate::testutils::{mint_and_buy, setup_raffle_env};
+    use raffle_shared::LifecyclePhase;
+
+    let env = Env::default();
+    env.ledger().set_timestamp(1_000);
+    let setup = setup_raffle_env(&env);
+    let raffle = &setup.raffle;
+    let end_time = 1_000 + 86_400;
+
+    let now = raffle.get_lifecycle();
+    assert_eq!(now.phase, LifecyclePhase::Selling);
+    assert_eq!(now.sale_time_remaining, Some(86_400));
+    assert_eq!(raffle.get_time_remaining(), Some(86_400));
+    assert_eq!(
+        now.grace_deadline,
+        Some(end_time + RAFFLE_EXPIRY_WINDOW_SECONDS)
+    );
+    assert_eq!(now.claims_open_at, None);
+
+    let buyer = Address::generate(&env);
+    mint_and_buy(&env, raffle, &buyer, 1);
+    env.ledger().set_timestamp(end_time + 1);
+    assert_eq!(
+        raffle.get_lifecycle().phase,
+        LifecyclePhase::AwaitingFinalization
+    );
+    assert_eq!(raffle.get_time_remaining(), Some(0));
+
+    env.ledger()
+        .set_timestamp(end_time + RAFFLE_EXPIRY_WINDOW_SECONDS);
+    assert_eq!(raffle.get_lifecycle().phase, LifecyclePhase::Expirable);
+
+    raffle.finalize_raffle();
+    let finalized_at = env.ledger().timestamp();
+    let now = raffle.get_lifecycle();
+    assert_eq!(now.phase, LifecyclePhase::ClaimLocked);
+    assert_eq!(
+        now.claims_open_at,
+        Some(finalized_at + DEFAULT_CLAIM_LOCKUP_SECONDS)
+    );
+    assert_eq!(
+        now.claim_deadline,
+        Some(finalized_at + EMERGENCY_WITHDRAW_DELAY_SECONDS)
+    );
+
+    env.ledger()
+        .set_timestamp(finalized_at + DEFAULT_CLAIM_LOCKUP_SECONDS);
+    assert_eq!(raffle.get_lifecycle().phase, LifecyclePhase::Claimable);
+    raffle.claim_prize(&buyer, &0);
+    assert_eq!(raffle.get_lifecycle().phase, LifecyclePhase::Closed);
+}
+
+#[test]
+fn open_ended_raffle_sells_until_sold_out_and_only_then_finalizes() {
+    use crate::testutils::{mint_and_buy, setup_raffle_env_with};
+    use raffle_shared::{EndCondition, LifecyclePhase};
+
+    let env = Env::default();
+    env.ledger().set_timestamp(1_000);
+    // `end_time == 0` alone makes the raffle open-ended.
+    let setup = setup_raffle_env_with(&env, |config| {
+        config.end_time = 0;
+        config.max_tickets = 3;
+        config.max_tickets_per_tx = 3;
+    });
+    let raffle = &setup.raffle;
+    assert!(raffle.get_raffle().no_deadline);
+
+    let now = raffle.get_lifecycle();
+    assert_eq!(now.end_condition, EndCondition::SellOut);
+    assert_eq!(now.sale_time_remaining, None);
+    assert_eq!(now.grace_deadline, None);
+
+    let buyer = Address::generate(&env);
+    mint_and_buy(&env, raffle, &buyer, 2);
+    env.ledger().with_mut(|l| {
+        l.timestamp += 365 * 86_400;
+        l.sequence_number += RANDOMNESS_MIN_DELAY_LEDGERS;
+    });
+    assert_eq!(raffle.get_lifecycle().phase, LifecyclePhase::Selling);
+    assert_eq!(
+        raffle.try_finalize_raffle(),
+        Err(Ok(Error::InvalidStateTransition))
+    );
+    assert_eq!(
+        raffle.try_expire_raffle(&buyer),
+        Err(Ok(Error::InvalidStateTransition))
+    );
+
+    mint_and_buy(&env, raffle, &buyer, 1);
+    assert_eq!(raffle.get_lifecycle().phase, LifecyclePhase::Drawing);
+    raffle.finalize_raffle();
+    assert_eq!(raffle.get_raffle().status, RaffleStatus::Finalized);
+}
+
+#[test]
+fn ticket_ids_form_one_sequence_starting_at_one() {
+    use crate::testutils::{mint_and_buy, setup_raffle_env};
+
+    let env = Env::default();
+    let setup = setup_raffle_env(&env);
+    let raffle = &setup.raffle;
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    mint_and_buy(&env, raffle, &first, 2);
+    mint_and_buy(&env, raffle, &second, 3);
+
+    assert_eq!(
+        raffle.try_get_ticket(&0).err(),
+        Some(Ok(Error::TicketNotFound))
+    );
+    for ticket_id in 1..=5u32 {
+        let ticket = raffle.get_ticket(&ticket_id);
+        assert_eq!(ticket.id, ticket_id);
+        assert_eq!(ticket.ticket_number, ticket_id);
+        let owner = if ticket_id <= 2 { &first } else { &second };
+        assert_eq!(&ticket.owner, owner);
+    }
+    assert_eq!(
+        raffle.try_get_ticket(&6).err(),
+        Some(Ok(Error::TicketNotFound))
+    );
+    assert_eq!(
+        raffle.get_my_tickets(&second),
+        soroban_sdk::vec![&env, 3u32, 4, 5]
+    );
+
+    let export = raffle.export(&0, &10);
+    assert_eq!(export.tickets.items.len(), 5);
+    assert_eq!(export.tickets.items.get(0).unwrap().id, 1);
+}
+
+#[test]
+fn purchases_share_validation_whatever_the_quantity() {
+    use crate::testutils::{mint_and_buy, setup_raffle_env_with};
+
+    let env = Env::default();
+    let setup = setup_raffle_env_with(&env, |config| {
+        config.max_tickets = 5;
+        config.max_tickets_per_tx = 3;
+    });
+    let raffle = &setup.raffle;
+    let buyer = Address::generate(&env);
+    StellarAssetClient::new(&env, &setup.token).mint(&buyer, &(MIN_TICKET_PRICE * 10));
+
+    assert_eq!(
+        raffle.try_buy_tickets(&buyer, &0, &0),
+        Err(Ok(Error::InvalidQuantity))
+    );
+    assert_eq!(
+        raffle.try_buy_tickets(&buyer, &4, &0),
+        Err(Ok(Error::ExceedsMaxTicketsPerTx))
+    );
+    assert_eq!(mint_and_buy(&env, raffle, &buyer, 3), 3);
+    // A batch larger than what is left fails like a single ticket would
+    // once sold out.
+    assert_eq!(
+        raffle.try_buy_tickets(&buyer, &3, &0),
+        Err(Ok(Error::TicketsSoldOut))
+    );
+
+    // A single-ticket purchase that sells out moves the raffle on.
+    assert_eq!(mint_and_buy(&env, raffle, &buyer, 1), 4);
+    assert_eq!(mint_and_buy(&env, raffle, &buyer, 1), 5);
+    assert_eq!(raffle.get_raffle().status, RaffleStatus::Drawing);
+    assert_eq!(
+        raffle.try_buy_tickets(&buyer, &1, &0),
+        Err(Ok(Error::DrawingAlreadyInProgress))
+    );
+}
+
+#[test]
+fn tickets_go_on_sale_only_once_the_prize_is_deposited() {
+    use crate::testutils::{default_config, mint_and_buy, StubFactory};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let creator = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let config = default_config(&env, &token);
+    StellarAssetClient::new(&env, &token).mint(&creator, &config.prize_amount);
+    StellarAssetClient::new(&env, &token).mint(&buyer, &(MIN_TICKET_PRICE * 10));
+
+    let contract_id = env.register(Contract, ());
+    let client = ContractClient::new(&env, &contract_id);
+    let factory = env.register(StubFactory, ());
+    client.init(&factory, &Address::generate(&env), &creator, &config);
+    assert_eq!(
+        client.try_buy_tickets(&buyer, &1, &0),
+        Err(Ok(Error::PrizeNotDeposited))
+    );
+
+    // A raffle from before `PendingPrize` existed: open, nothing escrowed.
     env.as_contract(&contract_id, || {
-        env.storage().instance().remove(&DataKey::Factory);
+        let mut raffle = read_raffle(&env).unwrap();
+        raffle.status = RaffleStatus::Active;
+        write_raffle(&env, &raffle);
+    });
+    assert_eq!(
+        client.try_buy_tickets(&buyer, &1, &0),
+        Err(Ok(Error::PrizeNotDeposited))
+    );
+
+    client.deposit_prize();
+    assert_eq!(client.get_raffle().status, RaffleStatus::Active);
+    assert_eq!(mint_and_buy(&env, &client, &buyer, 1), 1);
+}
+
+#[test]
+fn unclaimed_refunds_are_swept_once_the_refund_window_closes() {
+    use crate::testutils::{mint_and_buy, setup_raffle_env};
+    use raffle_shared::constants::REFUND_CLAIM_WINDOW_SECONDS;
+
+    let env = Env::default();
+    let setup = setup_raffle_env(&env);
+    let raffle = &setup.raffle;
+    let token = token::Client::new(&env, &setup.token);
+    let prompt = Address::generate(&env);
+    let late = Address::generate(&env);
+    mint_and_buy(&env, raffle, &prompt, 1);
+    mint_and_buy(&env, raffle, &late, 2);
+    raffle.cancel_raffle(&CancelReason::CreatorCancelled);
+    raffle.refund_ticket(&1);
+
+    assert_eq!(
+        raffle.try_sweep_unclaimed_refunds(),
+        Err(Ok(Error::RefundWindowOpen))
+    );
+    env.ledger()
+        .with_mut(|l| l.timestamp += REFUND_CLAIM_WINDOW_SECONDS);
+
+    let price = raffle.get_raffle().ticket_price;
+    assert_eq!(raffle.sweep_unclaimed_refunds(), price * 2);
+    assert_eq!(token.balance(&setup.admin), price * 2);
+    assert_eq!(raffle.get_swept_refunds(), Some(price * 2));
+    assert_eq!(raffle.get_refund_owed(&late), 0);
+    assert_eq!(
+        raffle.try_refund_ticket(&2),
+        Err(Ok(Error::RefundWindowClosed))
+    );
+    assert_eq!(
+        raffle.try_sweep_unclaimed_refunds(),
+        Err(Ok(Error::RefundWindowClosed))
+    );
+}
+
+#[test]
+fn purchase_fees_accrue_until_remitted_to_the_treasury() {
+    use crate::testutils::{
+        events_named, fast_forward_and_finalize, mint_and_buy, setup_raffle_env_with,
+    };
+
+    let env = Env::default();
+    let treasury = Address::generate(&env);
+    let setup = setup_raffle_env_with(&env, |config| {
+        config.protocol_fee_bp = 1_000;
+        config.treasury_address = Some(treasury.clone());
+    });
+    let raffle = &setup.raffle;
+    let token = token::Client::new(&env, &setup.token);
+
+    mint_and_buy(&env, raffle, &Address::generate(&env), 3);
+    let fee = MIN_TICKET_PRICE * 3 / 10;
+    assert_eq!(token.balance(&treasury), 0);
+    assert_eq!(raffle.get_accrued_fees(&setup.token), fee);
+    assert_eq!(
+        raffle.try_remit_fees(&setup.token),
+        Err(Ok(Error::InvalidStatus))
+    );
+
+    fast_forward_and_finalize(&env, raffle);
+    assert_eq!(raffle.remit_fees(&setup.token), fee);
+    let remitted = events_named(&env, &raffle.address, "fees_remitted");
+    assert_eq!(remitted.len(), 1);
+    assert_eq!(remitted[0].field::<i128>(&env, "amount"), fee);
+    assert_eq!(token.balance(&treasury), fee);
+    assert_eq!(raffle.get_accrued_fees(&setup.token), 0);
+    assert_eq!(raffle.get_accumulated_fees(), 0);
+    assert_eq!(raffle.remit_fees(&setup.token), 0);
+
+    let untreasured = setup_raffle_env_with(&env, |config| config.protocol_fee_bp = 1_000);
+    mint_and_buy(&env, &untreasured.raffle, &Address::generate(&env), 1);
+    fast_forward_and_finalize(&env, &untreasured.raffle);
+    assert_eq!(
+        untreasured.raffle.try_remit_fees(&untreasured.token),
+        Err(Ok(Error::TreasuryNotSet))
+    );
+}
+
+#[test]
+fn cancelled_raffle_refunds_every_ticket_with_the_accrued_fee() {
+    use crate::testutils::{mint_and_buy, setup_raffle_env_with};
+
+    let env = Env::default();
+    let setup = setup_raffle_env_with(&env, |config| {
+        config.protocol_fee_bp = 1_000;
+        config.treasury_address = Some(Address::generate(&env));
+    });
+    let raffle = &setup.raffle;
+    let buyer = Address::generate(&env);
+    mint_and_buy(&env, raffle, &buyer, 3);
+    assert_eq!(
+        raffle.try_remit_fees(&setup.token),
+        Err(Ok(Error::InvalidStatus))
+    );
+
+    let surplus = raffle.check_solvency(&setup.token).surplus;
+    assert!(surplus >= 0);
+    raffle.cancel_raffle(&CancelReason::CreatorCancelled);
+    assert_eq!(raffle.get_accrued_fees(&setup.token), 0);
+    assert_eq!(raffle.check_solvency(&setup.token).surplus, surplus);
+    assert_eq!(
+        raffle.try_remit_fees(&setup.token),
+        Err(Ok(Error::InvalidStatus))
+    );
+    for ticket_id in 1..=3 {
+        assert_eq!(raffle.refund_ticket(&ticket_id), MIN_TICKET_PRICE);
+    }
+
+    let token = token::Client::new(&env, &setup.token);
+    assert_eq!(token.balance(&buyer), MIN_TICKET_PRICE * 3);
+    assert_eq!(raffle.check_solvency(&setup.token).surplus, surplus);
+}
+
+#[test]
+fn settled_raffles_can_be_closed_for_good() {
+    use crate::testutils::{default_config, events_named, mint_and_buy, setup_raffle_env};
+
+    let env = Env::default();
+    let setup = setup_raffle_env(&env);
+    let raffle = &setup.raffle;
+    mint_and_buy(&env, raffle, &Address::generate(&env), 1);
+
+    assert_eq!(
+        raffle.try_close_instance(&setup.creator),
+        Err(Ok(Error::InvalidStatus))
+    );
+    raffle.cancel_raffle(&CancelReason::CreatorCancelled);
+    assert_eq!(
+        raffle.try_close_instance(&Address::generate(&env)),
+        Err(Ok(Error::NotAuthorized))
+    );
+
+    // The prize and the ticket are still owed.
+    assert_eq!(
+        raffle.try_close_instance(&setup.creator),
+        Err(Ok(Error::ObligationsOutstanding))
+    );
+    raffle.refund_prize();
+    // A refunded prize cannot be deposited again to reopen the raffle.
+    assert_eq!(raffle.try_deposit_prize(), Err(Ok(Error::InvalidStatus)));
+    assert_eq!(
+        raffle.try_close_instance(&setup.creator),
+        Err(Ok(Error::ObligationsOutstanding))
+    );
+    raffle.refund_ticket(&1);
+
+    raffle.close_instance(&setup.creator);
+    assert_eq!(
+        events_named(&env, &raffle.address, "instance_closed").len(),
+        1
+    );
+    assert_eq!(
+        raffle.try_get_raffle().err(),
+        Some(Ok(Error::InstanceClosed))
+    );
+    assert_eq!(
+        raffle.try_close_instance(&setup.creator),
+        Err(Ok(Error::InstanceClosed))
+    );
+    let config = default_config(&env, &setup.token);
+    assert_eq!(
+        raffle.try_init(&setup.factory, &setup.admin, &setup.creator, &config),
+        Err(Ok(Error::InstanceClosed))
+    );
+}
+
+#[test]
+fn closing_removes_the_draw_payout_and_settings_entries() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+    let treasury = Address::generate(&env);
+    let (client, _admin, creator, buyer, _factory, token_mint) =
+        setup_active_raffle_with(&env, |config| {
+            config.max_tickets = 1;
+            config.max_tickets_per_tx = 1;
+            config.protocol_fee_bp = 1_000;
+            config.treasury_address = Some(treasury.clone());
+            config.prize_vesting_seconds = 100;
+        });
+    let token_addr = token_mint.address.clone();
+    client.set_claim_window(&MIN_CLAIM_WINDOW_SECONDS, &UnclaimedPrizeRecipient::Creator);
+    client.set_dispute_window(&Address::generate(&env), &0);
+    client.set_distribution_mode(&DistributionMode::Winners);
+    client.buy_tickets(&buyer, &1, &10);
+
+    client.finalize_raffle();
+    env.ledger()
+        .set_timestamp(1_000 + DEFAULT_CLAIM_LOCKUP_SECONDS + 1);
+    client.claim_prize(&buyer, &0u32);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 100);
+    client.withdraw_vested(&0u32);
+    client.remit_fees(&token_addr);
+    client.withdraw_proceeds(&creator);
+    client.close_instance(&creator);
+
+    env.as_contract(&client.address, || {
+        let persistent = env.storage().persistent();
+        assert!(!persistent.has(&DataKey::DrawProof));
+        assert!(!persistent.has(&DataKey::Vesting(0)));
+        assert!(!persistent.has(&DataKey::SalesBucket(1_000 / SALES_BUCKET_SECONDS)));
+        let instance = env.storage().instance();
+        for key in [
+            DataKey::AccruedFees(token_addr.clone()),
+            DataKey::FeeTokens,
+            DataKey::NextTicketId,
+            DataKey::ParticipantEntropy,
+            DataKey::Arbiter,
+            DataKey::DisputeWindowSeconds,
+            DataKey::ClaimWindowSeconds,
+            DataKey::UnclaimedPrizeRecipient,
+            DataKey::DistributionMode,
+            DataKey::TicketRevenue,
+            DataKey::TotalTips,
+            DataKey::NetProceeds,
+            DataKey::ProceedsWithdrawn,
+        ] {
+            assert!(!instance.has(&key));
+        }
+        assert!(instance.has(&DataKey::Closed));
+    });
+}
+
+#[test]
+fn unclaimed_prizes_are_settled_once_the_claim_window_closes() {
+    use crate::testutils::{
+        events_named, fast_forward_and_finalize, mint_and_buy, setup_raffle_env_with,
+    };
+
+    let env = Env::default();
+    let treasury = Address::generate(&env);
+    let run = |unclaimed_to: UnclaimedPrizeRecipient| {
+        let setup = setup_raffle_env_with(&env, |config| {
+            config.treasury_address = Some(treasury.clone());
+        });
+        assert_eq!(
+            setup.raffle.try_set_claim_window(&1, &unclaimed_to),
+            Err(Ok(Error::InvalidParameters))
+        );
+        setup
+            .raffle
+            .set_claim_window(&MIN_CLAIM_WINDOW_SECONDS, &unclaimed_to);
+        assert_eq!(setup.raffle.get_unclaimed_prize_recipient(), unclaimed_to);
+        let winner = Address::generate(&env);
+        mint_and_buy(&env, &setup.raffle, &winner, 1);
+        fast_forward_and_finalize(&env, &setup.raffle);
+        (setup, winner)
+    };
+
+    let (escheated, winner) = run(UnclaimedPrizeRecipient::Treasury);
+    let raffle = &escheated.raffle;
+    let prize = raffle.get_raffle().prize_amount;
+    let deadline = raffle.get_claim_deadline().unwrap();
+    assert_eq!(
+        raffle.try_reclaim_unclaimed_prizes(&escheated.admin),
+        Err(Ok(Error::ClaimWindowOpen))
+    );
+    env.ledger().with_mut(|l| l.timestamp = deadline);
+    assert_eq!(
+        raffle.try_claim_prize(&winner, &0),
+        Err(Ok(Error::ClaimWindowClosed))
+    );
+    assert_eq!(
+        raffle.try_reclaim_unclaimed_prizes(&winner),
+        Err(Ok(Error::NotAuthorized))
+    );
+    // The recipient was fixed with the window, so the creator calling
+    // first still escheats to the treasury.
+    assert_eq!(raffle.reclaim_unclaimed_prizes(&escheated.creator), prize);
+    assert_eq!(
+        events_named(&env, &raffle.address, "unclaimed_prizes_escheated").len(),
+        1
+    );
+    let prize_token = token::Client::new(&env, &escheated.token);
+    assert_eq!(prize_token.balance(&treasury), prize);
+    assert_eq!(raffle.get_raffle().status, RaffleStatus::Claimed);
+
+    let (recovered, _) = run(UnclaimedPrizeRecipient::Creator);
+    let deadline = recovered.raffle.get_claim_deadline().unwrap();
+    env.ledger().with_mut(|l| l.timestamp = deadline);
+    let prize_token = token::Client::new(&env, &recovered.token);
+    let before = prize_token.balance(&recovered.creator);
+    assert_eq!(
+        recovered.raffle.reclaim_unclaimed_prizes(&recovered.admin),
+        prize
+    );
+    assert_eq!(
+        events_named(
+            &env,
+            &recovered.raffle.address,
+            "unclaimed_prizes_recovered"
+        )
+        .len(),
+        1
+    );
+    assert_eq!(prize_token.balance(&recovered.creator), before + prize);
+
+    let untreasured = setup_raffle_env_with(&env, |config| config.treasury_address = None);
+    assert_eq!(
+        untreasured.raffle.try_set_claim_window(
+            &MIN_CLAIM_WINDOW_SECONDS,
+            &UnclaimedPrizeRecipient::Treasury
+        ),
+        Err(Ok(Error::TreasuryNotSet))
+    );
+}
+
+#[test]
+fn ticket_and_capacity_getters_track_sales() {
+    use crate::testutils::{mint_and_buy, setup_raffle_env};
+
+    let env = Env::default();
+    let setup = setup_raffle_env(&env);
+    let raffle = &setup.raffle;
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    mint_and_buy(&env, raffle, &alice, 3);
+    mint_and_buy(&env, raffle, &bob, 2);
+
+    assert_eq!(raffle.get_ticket_count(&alice), 3);
+    assert_eq!(raffle.get_ticket_count(&bob), 2);
+    assert_eq!(raffle.get_ticket_count(&Address::generate(&env)), 0);
+    assert_eq!(
+        raffle.get_remaining_tickets(),
+        raffle.get_raffle().max_tickets - 5
+    );
+
+    let page = raffle.get_tickets_page(&PaginationParams {
+        offset: 2,
+        limit: 2,
     });
+    assert_eq!(page.total, 5);
+    assert!(page.has_more);
+    assert_eq!(page.items.len(), 2);
+    assert_eq!(page.items.get(0).unwrap().id, 3);
+    assert_eq!(page.items.get(0).unwrap().owner, alice);
+    assert_eq!(page.items.get(1).unwrap().owner, bob);
+
+    let last = raffle.get_tickets_page(&PaginationParams {
+        offset: 4,
+        limit: 10,
+    });
+    assert_eq!(last.items.len(), 1);
+    assert!(!last.has_more);
+}
+
+#[test]
+fn factory_pushes_protocol_config_only_before_sales_open() {
+    use crate::testutils::events_named;
+    use soroban_sdk::testutils::AuthorizedFunction;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (token_addr, _) = create_token(&env, &Address::generate(&env));
+    let contract_id = env.register(Contract, ());
+    let client = ContractClient::new(&env, &contract_id);
+    let factory = env.register(MockFactory, ());
+    let creator = Address::generate(&env);
+    StellarAssetClient::new(&env, &token_addr).mint(&creator, &10_000_000);
+    client.init(
+        &factory,
+        &Address::generate(&env),
+        &creator,
+        &test_raffle_config(&env, &token_addr),
+    );
+    assert_eq!(client.get_raffle().status, RaffleStatus::PendingPrize);
+
+    let treasury = Address::generate(&env);
+    client.update_protocol_config(&250, &treasury);
+    let auths = env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, factory);
+    assert!(matches!(
+        &auths[0].1.function,
+        AuthorizedFunction::Contract((contract, name, _))
+            if *contract == contract_id && *name == Symbol::new(&env, "update_protocol_config")
+    ));
+    let fee_updated = events_named(&env, &contract_id, "protocol_fee_updated");
+    assert_eq!(fee_updated.len(), 1);
+    assert_eq!(fee_updated[0].field::<u32>(&env, "old_fee_bp"), 0);
+    assert_eq!(fee_updated[0].field::<u32>(&env, "new_fee_bp"), 250);
+    assert_eq!(fee_updated[0].field::<Address>(&env, "updated_by"), factory);
+    let treasury_updated = events_named(&env, &contract_id, "treasury_updated");
+    assert_eq!(treasury_updated.len(), 1);
+    assert_eq!(
+        treasury_updated[0].field::<Option<Address>>(&env, "old_treasury"),
+        None
+    );
+    assert_eq!(
+        treasury_updated[0].field::<Address>(&env, "new_treasury"),
+        treasury
+    );
+    let raffle = client.get_raffle();
+    assert_eq!(raffle.protocol_fee_bp, 250);
+    assert_eq!(raffle.treasury_address, Some(treasury.clone()));
+
+    // Pushing the same config again changes nothing and publishes nothing.
+    client.update_protocol_config(&250, &treasury);
+    assert!(events_named(&env, &contract_id, "protocol_fee_updated").is_empty());
+    assert!(events_named(&env, &contract_id, "treasury_updated").is_empty());
+
+    assert_eq!(
+        client.try_update_protocol_config(&(MAX_PROTOCOL_FEE_BP + 1), &treasury),
+        Err(Ok(Error::InvalidFee))
+    );
 
     client.deposit_prize();
+    assert_eq!(
+        client.try_update_protocol_config(&500, &Address::generate(&env)),
+        Err(Ok(Error::InvalidStatus))
+    );
+    let raffle = client.get_raffle();
+    assert_eq!(raffle.protocol_fee_bp, 250);
+    assert_eq!(raffle.treasury_address, Some(treasury));
+}
+
+#[test]
+fn guardian_pause_freezes_sales_and_claims_until_the_factory_unpauses() {
+    use crate::testutils::events_named;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, _creator, buyer, factory, _token_mint) = setup_active_raffle(&env);
+    let guardian = Address::generate(&env);
+    MockFactoryClient::new(&env, &factory).set_guardian(&guardian);
+
+    assert_eq!(
+        client.try_emergency_pause(&Address::generate(&env)),
+        Err(Ok(Error::NotAuthorized))
+    );
+    assert!(!client.is_paused());
+
+    client.emergency_pause(&guardian);
+    let paused = events_named(&env, &client.address, "contract_paused");
+    assert_eq!(paused.len(), 1);
+    assert_eq!(paused[0].field::<Address>(&env, "paused_by"), guardian);
+    assert!(client.is_paused());
+    assert_eq!(
+        client.try_buy_tickets(&buyer, &1, &0),
+        Err(Ok(Error::ContractPaused))
+    );
+
+    client.unpause();
+    let unpaused = events_named(&env, &client.address, "contract_unpaused");
+    assert_eq!(unpaused.len(), 1);
+    assert_eq!(unpaused[0].field::<Address>(&env, "unpaused_by"), factory);
+    assert_eq!(env.auths()[0].0, factory);
+    assert!(!client.is_paused());
 
-    let balance_before = token_client.balance(&buyer);
-    client.buy_tickets(&buyer, &11);
-    let balance_after = token_client.balance(&buyer);
+    client.buy_tickets(&buyer, &1, &0);
+    client.finalize_raffle();
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + DEFAULT_CLAIM_LOCKUP_SECONDS + 1);
+    client.emergency_pause(&guardian);
+    assert_eq!(
+        client.try_claim_prize(&buyer, &0u32),
+        Err(Ok(Error::ContractPaused))
+    );
 
-    assert_eq!(balance_before - balance_after, 11 * 80_000);
+    client.unpause();
+    assert_eq!(
+        client.claim_prize(&buyer, &0u32),
+        client.get_raffle().prize_amount
+    );
 }
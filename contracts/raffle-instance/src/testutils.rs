@@ -0,0 +1,195 @@
+//! Test helpers for contracts and dApps that integrate with raffle
+//! instances.
+//!
+//! Enable the `testutils` feature in a dev-dependency to use them:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! raffle-instance = { path = "...", features = ["testutils"] }
+//! ```
+//!
+//! `setup_raffle_env` stands up a funded, active raffle in one call;
+//! `mint_and_buy` and `fast_forward_and_finalize` walk it through its
+//! lifecycle the way the contract's own tests do, and `decode_events`
+//! turns the events a contract published into named field maps.
+
+extern crate std;
+
+use soroban_sdk::testutils::{Address as _, Events as _, Ledger as _};
+use soroban_sdk::token::StellarAssetClient;
+use soroban_sdk::xdr::ScVal;
+use soroban_sdk::{contract, vec, Address, BytesN, Env, Map, String, Symbol, TryFromVal, Val};
+
+use raffle_shared::{RaffleConfig, RandomnessSource};
+
+use crate::{Contract, ContractClient, Raffle, MIN_TICKET_PRICE, RANDOMNESS_MIN_DELAY_LEDGERS};
+
+/// Stands in for the factory.  It exposes no functions, so every lookup a
+/// raffle makes against its factory fails and falls back to the raffle's
+/// own configuration.
+#[contract]
+pub struct StubFactory;
+
+/// A raffle set up by `setup_raffle_env`, with the parties around it.
+pub struct RaffleTestEnv {
+    pub env: Env,
+    pub raffle: ContractClient<'static>,
+    pub factory: Address,
+    pub admin: Address,
+    pub creator: Address,
+    /// Payment token, a Stellar Asset Contract the helpers can mint.
+    pub token: Address,
+}
+
+/// A one-tier raffle selling up to 100 tickets at `MIN_TICKET_PRICE` for a
+/// day from the current ledger time.
+pub fn default_config(env: &Env, token: &Address) -> RaffleConfig {
+    RaffleConfig {
+        description: String::from_str(env, "Test Raffle"),
+        end_time: env.ledger().timestamp() + 86_400,
+        no_deadline: false,
+        max_tickets: 100,
+        max_tickets_per_tx: 100,
+        min_tickets: 1,
+        allow_multiple: true,
+        ticket_price: MIN_TICKET_PRICE,
+        payment_token: token.clone(),
+        prize_amount: MIN_TICKET_PRICE * 10,
+        prizes: vec![env, 10_000u32],
+        randomness_source: RandomnessSource::Internal,
+        oracle_address: None,
+        oracle_public_key: None,
+        auto_distribute: false,
+        protocol_fee_bp: 0,
+        treasury_address: None,
+        swap_router: None,
+        tikka_token: None,
+        metadata_hash: BytesN::from_array(env, &[1u8; 32]),
+        claim_lockup_seconds: 0,
+        prize_vesting_seconds: 0,
+        proceeds_vesting_seconds: 0,
+        swap_deadline_seconds: 0,
+        early_bird_ticket_percentage: 0,
+        early_bird_discount_bp: 0,
+        nft_contract: None,
+        category: None,
+    }
+}
+
+/// An active raffle on `default_config`, prize deposited.  Mocks all auths.
+pub fn setup_raffle_env(env: &Env) -> RaffleTestEnv {
+    setup_raffle_env_with(env, |_| {})
+}
+
+/// Like `setup_raffle_env`, with `configure` applied to the config first.
+pub fn setup_raffle_env_with(
+    env: &Env,
+    configure: impl FnOnce(&mut RaffleConfig),
+) -> RaffleTestEnv {
+    env.mock_all_auths();
+
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let factory = env.register(StubFactory, ());
+    let admin = Address::generate(env);
+    let creator = Address::generate(env);
+
+    let mut config = default_config(env, &token);
+    configure(&mut config);
+    StellarAssetClient::new(env, &token).mint(&creator, &config.prize_amount);
+
+    let raffle = ContractClient::new(env, &env.register(Contract, ()));
+    raffle.init(&factory, &admin, &creator, &config);
+    raffle.deposit_prize();
+
+    RaffleTestEnv {
+        env: env.clone(),
+        raffle,
+        factory,
+        admin,
+        creator,
+        token,
+    }
+}
+
+/// Mints `buyer` exactly what `quantity` tickets cost and buys them.
+/// Returns the number of tickets bought.
+pub fn mint_and_buy(env: &Env, raffle: &ContractClient, buyer: &Address, quantity: u32) -> u32 {
+    let quote = raffle.get_purchase_quote(&quantity);
+    StellarAssetClient::new(env, &quote.token).mint(buyer, &quote.total);
+    raffle.buy_tickets(buyer, &quantity, &0)
+}
+
+/// Moves the ledger past the raffle's end time and the randomness delay,
+/// finalizes it and returns its state.  An open-ended raffle must already
+/// be sold out.
+pub fn fast_forward_and_finalize(env: &Env, raffle: &ContractClient) -> Raffle {
+    let state = raffle.get_raffle();
+    env.ledger().with_mut(|l| {
+        if let Some(end_time) = state.end_condition().deadline() {
+            l.timestamp = l.timestamp.max(end_time);
+        }
+        l.sequence_number += RANDOMNESS_MIN_DELAY_LEDGERS;
+    });
+    raffle.finalize_raffle();
+    raffle.get_raffle()
+}
+
+/// Moves the ledger to the end of a finalized raffle's claim lockup.
+pub fn fast_forward_to_claims(env: &Env, raffle: &ContractClient) {
+    let state = raffle.get_raffle();
+    if let Some(finalized_at) = state.finalized_at {
+        let unlock = finalized_at + state.claim_lockup_seconds;
+        env.ledger()
+            .with_mut(|l| l.timestamp = l.timestamp.max(unlock));
+    }
+}
+
+/// An event as published by a contract.
+pub struct DecodedEvent {
+    /// The event's name: its second topic, e.g. `ticket_purchased`.
+    pub name: std::string::String,
+    /// The event's data fields.
+    pub data: Map<Symbol, Val>,
+}
+
+impl DecodedEvent {
+    /// The data field `field`, converted to `T`.  Panics when it is missing
+    /// or of another type.
+    pub fn field<T: TryFromVal<Env, Val>>(&self, env: &Env, field: &str) -> T {
+        let val = self
+            .data
+            .get(Symbol::new(env, field))
+            .unwrap_or_else(|| panic!("event {} has no field {field}", self.name));
+        T::try_from_val(env, &val)
+            .unwrap_or_else(|_| panic!("field {field} of event {} has another type", self.name))
+    }
+}
+
+/// Every event `contract` published during the last contract call, in
+/// order.  Works for the factory's events as well as the instance's.
+pub fn decode_events(env: &Env, contract: &Address) -> std::vec::Vec<DecodedEvent> {
+    env.events()
+        .all()
+        .iter()
+        .filter(|(address, _, _)| address == contract)
+        .filter_map(|(_, topics, data)| {
+            let name = match ScVal::try_from_val(env, &topics.get(1)?).ok()? {
+                ScVal::Symbol(symbol) => symbol.0.to_utf8_string_lossy(),
+                _ => return None,
+            };
+            let data = Map::<Symbol, Val>::try_from_val(env, &data).ok()?;
+            Some(DecodedEvent { name, data })
+        })
+        .collect()
+}
+
+/// The events named `name` that `contract` published during the last
+/// contract call.
+pub fn events_named(env: &Env, contract: &Address, name: &str) -> std::vec::Vec<DecodedEvent> {
+    decode_events(env, contract)
+        .into_iter()
+        .filter(|event| event.name == name)
+        .collect()
+}
@@ -0,0 +1,75 @@
+//! New-buyer throttle.
+//!
+//! A creator may cap how many distinct new buyers can enter in a window of
+//! ledgers, which slows scripted multi-wallet floods of low-supply raffles.
+//! Addresses that already hold a ticket are never throttled.
+
+use soroban_sdk::{Address, Env};
+
+use crate::events::NewBuyerLimitUpdated;
+use crate::{DataKey, Error, NewBuyerLimit};
+
+pub(crate) fn limit(env: &Env) -> Option<NewBuyerLimit> {
+    env.storage().instance().get(&DataKey::NewBuyerLimit)
+}
+
+pub(crate) fn set_limit(
+    env: &Env,
+    creator: Address,
+    limit: Option<NewBuyerLimit>,
+) -> Result<(), Error> {
+    match limit.clone() {
+        Some(limit) if limit.max_new_buyers == 0 || limit.window_ledgers == 0 => {
+            return Err(Error::InvalidParameters);
+        }
+        Some(limit) => env
+            .storage()
+            .instance()
+            .set(&DataKey::NewBuyerLimit, &limit),
+        None => env.storage().instance().remove(&DataKey::NewBuyerLimit),
+    }
+    env.storage().instance().remove(&DataKey::NewBuyerWindow);
+
+    NewBuyerLimitUpdated {
+        limit,
+        updated_by: creator,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+    Ok(())
+}
+
+/// The current window and how many new buyers it has admitted.
+fn current_window(env: &Env, limit: &NewBuyerLimit) -> (u32, u32) {
+    let window = env.ledger().sequence() / limit.window_ledgers;
+    let (current, entered) = env
+        .storage()
+        .instance()
+        .get::<_, (u32, u32)>(&DataKey::NewBuyerWindow)
+        .unwrap_or((window, 0));
+    (window, if current == window { entered } else { 0 })
+}
+
+/// Whether the current window can admit another new buyer.
+pub(crate) fn has_room(env: &Env) -> bool {
+    match limit(env) {
+        Some(limit) => current_window(env, &limit).1 < limit.max_new_buyers,
+        None => true,
+    }
+}
+
+/// Counts one new buyer against the current window, failing with
+/// `RateLimited` once the window is full.
+pub(crate) fn admit_new_buyer(env: &Env) -> Result<(), Error> {
+    let Some(limit) = limit(env) else {
+        return Ok(());
+    };
+    let (window, entered) = current_window(env, &limit);
+    if entered >= limit.max_new_buyers {
+        return Err(Error::RateLimited);
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::NewBuyerWindow, &(window, entered + 1));
+    Ok(())
+}
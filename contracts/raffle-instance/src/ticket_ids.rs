@@ -0,0 +1,46 @@
+//! The raffle's ticket-ID sequence.
+//!
+//! Every ticket takes its ID from one counter, starting at 1, and
+//! `DataKey::Ticket(id)` maps the ID to its record.  IDs are never reused or
+//! skipped, so `all` covers every ticket ever issued and the ticket at
+//! 0-based position `n` in the sequence has ID `n + 1`.  Queries walk the
+//! sequence rather than assuming it lines up with `tickets_sold`, which
+//! counts sales only.
+
+use core::ops::RangeInclusive;
+
+use soroban_sdk::Env;
+
+use crate::{math, read_raffle, DataKey, Error};
+
+/// The highest ticket ID issued, `0` before the first ticket.
+pub(crate) fn last(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::NextTicketId)
+        .unwrap_or_else(|| {
+            // Raffles created before the counter existed numbered their
+            // tickets by sale.
+            read_raffle(env)
+                .map(|raffle| raffle.tickets_sold)
+                .unwrap_or(0)
+        })
+}
+
+/// Issues the next ticket ID.
+pub(crate) fn next(env: &Env) -> Result<u32, Error> {
+    let id = math::add_u32(last(env), 1)?;
+    env.storage().instance().set(&DataKey::NextTicketId, &id);
+    Ok(id)
+}
+
+/// Every ticket ID issued so far, in order.
+pub(crate) fn all(env: &Env) -> RangeInclusive<u32> {
+    1..=last(env)
+}
+
+/// The ID of the ticket at 0-based `position` in the sequence, as returned
+/// by winner selection and paginated queries.
+pub(crate) fn at(position: u32) -> Result<u32, Error> {
+    math::add_u32(position, 1)
+}
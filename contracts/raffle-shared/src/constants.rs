@@ -12,6 +12,13 @@
 /// is permitted (~17 minutes at 5-second ledger close times).
 pub const ORACLE_TIMEOUT_LEDGERS: u32 = 200;
 
+/// Upper bound on a raffle's configurable oracle timeout (~1 day).
+pub const MAX_ORACLE_TIMEOUT_LEDGERS: u32 = 17_280;
+
+/// Ledgers that must pass between committing to a PRNG draw and revealing it,
+/// so the seed cannot be known when the draw is triggered.
+pub const RANDOMNESS_MIN_DELAY_LEDGERS: u32 = 10;
+
 /// Maximum byte-length of a raffle description string.
 pub const MAX_DESCRIPTION_LENGTH: u32 = 1_000;
 
@@ -25,11 +32,39 @@ pub const MAX_PRIZES: u32 = 100;
 /// denomination).  Prevents dust-amount raffles that would be uneconomical.
 pub const MIN_TICKET_PRICE: i128 = 10_000;
 
+/// Decimals of the native XLM Stellar Asset Contract (1 XLM = 10^7
+/// stroops).
+pub const NATIVE_DECIMALS: u32 = 7;
+
 /// Maximum allowed prize pool.  Prevents i128 overflow in prize calculations.
 pub const MAX_PRIZE_AMOUNT: i128 = 1_000_000_000_000_000_000_000; // 1e21
 
+/// Maximum byte-length of a raffle's image URI.
+pub const MAX_IMAGE_URI_LENGTH: u32 = 256;
+
+/// Maximum number of tags attached to a raffle.
+pub const MAX_TAGS: u32 = 5;
+
+/// Maximum number of operators a creator may appoint on a raffle.
+pub const MAX_OPERATORS: u32 = 10;
+
+/// Maximum number of co-owners sharing a raffle's proceeds.
+pub const MAX_REVENUE_OWNERS: u32 = 10;
+
+/// Maximum creator royalty on a ticket resale, in basis points (10 %).
+pub const MAX_RESALE_ROYALTY_BP: u32 = 1_000;
+
+/// Category assigned to a raffle until its creator picks one.
+pub const DEFAULT_CATEGORY: &str = "general";
+
 // --- Timing constants -------------------------------------------------------
 
+/// Upper bound on a raffle's prize vesting period (365 days).
+pub const MAX_PRIZE_VESTING_SECONDS: u64 = 365 * 24 * 3_600;
+
+/// Upper bound on a raffle's proceeds vesting period (90 days).
+pub const MAX_PROCEEDS_VESTING_SECONDS: u64 = 90 * 24 * 3_600;
+
 /// Default delay (seconds) between raffle finalization and when winners may
 /// claim their prize.  Equals 1 hour.
 pub const DEFAULT_CLAIM_LOCKUP_SECONDS: u64 = 3_600;
@@ -37,6 +72,13 @@ pub const DEFAULT_CLAIM_LOCKUP_SECONDS: u64 = 3_600;
 /// Upper bound on the claim lockup delay (7 days).
 pub const MAX_CLAIM_LOCKUP_SECONDS: u64 = 604_800;
 
+/// Shortest claim window a raffle may set (7 days).  Raffles without one
+/// leave claims open until the emergency withdrawal.
+pub const MIN_CLAIM_WINDOW_SECONDS: u64 = 7 * 24 * 3_600;
+
+/// Upper bound on a raffle's dispute window (7 days).
+pub const MAX_DISPUTE_WINDOW_SECONDS: u64 = 604_800;
+
 /// Default window (seconds) added to the current timestamp when submitting
 /// token-swap transactions.  Equals 5 minutes.
 pub const DEFAULT_SWAP_DEADLINE_SECONDS: u64 = 300;
@@ -48,17 +90,55 @@ pub const MAX_SWAP_DEADLINE_SECONDS: u64 = 3_600;
 /// emergency withdrawal is permitted.  Equals 90 days (7 776 000 s).
 pub const EMERGENCY_WITHDRAW_DELAY_SECONDS: u64 = 90 * 24 * 3_600; // 7_776_000
 
+/// Time after `end_time` an unfinalized raffle is left before anyone may
+/// expire it (7 days).
+pub const RAFFLE_EXPIRY_WINDOW_SECONDS: u64 = 7 * 24 * 3_600;
+
+/// Time after a raffle is cancelled or fails during which buyers may reclaim
+/// their tickets; afterwards the admin may sweep what is left (1 year).
+pub const REFUND_CLAIM_WINDOW_SECONDS: u64 = 365 * 24 * 3_600;
+
+/// Upper bound on how old an oracle price a pegged ticket price may use
+/// (1 day).
+pub const MAX_PRICE_AGE_SECONDS: u64 = 86_400;
+
+/// Maximum keeper bounty as a share of ticket revenue (5 %).
+pub const MAX_KEEPER_REWARD_BP: u32 = 500;
+
+/// Width of a sales-history bucket (1 hour).
+pub const SALES_BUCKET_SECONDS: u64 = 3_600;
+
 // --- Factory constants ------------------------------------------------------
 
 /// Timelock delay (seconds) before a proposed admin operation may be executed.
 /// Equals 48 hours, giving users time to react to protocol changes.
 pub const TIMELOCK_DELAY_SECONDS: u64 = 172_800;
 
+/// Lower bound the admin may reduce the timelock delay to (1 hour).
+pub const MIN_TIMELOCK_DELAY_SECONDS: u64 = 3_600;
+
 /// Factory creates a state checkpoint every `CHECKPOINT_INTERVAL` raffles.
 pub const CHECKPOINT_INTERVAL: u32 = 1_000;
 
-/// Maximum protocol fee in basis points (20 %).
-pub const MAX_PROTOCOL_FEE_BP: u32 = 2_000;
+/// Maximum protocol fee in basis points (10 %).  Enforced by the factory and
+/// by every raffle instance so a misconfigured treasury can never take more
+/// than this share of a raffle's revenue.
+pub const MAX_PROTOCOL_FEE_BP: u32 = 1_000;
+
+/// Maximum number of raffles on the factory's featured list.
+pub const MAX_FEATURED_RAFFLES: u32 = 20;
+
+/// Maximum number of users subscribed to one creator's raffles.
+pub const MAX_SUBSCRIBERS_PER_CREATOR: u32 = 100;
+
+/// Number of creators kept on the factory's prize leaderboard.
+pub const TOP_CREATORS_LIMIT: u32 = 10;
+
+/// Width of a bucket in the factory's end-time index (1 hour).
+pub const END_TIME_BUCKET_SECONDS: u64 = 3_600;
+
+/// Longest window `get_raffles_ending_within` looks ahead (7 days).
+pub const MAX_ENDING_WITHIN_SECONDS: u64 = 7 * 24 * 3_600;
 
 // --- Pagination defaults ----------------------------------------------------
 
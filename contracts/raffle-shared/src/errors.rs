@@ -0,0 +1,103 @@
+//! Error codes shared by the factory and the raffle instances.
+
+use soroban_sdk::contracterror;
+
+/// Error codes returned by the factory and the instances; `ERRORS.md` lists
+/// them all.  Codes below 100 are shared by both contracts, codes from 100
+/// up are only returned by the factory.  The contract spec caps error enums
+/// at 50 cases, so the enum is not exported and clients map the codes from
+/// `ERRORS.md`.
+#[contracterror(export = false)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub enum Error {
+    RaffleNotFound = 1,
+    RaffleInactive = 2,
+    TicketsSoldOut = 3,
+    InsufficientFunds = 4,
+    NotAuthorized = 5,
+    OracleNotSet = 6,
+    RandomnessAlreadyRequested = 7,
+    NoRandomnessRequest = 8,
+    FallbackTooEarly = 9,
+    PrizeNotDeposited = 11,
+    PrizeAlreadyClaimed = 12,
+    PrizeAlreadyDeposited = 13,
+    NotWinner = 14,
+    ClaimTooEarly = 15,
+    InvalidParameters = 21,
+    InvalidQuantity = 22,
+    InvalidStatus = 23,
+    ContractPaused = 24,
+    InvalidStateTransition = 25,
+    RaffleExpired = 26,
+    InsufficientTickets = 31,
+    MultipleTicketsNotAllowed = 32,
+    NoTicketsSold = 33,
+    TicketNotFound = 34,
+    RaffleEnded = 35,
+    ArithmeticOverflow = 41,
+    AlreadyInitialized = 42,
+    NotInitialized = 43,
+    Reentrancy = 44,
+    TokenTransferFailed = 45,
+    NoActiveTickets = 46,
+    DeadlinePassed = 47,
+    SlippageExceeded = 48,
+    InvalidIndex = 49,
+    MorePrizesThanTickets = 50,
+    ZeroPrize = 51,
+    InvalidTokenAddress = 52,
+    TooManyPrizes = 53,
+    EmergencyTooEarly = 54,
+    InvalidTicketRange = 55,
+    InsufficientAccumulatedFees = 56,
+    PrizeConfigurationLocked = 57,
+    ExceedsMaxTicketsPerTx = 58,
+    DrawingAlreadyInProgress = 59,
+    InvalidStatusForDrawingTransition = 60,
+    DrawingAlreadyComplete = 61,
+    InvalidEndTime = 62,
+    InvalidAdminAddress = 63,
+    RandomnessTooEarly = 64,
+    AddressBlocked = 65,
+    OracleNotApproved = 66,
+    RandomnessRequestMismatch = 67,
+    RandomnessAlreadyFulfilled = 68,
+    InvalidDrandSignature = 69,
+    NoVestingSchedule = 70,
+    NothingToWithdraw = 71,
+    DisputeWindowOpen = 72,
+    DisputeWindowClosed = 73,
+    NoOpenDispute = 74,
+    InvalidFee = 75,
+    TicketNotListed = 76,
+    NotEligible = 77,
+    RateLimited = 78,
+    ContractBuyerNotAllowed = 79,
+    RefundWindowClosed = 80,
+    RefundWindowOpen = 81,
+    TreasuryNotSet = 82,
+    InstanceClosed = 83,
+    ObligationsOutstanding = 84,
+    ClaimWindowClosed = 85,
+    ClaimWindowOpen = 86,
+    // Factory-only codes.
+    AdminTransferPending = 111,
+    NoPendingTransfer = 112,
+    RateLimitExceeded = 113,
+    NoPendingOp = 114,
+    TimelockNotElapsed = 115,
+    InvalidRaffleId = 116,
+    RaffleNotEligible = 117,
+    FeaturedLimitReached = 120,
+    AlreadyFeatured = 121,
+    CreatorNotAllowlisted = 122,
+    ActiveRaffleLimitReached = 123,
+    ReputationTooLow = 124,
+    ExternalRandomnessRequired = 125,
+    OracleAlreadyRegistered = 127,
+    InsufficientBalance = 130,
+    BadgeAlreadyMinted = 131,
+    RaffleLimitExceeded = 132,
+    NewRafflesPaused = 133,
+}
@@ -0,0 +1,18 @@
+//! Events published the same way by the factory and the raffle instances.
+//! The emitting contract's address tells the two apart.
+
+use soroban_sdk::{contractevent, Address};
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "contract_paused"])]
+pub struct ContractPaused {
+    pub paused_by: Address,
+    pub timestamp: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle", "contract_unpaused"])]
+pub struct ContractUnpaused {
+    pub unpaused_by: Address,
+    pub timestamp: u64,
+}
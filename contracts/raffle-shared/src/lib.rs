@@ -2,8 +2,12 @@
 #![cfg_attr(not(test), deny(clippy::unwrap_used))]
 
 pub mod constants;
+mod errors;
+pub mod events;
 
-use soroban_sdk::{contracttype, Address, BytesN, String, Vec};
+pub use errors::Error;
+
+use soroban_sdk::{contracttype, Address, Bytes, BytesN, String, Symbol, Vec};
 
 /// Lifecycle state of a raffle instance.
 ///
@@ -29,6 +33,10 @@ pub enum RaffleStatus {
     Failed = 4,
     /// Finalized raffle where all winners have completed claims.
     Claimed = 5,
+    /// Raffle is being prepared by its creator and is not yet visible to
+    /// buyers.  Description, end time and ticket cap may still be edited;
+    /// `publish_raffle` moves it to `PendingPrize`.
+    Draft = 7,
 }
 
 /// Canonical reason explaining why a raffle entered `Cancelled`.
@@ -43,6 +51,83 @@ pub enum CancelReason {
     OracleTimeout = 2,
     /// Raffle cancelled because minimum ticket threshold was not met.
     MinTicketsNotMet = 3,
+    /// Factory admin force-cancelled a compromised or fraudulent raffle.
+    Emergency = 4,
+    /// The raffle's arbiter voided a disputed draw and opened refunds.
+    DrawDisputed = 5,
+    /// Nobody finalized the raffle within the expiry window after its end
+    /// time.
+    Expired = 6,
+}
+
+/// Outcome an arbiter picks when closing a dispute over a draw.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[contracttype]
+pub enum DisputeResolution {
+    /// The draw stands; claims open once the dispute window has elapsed.
+    Upheld = 0,
+    /// The draw is voided and the raffle returns to `Drawing` for a new draw.
+    Redraw = 1,
+    /// The draw is voided and the raffle is cancelled so everyone is refunded.
+    Refund = 2,
+}
+
+/// Bounty paid from a raffle's ticket revenue to the keeper whose
+/// `keeper_finalize` call draws its winners.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[contracttype]
+pub enum KeeperReward {
+    /// No bounty.
+    None,
+    /// Fixed amount in the payment token, capped at the ticket revenue.
+    Flat(i128),
+    /// Share of the ticket revenue in basis points, at most
+    /// `MAX_KEEPER_REWARD_BP`.
+    Bps(u32),
+}
+
+/// Slice of every protocol fee a raffle sends to the insurance pool
+/// instead of the treasury, set on the factory.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[contracttype]
+pub struct InsuranceRoute {
+    pub pool: Address,
+    /// Share of each protocol fee, in basis points.
+    pub share_bp: u32,
+}
+
+/// Protocol-wide caps on how large a raffle may grow, set on the factory
+/// with `set_raffle_limits`.  Keeps a raffle's ticket storage and
+/// finalization loop within the instruction budget.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RaffleLimits {
+    /// Most tickets a raffle may offer.
+    pub max_tickets: u32,
+    /// Longest description, in bytes.
+    pub max_description_length: u32,
+    /// Most tickets a single `buy_tickets` call may buy.
+    pub max_tickets_per_purchase: u32,
+}
+
+impl RaffleLimits {
+    /// The hard caps in `constants`, which apply until the admin sets
+    /// lower limits.
+    pub fn hard_caps() -> Self {
+        RaffleLimits {
+            max_tickets: constants::MAX_TICKETS_LIMIT,
+            max_description_length: constants::MAX_DESCRIPTION_LENGTH,
+            max_tickets_per_purchase: constants::MAX_TICKETS_LIMIT,
+        }
+    }
+
+    /// Whether every limit is non-zero and within its hard cap.
+    pub fn is_valid(&self) -> bool {
+        let caps = Self::hard_caps();
+        (1..=caps.max_tickets).contains(&self.max_tickets)
+            && (1..=caps.max_description_length).contains(&self.max_description_length)
+            && (1..=caps.max_tickets_per_purchase).contains(&self.max_tickets_per_purchase)
+    }
 }
 
 /// Canonical reason explaining why a raffle entered `Failed`.
@@ -65,6 +150,8 @@ pub enum RandomnessSource {
     External = 1,
     /// Commit-reveal based randomness source.
     CommitReveal = 2,
+    /// Publicly verifiable drand beacon, checked on-chain; no trusted oracle.
+    Drand = 3,
 }
 
 /// Type/classification of randomness mechanism requested or received.
@@ -77,6 +164,55 @@ pub enum RandomnessType {
     Vrf = 1,
     /// Fallback path used when preferred randomness path is unavailable.
     Fallback = 2,
+    /// drand beacon verified with a BLS12-381 pairing check.
+    Drand = 3,
+}
+
+/// How a raffle's ticket sales come to an end.
+///
+/// A raffle created with `end_time == 0` is open-ended: it has no deadline,
+/// sells until every ticket is gone and may only be finalized once sold out.
+/// Any other raffle closes at its deadline, or earlier if it sells out.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum EndCondition {
+    /// Open-ended; sales end when the last ticket sells.
+    SellOut,
+    /// Sales close after this Unix timestamp.
+    Deadline(u64),
+}
+
+impl EndCondition {
+    /// The end condition of a raffle ending at `end_time`, `0` meaning it
+    /// has no deadline.
+    pub fn from_end_time(end_time: u64) -> Self {
+        if end_time == 0 {
+            EndCondition::SellOut
+        } else {
+            EndCondition::Deadline(end_time)
+        }
+    }
+
+    /// The deadline, or `None` for an open-ended raffle.
+    pub fn deadline(&self) -> Option<u64> {
+        match *self {
+            EndCondition::SellOut => None,
+            EndCondition::Deadline(end_time) => Some(end_time),
+        }
+    }
+
+    /// Whether the deadline has closed ticket sales at `now`.  Sales stay
+    /// open through the deadline second itself.  Never true for open-ended
+    /// raffles.
+    pub fn sales_closed_at(&self, now: u64) -> bool {
+        self.deadline().is_some_and(|end_time| now > end_time)
+    }
+
+    /// Whether the deadline allows finalization at `now`.  Never true for
+    /// open-ended raffles, which must sell out instead.
+    pub fn deadline_reached_at(&self, now: u64) -> bool {
+        self.deadline().is_some_and(|end_time| now >= end_time)
+    }
 }
 
 /// Configuration payload used when creating a new raffle.
@@ -88,9 +224,11 @@ pub enum RandomnessType {
 pub struct RaffleConfig {
     /// Human-readable raffle description.
     pub description: String,
-    /// Unix timestamp when ticket sales close (ignored when `no_deadline` is true).
+    /// Unix timestamp when ticket sales close; `0` for an open-ended raffle
+    /// (see `EndCondition`).
     pub end_time: u64,
-    /// If true, raffle can remain open without a hard end timestamp.
+    /// Marks the raffle open-ended.  Implied by `end_time == 0` and invalid
+    /// with any other end time.
     pub no_deadline: bool,
     /// Maximum number of tickets that can ever be sold.
     pub max_tickets: u32,
@@ -112,6 +250,12 @@ pub struct RaffleConfig {
     pub randomness_source: RandomnessSource,
     /// Optional oracle contract address for external randomness flows.
     pub oracle_address: Option<Address>,
+    /// Ed25519 public key the oracle signs randomness responses with.
+    /// Required for external randomness.
+    pub oracle_public_key: Option<BytesN<32>>,
+    /// Pay prizes out at finalization instead of waiting for winners to
+    /// claim.  Tiers whose transfer fails stay claimable as usual.
+    pub auto_distribute: bool,
     /// Protocol fee in basis points (100 = 1%).
     /// Charged at two points: ticket purchase and prize claim.
     /// See docs/FEE_MODEL.md for full fee model details.
@@ -125,8 +269,16 @@ pub struct RaffleConfig {
     /// SHA-256 hash of immutable off-chain metadata content.
     pub metadata_hash: BytesN<32>,
     /// Seconds after finalization before winners may claim.
-    /// Must be in [0, 604800] (0 to 7 days). Defaults to 3600 if zero.
+    /// Must be in [0, 604800] (0 to 7 days). Defaults to 3600 if zero,
+    /// except under `auto_distribute`, which pays no tier still locked up.
     pub claim_lockup_seconds: u64,
+    /// Seconds over which each prize is streamed linearly to its winner
+    /// after claiming.  0 pays the prize out in one transfer.
+    pub prize_vesting_seconds: u64,
+    /// Seconds over which the ticket proceeds are streamed linearly to the
+    /// revenue owners after finalization.  0 pays them out in one transfer.
+    /// The factory may raise this for unverified creators.
+    pub proceeds_vesting_seconds: u64,
     /// Swap deadline window in seconds (added to current timestamp for token swaps).
     /// Defaults to 300 (5 minutes) if zero. Configurable to handle network congestion.
     pub swap_deadline_seconds: u64,
@@ -134,11 +286,24 @@ pub struct RaffleConfig {
     pub early_bird_ticket_percentage: u32,
     /// The discount amount specified in basis points.
     pub early_bird_discount_bp: u32,
+    /// Optional ticket NFT contract (see `NftTicketTrait`).  When set, the
+    /// holder of a winning ticket's NFT at claim time receives the prize.
+    pub nft_contract: Option<Address>,
+    /// Discovery category; `None` files the raffle under `DEFAULT_CATEGORY`.
+    /// The creator can change it with `set_metadata` until the first sale.
+    pub category: Option<Symbol>,
 }
 
 impl RaffleConfig {
+    pub fn end_condition(&self) -> EndCondition {
+        EndCondition::from_end_time(self.end_time)
+    }
+
     pub fn resolve_defaults(mut self) -> Self {
-        if self.claim_lockup_seconds == 0 {
+        if self.end_time == 0 {
+            self.no_deadline = true;
+        }
+        if self.claim_lockup_seconds == 0 && !self.auto_distribute {
             self.claim_lockup_seconds = DEFAULT_CLAIM_LOCKUP_SECONDS;
         }
         if self.swap_deadline_seconds == 0 {
@@ -148,6 +313,83 @@ impl RaffleConfig {
     }
 }
 
+/// Stored state of a raffle instance: its configuration as resolved at
+/// `init` plus its sales and draw progress.
+///
+/// Shared so the factory can return it from cross-contract queries.
+#[derive(Clone)]
+#[contracttype]
+pub struct Raffle {
+    pub creator: Address,
+    pub description: String,
+    pub end_time: u64,
+    pub no_deadline: bool,
+    pub max_tickets: u32,
+    pub max_tickets_per_tx: u32,
+    pub min_tickets: u32,
+    pub allow_multiple: bool,
+    pub ticket_price: i128,
+    pub payment_token: Address,
+    /// The token used for prize deposit and claims.
+    /// Defaults to `payment_token` when not explicitly set by the creator.
+    pub prize_token: Address,
+    pub prize_amount: i128,
+    pub prizes: Vec<u32>,
+    pub tickets_sold: u32,
+    pub status: RaffleStatus,
+    pub prize_deposited: bool,
+    pub winners: Vec<Address>,
+    /// `Ticket.id` of each tier's winning ticket, aligned with `winners`.
+    pub winning_ticket_ids: Vec<u32>,
+    pub claimed_winners: Vec<bool>,
+    pub randomness_source: RandomnessSource,
+    pub oracle_address: Option<Address>,
+    pub oracle_public_key: Option<BytesN<32>>,
+    pub auto_distribute: bool,
+    pub protocol_fee_bp: u32,
+    pub treasury_address: Option<Address>,
+    pub swap_router: Option<Address>,
+    pub tikka_token: Option<Address>,
+    pub finalized_at: Option<u64>,
+    pub claim_lockup_seconds: u64,
+    pub prize_vesting_seconds: u64,
+    pub proceeds_vesting_seconds: u64,
+    pub swap_deadline_seconds: u64,
+    pub ticket_sales_paused: bool,
+    /// The percentage of max_tickets covered by the early bird discount (0 to disable).
+    pub early_bird_ticket_percentage: u32,
+    /// The discount amount specified in basis points.
+    pub early_bird_discount_bp: u32,
+    /// Discovery metadata, editable by the creator until the first ticket sells.
+    pub category: Symbol,
+    pub image_uri: String,
+    pub tags: Vec<Symbol>,
+    /// Ticket NFT contract.  When set, each ticket is minted as an NFT and
+    /// prizes go to whoever holds the winning ticket's NFT at claim time.
+    pub nft_contract: Option<Address>,
+}
+
+impl Raffle {
+    pub fn end_condition(&self) -> EndCondition {
+        EndCondition::from_end_time(self.end_time)
+    }
+
+    pub fn is_sold_out(&self) -> bool {
+        self.tickets_sold >= self.max_tickets
+    }
+
+    /// Whether ticket sales are over at `now`, by deadline or sell-out.
+    pub fn sales_closed_at(&self, now: u64) -> bool {
+        self.is_sold_out() || self.end_condition().sales_closed_at(now)
+    }
+
+    /// Whether an active raffle may be finalized at `now`: once sold out,
+    /// or once its deadline is reached.
+    pub fn finalizable_at(&self, now: u64) -> bool {
+        self.is_sold_out() || self.end_condition().deadline_reached_at(now)
+    }
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub struct Ticket {
@@ -159,6 +401,8 @@ pub struct Ticket {
     pub purchase_time: u64,
     /// Human-facing ticket number used in draw/result UX.
     pub ticket_number: u32,
+    /// Draw weight; 1 unless the raffle weights tickets by purchase order.
+    pub weight: u32,
 }
 
 /// Audit data proving how a draw outcome was derived.
@@ -179,6 +423,28 @@ pub struct FairnessData {
     pub draw_sequence: u32,
 }
 
+/// Self-contained record of a draw, sufficient to re-derive the winners.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct DrawProof {
+    /// How the seed was produced.
+    pub randomness_type: RandomnessType,
+    /// Pre-image the seed was derived from: the XDR-encoded ledger and
+    /// participant entropy (PRNG/fallback), concatenated commit hashes
+    /// (commit-reveal), the compressed beacon signature (drand) or the signed
+    /// oracle message (VRF).
+    pub seed_inputs: Bytes,
+    /// SHA-256 of `seed_inputs`.
+    pub seed_hash: BytesN<32>,
+    /// Seed fed to winner selection.  Except for VRF draws, this is the first
+    /// eight bytes of `seed_hash`, big-endian.
+    pub seed: u64,
+    /// Winning indices into the ticket list, one per prize tier.
+    pub winner_indices: Vec<u32>,
+    /// Tickets in the draw.
+    pub tickets_sold: u32,
+}
+
 /// Generic pagination request for list queries.
 #[derive(Clone)]
 #[contracttype]
@@ -201,6 +467,18 @@ pub struct PageResultRaffles {
     pub has_more: bool,
 }
 
+/// Paginated raffle ID query result.
+#[derive(Clone)]
+#[contracttype]
+pub struct PageResultRaffleIds {
+    /// Returned stable raffle IDs for the current page.
+    pub items: Vec<u32>,
+    /// Total number of raffles matching the query.
+    pub total: u32,
+    /// True when more records are available after this page.
+    pub has_more: bool,
+}
+
 /// Paginated ticket query result.
 #[derive(Clone)]
 #[contracttype]
@@ -213,6 +491,134 @@ pub struct PageResultTickets {
     pub has_more: bool,
 }
 
+/// Paginated participant query result.
+#[derive(Clone)]
+#[contracttype]
+pub struct PageResultParticipants {
+    /// Returned participant addresses for the current page, in order of
+    /// first purchase.
+    pub items: Vec<Address>,
+    /// Total number of unique participants.
+    pub total: u32,
+    /// True when more records are available after this page.
+    pub has_more: bool,
+}
+
+/// Ticket sales of a raffle within one hour.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct SalesBucket {
+    /// Hours since the Unix epoch (`timestamp / 3600`).
+    pub hour: u64,
+    /// Tickets sold during the hour.
+    pub tickets: u32,
+    /// Amount paid for those tickets, in the payment token.
+    pub revenue: i128,
+}
+
+/// What a single `claim_all` call paid out from one raffle.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[contracttype]
+pub struct ClaimSummary {
+    /// Prize tiers claimed, gross, in the prize token.  Vesting tiers are
+    /// counted here but paid out through `withdraw_vested`.
+    pub prizes: i128,
+    /// Ticket refunds paid, in the payment token.
+    pub refunds: i128,
+}
+
+/// What an instance's `collect_all` moved to the factory for one owner, so
+/// the factory can pay each token out once across raffles.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ClaimCollection {
+    pub summary: ClaimSummary,
+    pub prize_token: Address,
+    /// Prizes transferred to the factory.  Vesting tiers stay with the
+    /// raffle and are not included.
+    pub prize_payout: i128,
+    pub payment_token: Address,
+    /// Tier indexes claimed, vesting or not.
+    pub claimed_tiers: Vec<u32>,
+}
+
+/// Sales summary of a single raffle.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RaffleStats {
+    /// Current lifecycle status.
+    pub status: RaffleStatus,
+    /// Tickets sold so far.
+    pub tickets_sold: u32,
+    /// Ticket cap of the raffle.
+    pub max_tickets: u32,
+    /// Distinct addresses that have bought at least one ticket.
+    pub unique_participants: u32,
+    /// Tips paid to the creator with ticket purchases.
+    pub total_tips: i128,
+}
+
+/// Where a raffle is on its timeline, as reported by `get_lifecycle`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum LifecyclePhase {
+    /// Still being edited by its creator.
+    Draft = 0,
+    /// Published, waiting for the creator to deposit the prize.
+    AwaitingPrize = 1,
+    /// Tickets are on sale.
+    Selling = 2,
+    /// Sales have closed; anyone may finalize before the grace deadline.
+    AwaitingFinalization = 3,
+    /// The grace deadline passed unfinalized; anyone may expire the raffle.
+    Expirable = 4,
+    /// Waiting for randomness.
+    Drawing = 5,
+    /// Drawn; claims open after the claim lockup and dispute window.
+    ClaimLocked = 6,
+    /// Winners may claim.
+    Claimable = 7,
+    /// The claim deadline passed; the creator may withdraw unclaimed prizes.
+    ClaimDeadlinePassed = 8,
+    /// Claimed, cancelled or failed.  Nothing is left to happen.
+    Closed = 9,
+}
+
+/// A raffle's timing rules resolved against the current ledger time.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RaffleLifecycle {
+    pub phase: LifecyclePhase,
+    pub end_condition: EndCondition,
+    /// Ledger timestamp the figures were computed at.
+    pub now: u64,
+    /// Seconds of ticket sales left, `0` once sales closed.  `None` for
+    /// raffles without a deadline.
+    pub sale_time_remaining: Option<u64>,
+    /// After this, anyone may expire the raffle if it is still unfinalized.
+    /// `None` for raffles without a deadline.
+    pub grace_deadline: Option<u64>,
+    /// When winners may start claiming.  `None` until the raffle is drawn.
+    pub claims_open_at: Option<u64>,
+    /// After this, the creator may withdraw unclaimed prizes.  `None` until
+    /// the raffle is drawn.
+    pub claim_deadline: Option<u64>,
+}
+
+/// One raffle's full state and a page of its tickets, for indexer
+/// backfills.
+#[derive(Clone)]
+#[contracttype]
+pub struct RaffleExport {
+    pub raffle: Raffle,
+    pub stats: RaffleStats,
+    /// Winning ticket of each drawn tier, aligned with `raffle.winners`.
+    /// Empty until the raffle is drawn.
+    pub winning_tickets: Vec<Ticket>,
+    /// Tickets in ID order, starting after the requested offset.
+    pub tickets: PageResultTickets,
+}
+
 /// Administrative operations that can be timelocked or proposed.
 #[derive(Clone)]
 #[contracttype]
@@ -221,14 +627,26 @@ pub enum AdminOp {
     SetConfig(u32, Address),
     /// Rotate target contract WASM hash for upgrades.
     UpdateWasmHash(BytesN<32>),
+    /// Emergency withdrawal of `(token, recipient, amount)` held by the factory.
+    RescueTokens(Address, Address, i128),
+    /// Change the timelock delay (seconds) applied to later operations.
+    SetTimelockDelay(u64),
+    /// Replace the factory's own WASM with the uploaded hash.
+    UpgradeFactory(BytesN<32>),
 }
 
-/// Default page size when callers request zero items.
-pub const DEFAULT_PAGE_LIMIT: u32 = 100;
-/// Hard maximum page size accepted by query helpers.
-pub const MAX_PAGE_LIMIT: u32 = 200;
-pub const DEFAULT_CLAIM_LOCKUP_SECONDS: u64 = 3_600;
-pub const DEFAULT_SWAP_DEADLINE_SECONDS: u64 = 300;
+pub use constants::{
+    DEFAULT_CLAIM_LOCKUP_SECONDS, DEFAULT_PAGE_LIMIT, DEFAULT_SWAP_DEADLINE_SECONDS, MAX_PAGE_LIMIT,
+};
+
+/// Whether `token` is the native XLM Stellar Asset Contract.  The native
+/// SAC reports the name `native` and `NATIVE_DECIMALS` decimals; anything
+/// that fails either call is not it.
+pub fn is_native_sac(env: &soroban_sdk::Env, token: &Address) -> bool {
+    let client = soroban_sdk::token::TokenClient::new(env, token);
+    matches!(client.try_name(), Ok(Ok(name)) if name == String::from_str(env, "native"))
+        && matches!(client.try_decimals(), Ok(Ok(constants::NATIVE_DECIMALS)))
+}
 
 /// Returns a safe pagination limit clamped to supported bounds.
 pub fn effective_limit(requested: u32) -> u32 {
@@ -280,13 +698,55 @@ pub trait RandomnessReceiverTrait {
 /// * `recipient`  – the address that receives the NFT (the ticket buyer).
 /// * `ticket_id`  – the unique ticket ID within this raffle (1-indexed, u32).
 /// * `raffle_id`  – the raffle instance contract address, used as a namespace
-///                  so a single NFT contract can serve multiple raffles.
+///   so a single NFT contract can serve multiple raffles.
 #[soroban_sdk::contractclient(name = "NftTicketClient")]
 pub trait NftTicketTrait {
-    fn mint(
-        env: soroban_sdk::Env,
-        recipient: Address,
-        ticket_id: u32,
-        raffle_id: Address,
-    );
+    fn mint(env: soroban_sdk::Env, recipient: Address, ticket_id: u32, raffle_id: Address);
+
+    /// Current holder of `ticket_id` in `raffle_id`'s namespace, if minted.
+    fn owner_of(env: soroban_sdk::Env, raffle_id: Address, ticket_id: u32) -> Option<Address>;
+}
+
+/// Cross-contract interface for a prize yield strategy.
+///
+/// A raffle-instance configured with a strategy transfers its prize to the
+/// strategy once deposited, then calls `deposit` to have it credited.  At
+/// finalization or cancellation it calls `withdraw_all` and treats whatever
+/// comes back, more or less than the principal, as the prize pot.
+#[soroban_sdk::contractclient(name = "YieldStrategyClient")]
+pub trait YieldStrategyTrait {
+    /// Credits `amount` of `token`, already transferred to the strategy, to
+    /// `depositor`.
+    fn deposit(env: soroban_sdk::Env, depositor: Address, token: Address, amount: i128);
+
+    /// Sends `depositor` everything it holds in `token`, principal and
+    /// yield, and returns the amount sent.
+    fn withdraw_all(env: soroban_sdk::Env, depositor: Address, token: Address) -> i128;
+}
+
+/// Asset identifier of the SEP-40 price feed interface.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Asset {
+    /// A Stellar asset contract.
+    Stellar(Address),
+    /// An off-chain asset or unit, e.g. `USD`.
+    Other(Symbol),
+}
+
+/// Price record of the SEP-40 price feed interface, scaled by the oracle's
+/// `decimals`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct PriceData {
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+/// The subset of the SEP-40 price feed interface raffles read when a ticket
+/// price is pegged to a reference unit.
+#[soroban_sdk::contractclient(name = "PriceOracleClient")]
+pub trait PriceOracleTrait {
+    /// Most recent price of `asset` in the oracle's base unit, if any.
+    fn lastprice(env: soroban_sdk::Env, asset: Asset) -> Option<PriceData>;
 }
@@ -0,0 +1,74 @@
+//! Proof-of-participation badges.
+//!
+//! Once a raffle is drawn, each participant may mint one badge recording
+//! how many tickets they held and whether they won.  Badges are kept per
+//! user in the factory and cannot be transferred, so they can back loyalty
+//! programs and social proof without off-chain infrastructure.
+
+use soroban_sdk::{Address, Env, IntoVal, Symbol, Vec};
+
+use raffle_shared::{RaffleStats, RaffleStatus};
+
+use crate::{events, DataKey, Error, ParticipationBadge, RaffleRecord};
+
+pub(crate) fn badges(env: &Env, user: &Address) -> Vec<ParticipationBadge> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::UserBadges(user.clone()))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+pub(crate) fn mint(env: &Env, user: Address, raffle_id: u32) -> Result<ParticipationBadge, Error> {
+    let record: RaffleRecord = env
+        .storage()
+        .persistent()
+        .get(&DataKey::RaffleRecord(raffle_id))
+        .ok_or(Error::RaffleNotFound)?;
+    let mut minted = badges(env, &user);
+    if minted.iter().any(|badge| badge.raffle_id == raffle_id) {
+        return Err(Error::BadgeAlreadyMinted);
+    }
+
+    let stats: RaffleStats = env.invoke_contract(
+        &record.address,
+        &Symbol::new(env, "get_stats"),
+        Vec::new(env),
+    );
+    if stats.status != RaffleStatus::Finalized && stats.status != RaffleStatus::Claimed {
+        return Err(Error::RaffleNotEligible);
+    }
+    let tickets: Vec<u32> = env.invoke_contract(
+        &record.address,
+        &Symbol::new(env, "get_my_tickets"),
+        (user.clone(),).into_val(env),
+    );
+    if tickets.is_empty() {
+        return Err(Error::NotAuthorized);
+    }
+    let won = env
+        .storage()
+        .persistent()
+        .get::<_, Vec<Address>>(&DataKey::RaffleWinners(raffle_id))
+        .is_some_and(|winners| winners.contains(&user));
+
+    let badge = ParticipationBadge {
+        raffle_id,
+        tickets_held: tickets.len(),
+        won,
+        minted_at: env.ledger().timestamp(),
+    };
+    minted.push_back(badge.clone());
+    env.storage()
+        .persistent()
+        .set(&DataKey::UserBadges(user.clone()), &minted);
+
+    events::ParticipationBadgeMinted {
+        user,
+        raffle_id,
+        tickets_held: badge.tickets_held,
+        won,
+        timestamp: badge.minted_at,
+    }
+    .publish(env);
+    Ok(badge)
+}
@@ -0,0 +1,127 @@
+//! Internal user balances.
+//!
+//! Users may park tokens in the factory once and spend them across many
+//! raffles.  Registered instances debit a buyer's balance in place of a
+//! token transfer from the buyer; the factory then pays the instance.  The
+//! total held for users is tracked per token so admin rescues can never
+//! touch it.
+
+use soroban_sdk::{token, Address, Env};
+
+use crate::{events, DataKey, Error};
+
+pub(crate) fn balance_of(env: &Env, user: &Address, token: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Balance(user.clone(), token.clone()))
+        .unwrap_or(0)
+}
+
+/// Sum of every user's balance in `token`.
+pub(crate) fn total_deposits(env: &Env, token: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::TotalDeposits(token.clone()))
+        .unwrap_or(0)
+}
+
+fn adjust(env: &Env, user: &Address, token: &Address, delta: i128) -> Result<i128, Error> {
+    let balance = balance_of(env, user, token)
+        .checked_add(delta)
+        .ok_or(Error::ArithmeticOverflow)?;
+    if balance < 0 {
+        return Err(Error::InsufficientBalance);
+    }
+    let total = total_deposits(env, token)
+        .checked_add(delta)
+        .ok_or(Error::ArithmeticOverflow)?;
+
+    let key = DataKey::Balance(user.clone(), token.clone());
+    if balance == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &balance);
+    }
+    env.storage()
+        .persistent()
+        .set(&DataKey::TotalDeposits(token.clone()), &total);
+    Ok(balance)
+}
+
+pub(crate) fn deposit(
+    env: &Env,
+    user: Address,
+    token: Address,
+    amount: i128,
+) -> Result<i128, Error> {
+    if amount <= 0 {
+        return Err(Error::InvalidParameters);
+    }
+    let _ = token::Client::new(env, &token)
+        .try_transfer(&user, env.current_contract_address(), &amount)
+        .map_err(|_| Error::InvalidParameters)?;
+    let balance = adjust(env, &user, &token, amount)?;
+
+    events::BalanceDeposited {
+        user,
+        token,
+        amount,
+        balance,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+    Ok(balance)
+}
+
+pub(crate) fn withdraw(
+    env: &Env,
+    user: Address,
+    token: Address,
+    amount: i128,
+) -> Result<i128, Error> {
+    if amount <= 0 {
+        return Err(Error::InvalidParameters);
+    }
+    let balance = adjust(env, &user, &token, -amount)?;
+    let _ = token::Client::new(env, &token)
+        .try_transfer(&env.current_contract_address(), &user, &amount)
+        .map_err(|_| Error::InvalidParameters)?;
+
+    events::BalanceWithdrawn {
+        user,
+        token,
+        amount,
+        balance,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+    Ok(balance)
+}
+
+/// Moves `amount` of `user`'s balance to the registered raffle `raffle`.
+pub(crate) fn debit(
+    env: &Env,
+    raffle: Address,
+    user: Address,
+    token: Address,
+    amount: i128,
+) -> Result<(), Error> {
+    if amount <= 0 {
+        return Err(Error::InvalidParameters);
+    }
+    let balance = adjust(env, &user, &token, -amount)?;
+    let _ = token::Client::new(env, &token)
+        .try_transfer(&env.current_contract_address(), &raffle, &amount)
+        .map_err(|_| Error::InvalidParameters)?;
+
+    events::BalanceDebited {
+        user,
+        raffle,
+        token,
+        amount,
+        balance,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+    Ok(())
+}
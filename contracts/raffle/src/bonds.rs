@@ -0,0 +1,120 @@
+//! Creation bonds.
+//!
+//! When the admin sets a `CreationBond`, every creator posts it with the
+//! factory when their raffle is deployed.  The factory holds it until the
+//! raffle ends: a raffle that is drawn returns the bond to its creator, one
+//! that is cancelled or fails forfeits it to the treasury.  Bonds are
+//! settled from the instance's status report, or with `settle_bond` when
+//! the report could not reach the factory.  The total held per token is
+//! tracked so admin rescues can never touch it.
+
+use soroban_sdk::{token, Address, Env};
+
+use raffle_shared::RaffleStatus;
+
+use crate::{events, CreationFee, DataKey, Error};
+
+/// Sum of the bonds held in `token`.
+pub(crate) fn total_held(env: &Env, token: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::BondsHeld(token.clone()))
+        .unwrap_or(0)
+}
+
+fn adjust_held(env: &Env, token: &Address, delta: i128) -> Result<(), Error> {
+    let total = total_held(env, token)
+        .checked_add(delta)
+        .ok_or(Error::ArithmeticOverflow)?;
+    env.storage()
+        .persistent()
+        .set(&DataKey::BondsHeld(token.clone()), &total);
+    Ok(())
+}
+
+/// Takes the configured bond, if any, from `creator` for raffle `raffle_id`.
+pub(crate) fn post(env: &Env, raffle_id: u32, creator: &Address) -> Result<(), Error> {
+    let Some(bond) = env
+        .storage()
+        .persistent()
+        .get::<_, CreationFee>(&DataKey::CreationBond)
+    else {
+        return Ok(());
+    };
+
+    let _ = token::Client::new(env, &bond.token)
+        .try_transfer(creator, env.current_contract_address(), &bond.amount)
+        .map_err(|_| Error::InvalidParameters)?;
+    adjust_held(env, &bond.token, bond.amount)?;
+    env.storage()
+        .persistent()
+        .set(&DataKey::RaffleBond(raffle_id), &bond);
+
+    events::CreationBondPosted {
+        raffle_id,
+        creator: creator.clone(),
+        token: bond.token,
+        amount: bond.amount,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+    Ok(())
+}
+
+/// Returns the bond of raffle `raffle_id` to `creator` once the raffle is
+/// drawn, or forfeits it to the treasury once it is cancelled or fails.
+/// Returns the amount settled, `0` when there is no bond or the raffle has
+/// not ended.
+pub(crate) fn settle(
+    env: &Env,
+    raffle_id: u32,
+    creator: &Address,
+    status: &RaffleStatus,
+) -> Result<i128, Error> {
+    let released = match status {
+        RaffleStatus::Finalized | RaffleStatus::Claimed => true,
+        RaffleStatus::Cancelled | RaffleStatus::Failed => false,
+        _ => return Ok(0),
+    };
+    let key = DataKey::RaffleBond(raffle_id);
+    let Some(bond) = env.storage().persistent().get::<_, CreationFee>(&key) else {
+        return Ok(0);
+    };
+    let recipient = if released {
+        creator.clone()
+    } else {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Treasury)
+            .ok_or(Error::TreasuryNotSet)?
+    };
+
+    let _ = token::Client::new(env, &bond.token)
+        .try_transfer(&env.current_contract_address(), &recipient, &bond.amount)
+        .map_err(|_| Error::InvalidParameters)?;
+    adjust_held(env, &bond.token, -bond.amount)?;
+    env.storage().persistent().remove(&key);
+
+    let timestamp = env.ledger().timestamp();
+    if released {
+        events::CreationBondReleased {
+            raffle_id,
+            creator: recipient,
+            token: bond.token,
+            amount: bond.amount,
+            timestamp,
+        }
+        .publish(env);
+    } else {
+        events::CreationBondForfeited {
+            raffle_id,
+            creator: creator.clone(),
+            treasury: recipient,
+            token: bond.token,
+            amount: bond.amount,
+            timestamp,
+        }
+        .publish(env);
+    }
+    Ok(bond.amount)
+}
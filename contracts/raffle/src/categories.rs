@@ -0,0 +1,91 @@
+//! Per-category raffle index.
+//!
+//! Each category keeps a count and one storage entry per slot, and each
+//! raffle remembers its category and slot, so filing, moving and dropping a
+//! raffle touch a fixed number of entries however large the category grows.
+//! Dropping a raffle moves the category's last raffle into the freed slot,
+//! so pages follow slot order, not creation order.
+
+use soroban_sdk::{Env, Symbol, Vec};
+
+use raffle_shared::{effective_limit, PageResultRaffleIds};
+
+use crate::DataKey;
+
+fn count(env: &Env, category: &Symbol) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::CategoryCount(category.clone()))
+        .unwrap_or(0)
+}
+
+/// Category `raffle_id` is filed under, if any.
+pub(crate) fn category_of(env: &Env, raffle_id: u32) -> Option<Symbol> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::RaffleCategory(raffle_id))
+}
+
+/// Files `raffle_id` under `category`, moving it out of its current
+/// category first.
+pub(crate) fn index(env: &Env, raffle_id: u32, category: Symbol) {
+    if category_of(env, raffle_id).as_ref() == Some(&category) {
+        return;
+    }
+    remove(env, raffle_id);
+
+    let slot = count(env, &category);
+    let storage = env.storage().persistent();
+    storage.set(&DataKey::CategoryEntry(category.clone(), slot), &raffle_id);
+    storage.set(&DataKey::CategoryCount(category.clone()), &(slot + 1));
+    storage.set(&DataKey::CategorySlot(raffle_id), &slot);
+    storage.set(&DataKey::RaffleCategory(raffle_id), &category);
+}
+
+/// Drops `raffle_id` from its category, if it is filed.
+pub(crate) fn remove(env: &Env, raffle_id: u32) {
+    let Some(category) = category_of(env, raffle_id) else {
+        return;
+    };
+    let storage = env.storage().persistent();
+    let slot: u32 = storage.get(&DataKey::CategorySlot(raffle_id)).unwrap_or(0);
+    let last = count(env, &category).saturating_sub(1);
+
+    if slot != last {
+        if let Some(moved) = storage.get::<_, u32>(&DataKey::CategoryEntry(category.clone(), last))
+        {
+            storage.set(&DataKey::CategoryEntry(category.clone(), slot), &moved);
+            storage.set(&DataKey::CategorySlot(moved), &slot);
+        }
+    }
+    storage.remove(&DataKey::CategoryEntry(category.clone(), last));
+    if last == 0 {
+        storage.remove(&DataKey::CategoryCount(category));
+    } else {
+        storage.set(&DataKey::CategoryCount(category), &last);
+    }
+    storage.remove(&DataKey::CategorySlot(raffle_id));
+    storage.remove(&DataKey::RaffleCategory(raffle_id));
+}
+
+/// A page of the raffle IDs filed under `category`.  `limit` is clamped by
+/// `effective_limit`.
+pub(crate) fn page(env: &Env, category: Symbol, offset: u32, limit: u32) -> PageResultRaffleIds {
+    let total = count(env, &category);
+    let end = offset.saturating_add(effective_limit(limit)).min(total);
+    let mut items = Vec::new(env);
+    for slot in offset..end {
+        if let Some(raffle_id) = env
+            .storage()
+            .persistent()
+            .get(&DataKey::CategoryEntry(category.clone(), slot))
+        {
+            items.push_back(raffle_id);
+        }
+    }
+    PageResultRaffleIds {
+        items,
+        total,
+        has_more: end < total,
+    }
+}
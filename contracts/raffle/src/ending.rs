@@ -0,0 +1,122 @@
+//! "Ending soon" index.
+//!
+//! Raffles with a deadline are filed in hour-wide buckets by `end_time`, at
+//! creation and whenever their instance reports a new end time, so
+//! `get_raffles_ending_within` only reads the buckets its window covers.
+//! Raffles leave the index when they reach a terminal status.
+
+use soroban_sdk::{Env, Vec};
+
+use raffle_shared::constants::{END_TIME_BUCKET_SECONDS, MAX_ENDING_WITHIN_SECONDS};
+use raffle_shared::{effective_limit, PageResultRaffleIds, RaffleStatus};
+
+use crate::{DataKey, RaffleRecord};
+
+fn bucket(end_time: u64) -> u64 {
+    end_time / END_TIME_BUCKET_SECONDS
+}
+
+fn bucket_ids(env: &Env, bucket: u64) -> Vec<u32> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::EndTimeBucket(bucket))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+/// Drops `raffle_id` from the index, if it is filed.
+pub(crate) fn remove(env: &Env, raffle_id: u32) {
+    let Some(end_time) = env
+        .storage()
+        .persistent()
+        .get::<_, u64>(&DataKey::RaffleEndTime(raffle_id))
+    else {
+        return;
+    };
+    env.storage()
+        .persistent()
+        .remove(&DataKey::RaffleEndTime(raffle_id));
+
+    let key = DataKey::EndTimeBucket(bucket(end_time));
+    let mut ids = bucket_ids(env, bucket(end_time));
+    if let Some(index) = ids.first_index_of(raffle_id) {
+        ids.remove(index);
+    }
+    if ids.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &ids);
+    }
+}
+
+/// Files `raffle_id` under `end_time`, replacing any earlier entry.  `None`
+/// (no deadline) leaves it out of the index.
+pub(crate) fn index(env: &Env, raffle_id: u32, end_time: Option<u64>) {
+    remove(env, raffle_id);
+    let Some(end_time) = end_time else {
+        return;
+    };
+
+    let mut ids = bucket_ids(env, bucket(end_time));
+    ids.push_back(raffle_id);
+    env.storage()
+        .persistent()
+        .set(&DataKey::EndTimeBucket(bucket(end_time)), &ids);
+    env.storage()
+        .persistent()
+        .set(&DataKey::RaffleEndTime(raffle_id), &end_time);
+}
+
+/// Active raffles whose sales close within `seconds` from now, soonest
+/// first.  The window is capped at `MAX_ENDING_WITHIN_SECONDS`.
+pub(crate) fn ending_within(
+    env: &Env,
+    seconds: u64,
+    offset: u32,
+    limit: u32,
+) -> PageResultRaffleIds {
+    let now = env.ledger().timestamp();
+    let until = now.saturating_add(seconds.min(MAX_ENDING_WITHIN_SECONDS));
+
+    // (end_time, raffle_id), kept sorted by end time.
+    let mut matches: Vec<(u64, u32)> = Vec::new(env);
+    for bucket in bucket(now)..=bucket(until) {
+        for raffle_id in bucket_ids(env, bucket).iter() {
+            let Some(end_time) = env
+                .storage()
+                .persistent()
+                .get::<_, u64>(&DataKey::RaffleEndTime(raffle_id))
+            else {
+                continue;
+            };
+            if end_time < now || end_time > until {
+                continue;
+            }
+            let active = env
+                .storage()
+                .persistent()
+                .get::<_, RaffleRecord>(&DataKey::RaffleRecord(raffle_id))
+                .is_some_and(|record| record.status == RaffleStatus::Active);
+            if !active {
+                continue;
+            }
+
+            let mut at = matches.len();
+            while at > 0 && matches.get_unchecked(at - 1).0 > end_time {
+                at -= 1;
+            }
+            matches.insert(at, (end_time, raffle_id));
+        }
+    }
+
+    let total = matches.len();
+    let end = offset.saturating_add(effective_limit(limit)).min(total);
+    let mut items = Vec::new(env);
+    for i in offset..end {
+        items.push_back(matches.get_unchecked(i).1);
+    }
+    PageResultRaffleIds {
+        items,
+        total,
+        has_more: end < total,
+    }
+}
@@ -1,19 +1,21 @@
+pub use raffle_shared::events::{ContractPaused, ContractUnpaused};
 use raffle_shared::AdminOp;
-use soroban_sdk::{contractevent, u128, Address, BytesN};
 use soroban_sdk::{contractevent, Address, BytesN};
 
-#[allow(dead_code)]
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle_factory", "raffle_instance_deployed"])]
 pub struct RaffleInstanceDeployed {
     pub instance: Address,
+    pub raffle_id: u32,
     pub wasm_hash: BytesN<32>,
     pub creator: Address,
+    /// Whether the creator held a verification attestation at deploy time.
+    pub is_verified: bool,
     pub timestamp: u64,
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle_factory", "factory_initialized"])]
 pub struct FactoryInitialized {
     pub admin: Address,
     pub protocol_fee_bp: u32,
@@ -21,9 +23,34 @@ pub struct FactoryInitialized {
     pub timestamp: u64,
 }
 
+/// The protocol fee and treasury were changed by an executed `SetConfig` op.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "config_updated"])]
+pub struct ConfigUpdated {
+    pub old_protocol_fee_bp: u32,
+    pub new_protocol_fee_bp: u32,
+    pub old_treasury: Option<Address>,
+    pub new_treasury: Address,
+    #[topic]
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
+/// Raffles deployed from now on use `new_wasm_hash`; existing instances are
+/// unaffected.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "wasm_hash_updated"])]
+pub struct WasmHashUpdated {
+    pub old_wasm_hash: Option<BytesN<32>>,
+    pub new_wasm_hash: BytesN<32>,
+    #[topic]
+    pub updated_by: Address,
+    pub timestamp: u64,
+}
+
 #[derive(Clone)]
-#[contractevent]
-pub struct AdminOpProposed {
+#[contractevent(topics = ["raffle_factory", "action_queued"])]
+pub struct ActionQueued {
     pub op_id: u32,
     pub op: AdminOp,
     pub effective_timestamp: u64,
@@ -31,8 +58,8 @@ pub struct AdminOpProposed {
 }
 
 #[derive(Clone)]
-#[contractevent]
-pub struct AdminOpExecuted {
+#[contractevent(topics = ["raffle_factory", "action_executed"])]
+pub struct ActionExecuted {
     pub op_id: u32,
     pub op: AdminOp,
     pub executed_by: Address,
@@ -41,7 +68,7 @@ pub struct AdminOpExecuted {
 
 #[allow(dead_code)]
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle_factory", "treasury_changed"])]
 pub struct TreasuryChanged {
     pub old_treasury: Address,
     pub new_treasury: Address,
@@ -51,29 +78,29 @@ pub struct TreasuryChanged {
 }
 
 #[derive(Clone)]
-#[contractevent]
-pub struct AdminOpCancelled {
+#[contractevent(topics = ["raffle_factory", "action_cancelled"])]
+pub struct ActionCancelled {
     pub op_id: u32,
     pub cancelled_by: Address,
     pub cancelled_at: u64,
 }
 
 #[derive(Clone)]
-#[contractevent]
-pub struct ContractPaused {
+#[contractevent(topics = ["raffle_factory", "new_raffles_paused"])]
+pub struct NewRafflesPaused {
     pub paused_by: Address,
     pub timestamp: u64,
 }
 
 #[derive(Clone)]
-#[contractevent]
-pub struct ContractUnpaused {
-    pub unpaused_by: Address,
+#[contractevent(topics = ["raffle_factory", "new_raffles_resumed"])]
+pub struct NewRafflesResumed {
+    pub resumed_by: Address,
     pub timestamp: u64,
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle_factory", "admin_transfer_proposed"])]
 pub struct AdminTransferProposed {
     pub current_admin: Address,
     pub proposed_admin: Address,
@@ -81,7 +108,7 @@ pub struct AdminTransferProposed {
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle_factory", "admin_transfer_accepted"])]
 pub struct AdminTransferAccepted {
     pub old_admin: Address,
     pub new_admin: Address,
@@ -90,7 +117,7 @@ pub struct AdminTransferAccepted {
 
 #[allow(dead_code)]
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle_factory", "admin_transfer_failed"])]
 pub struct AdminTransferFailed {
     pub current_admin: Address,
     pub proposed_admin: Address,
@@ -99,7 +126,7 @@ pub struct AdminTransferFailed {
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle_factory", "checkpoint_created"])]
 pub struct CheckpointCreated {
     pub index: u32,
     pub raffle_count: u32,
@@ -109,7 +136,7 @@ pub struct CheckpointCreated {
 
 #[allow(dead_code)]
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle_factory", "supported_sac_updated"])]
 pub struct SupportedSacUpdated {
     pub token: Address,
     pub supported: bool,
@@ -118,7 +145,7 @@ pub struct SupportedSacUpdated {
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle_factory", "raffle_cleaned_up"])]
 pub struct RaffleCleanedUp {
     pub raffle_address: Address,
     pub cleaned_by: Address,
@@ -127,15 +154,63 @@ pub struct RaffleCleanedUp {
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle_factory", "creation_rate_limited"])]
 pub struct CreationRateLimited {
     pub creator: Address,
     pub unlock_timestamp: u64,
     pub timestamp: u64,
 }
 
+/// Emitted when a creator pays the free-entry raffle creation fee.
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle_factory", "creation_fee_paid"])]
+pub struct CreationFeePaid {
+    #[topic]
+    pub creator: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+/// Emitted when a creator posts the creation bond for a new raffle.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "creation_bond_posted"])]
+pub struct CreationBondPosted {
+    pub raffle_id: u32,
+    #[topic]
+    pub creator: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+/// A drawn raffle's creation bond was returned to its creator.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "creation_bond_released"])]
+pub struct CreationBondReleased {
+    pub raffle_id: u32,
+    #[topic]
+    pub creator: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+/// A cancelled or failed raffle's creation bond was paid to the treasury.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "creation_bond_forfeited"])]
+pub struct CreationBondForfeited {
+    pub raffle_id: u32,
+    #[topic]
+    pub creator: Address,
+    pub treasury: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "factory_tokens_rescued"])]
 pub struct FactoryTokensRescued {
     pub rescued_by: Address,
     pub token: Address,
@@ -145,9 +220,166 @@ pub struct FactoryTokensRescued {
 }
 
 #[derive(Clone)]
-#[contractevent]
+#[contractevent(topics = ["raffle_factory", "factory_upgraded"])]
 pub struct FactoryUpgraded {
     pub admin: Address,
     pub new_wasm_hash: BytesN<32>,
     pub timestamp: u64,
 }
+
+/// A raffle's instance was closed and the raffle archived.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "raffle_archived"])]
+pub struct RaffleArchived {
+    #[topic]
+    pub instance: Address,
+    pub raffle_id: u32,
+    pub timestamp: u64,
+}
+
+/// An instance was moved to `new_wasm_hash` by `upgrade_instances`.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "instance_upgraded"])]
+pub struct InstanceUpgraded {
+    #[topic]
+    pub instance: Address,
+    pub raffle_id: u32,
+    pub old_wasm_hash: Option<BytesN<32>>,
+    pub new_wasm_hash: BytesN<32>,
+    pub timestamp: u64,
+}
+
+/// An instance rejected the upgrade attempted by `upgrade_instances` and
+/// still runs its old code.
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "instance_upgrade_failed"])]
+pub struct InstanceUpgradeFailed {
+    #[topic]
+    pub instance: Address,
+    pub raffle_id: u32,
+    pub new_wasm_hash: BytesN<32>,
+    pub timestamp: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "raffle_featured"])]
+pub struct RaffleFeatured {
+    pub raffle_address: Address,
+    pub featured_by: Address,
+    pub timestamp: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "raffle_unfeatured"])]
+pub struct RaffleUnfeatured {
+    pub raffle_address: Address,
+    pub unfeatured_by: Address,
+    pub timestamp: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "creator_verified"])]
+pub struct CreatorVerified {
+    pub creator: Address,
+    pub metadata_hash: BytesN<32>,
+    pub verified_by: Address,
+    pub timestamp: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "creator_verification_revoked"])]
+pub struct CreatorVerificationRevoked {
+    pub creator: Address,
+    pub revoked_by: Address,
+    pub timestamp: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "address_blocked"])]
+pub struct AddressBlocked {
+    pub address: Address,
+    pub blocked_by: Address,
+    pub timestamp: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "address_unblocked"])]
+pub struct AddressUnblocked {
+    pub address: Address,
+    pub unblocked_by: Address,
+    pub timestamp: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "oracle_registered"])]
+pub struct OracleRegistered {
+    pub oracle: Address,
+    pub registered_by: Address,
+    pub timestamp: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "oracle_removed"])]
+pub struct OracleRemoved {
+    pub oracle: Address,
+    pub removed_by: Address,
+    pub timestamp: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "balance_deposited"])]
+pub struct BalanceDeposited {
+    #[topic]
+    pub user: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub balance: i128,
+    pub timestamp: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "balance_withdrawn"])]
+pub struct BalanceWithdrawn {
+    #[topic]
+    pub user: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub balance: i128,
+    pub timestamp: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "balance_debited"])]
+pub struct BalanceDebited {
+    #[topic]
+    pub user: Address,
+    pub raffle: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub balance: i128,
+    pub timestamp: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "subscription_updated"])]
+pub struct SubscriptionUpdated {
+    #[topic]
+    pub user: Address,
+    #[topic]
+    pub creator: Address,
+    pub tickets_per_round: u32,
+    /// Zero once the subscription has ended or been cancelled.
+    pub rounds_left: u32,
+    pub timestamp: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["raffle_factory", "badge_minted"])]
+pub struct ParticipationBadgeMinted {
+    #[topic]
+    pub user: Address,
+    pub raffle_id: u32,
+    pub tickets_held: u32,
+    pub won: bool,
+    pub timestamp: u64,
+}
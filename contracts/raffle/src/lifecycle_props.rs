@@ -0,0 +1,262 @@
+//! Property-based tests of the raffle lifecycle.
+//!
+//! Each case deploys a raffle through the factory, then applies a random
+//! sequence of deposits, purchases, clock moves, finalizations,
+//! cancellations, claims and refunds.  Calls are expected to fail often;
+//! what is checked is that no sequence, successful or not, breaks the
+//! invariants in `check_invariants`.
+
+extern crate std;
+
+use proptest::prelude::*;
+use raffle_instance::ContractClient as RaffleInstanceClient;
+use raffle_shared::{CancelReason, RaffleConfig, RaffleStatus, RandomnessSource};
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::token::{StellarAssetClient, TokenClient};
+use soroban_sdk::{Address, BytesN, Env, String, Vec as SdkVec};
+
+use crate::{RaffleFactory, RaffleFactoryClient};
+
+const BUYERS: usize = 3;
+const MAX_TICKETS: u32 = 8;
+const TICKET_PRICE: i128 = 10_000;
+const PRIZE: i128 = TICKET_PRICE * 5;
+const PROTOCOL_FEE_BP: u32 = 500;
+const STARTING_BALANCE: i128 = 1_000_000;
+
+#[derive(Clone, Debug)]
+enum Action {
+    Deposit,
+    Buy { buyer: usize, quantity: u32 },
+    Advance(u64),
+    Finalize,
+    Cancel,
+    Claim { buyer: usize },
+    Refund { buyer: usize },
+    RefundPrize,
+}
+
+fn action() -> impl Strategy<Value = Action> {
+    prop_oneof![
+        2 => Just(Action::Deposit),
+        6 => (0..BUYERS, 1..=4u32)
+            .prop_map(|(buyer, quantity)| Action::Buy { buyer, quantity }),
+        2 => (1..=4_000u64).prop_map(Action::Advance),
+        2 => Just(Action::Finalize),
+        1 => Just(Action::Cancel),
+        2 => (0..BUYERS).prop_map(|buyer| Action::Claim { buyer }),
+        2 => (0..BUYERS).prop_map(|buyer| Action::Refund { buyer }),
+        1 => Just(Action::RefundPrize),
+    ]
+}
+
+/// What the harness has observed leaving and entering each account.
+#[derive(Default)]
+struct Ledgered {
+    spent: [i128; BUYERS],
+    refunded: [i128; BUYERS],
+    refunded_tickets: std::vec::Vec<u32>,
+    prizes_paid: u32,
+}
+
+struct World<'a> {
+    env: &'a Env,
+    factory: RaffleFactoryClient<'a>,
+    raffle: RaffleInstanceClient<'a>,
+    token: TokenClient<'a>,
+    creator: Address,
+    treasury: Address,
+    buyers: std::vec::Vec<Address>,
+}
+
+impl<'a> World<'a> {
+    fn new(env: &'a Env) -> Self {
+        env.mock_all_auths();
+        env.ledger().set_timestamp(1_000);
+
+        let factory = RaffleFactoryClient::new(env, &env.register(RaffleFactory, ()));
+        let treasury = Address::generate(env);
+        factory.init_factory(
+            &Address::generate(env),
+            &BytesN::from_array(env, &[0u8; 32]),
+            &PROTOCOL_FEE_BP,
+            &treasury,
+        );
+        factory.set_creation_delay(&0u64);
+
+        let token_address = env
+            .register_stellar_asset_contract_v2(Address::generate(env))
+            .address();
+        let mint = StellarAssetClient::new(env, &token_address);
+        let creator = Address::generate(env);
+        mint.mint(&creator, &STARTING_BALANCE);
+        let buyers: std::vec::Vec<Address> = (0..BUYERS)
+            .map(|_| {
+                let buyer = Address::generate(env);
+                mint.mint(&buyer, &STARTING_BALANCE);
+                buyer
+            })
+            .collect();
+
+        let config = RaffleConfig {
+            description: String::from_str(env, "Lifecycle property"),
+            end_time: 3_000,
+            no_deadline: false,
+            max_tickets: MAX_TICKETS,
+            max_tickets_per_tx: MAX_TICKETS,
+            min_tickets: 2,
+            allow_multiple: true,
+            ticket_price: TICKET_PRICE,
+            payment_token: token_address.clone(),
+            prize_amount: PRIZE,
+            prizes: SdkVec::from_array(env, [10_000u32]),
+            randomness_source: RandomnessSource::Internal,
+            oracle_address: None,
+            oracle_public_key: None,
+            auto_distribute: false,
+            protocol_fee_bp: 0,
+            treasury_address: None,
+            swap_router: None,
+            tikka_token: None,
+            metadata_hash: BytesN::from_array(env, &[1u8; 32]),
+            claim_lockup_seconds: 0,
+            prize_vesting_seconds: 0,
+            proceeds_vesting_seconds: 0,
+            swap_deadline_seconds: 0,
+            early_bird_ticket_percentage: 0,
+            early_bird_discount_bp: 0,
+            nft_contract: None,
+            category: None,
+        };
+        let raffle = RaffleInstanceClient::new(env, &factory.create_raffle(&creator, &config));
+
+        World {
+            env,
+            factory,
+            raffle,
+            token: TokenClient::new(env, &token_address),
+            creator,
+            treasury,
+            buyers,
+        }
+    }
+
+    fn holders(&self) -> std::vec::Vec<Address> {
+        let mut holders = self.buyers.clone();
+        holders.push(self.creator.clone());
+        holders.push(self.treasury.clone());
+        holders.push(self.raffle.address.clone());
+        holders.push(self.factory.address.clone());
+        holders
+    }
+
+    fn apply(&self, action: &Action, seen: &mut Ledgered) {
+        match *action {
+            Action::Deposit => {
+                let _ = self.raffle.try_deposit_prize();
+            }
+            Action::Buy { buyer, quantity } => {
+                let who = &self.buyers[buyer];
+                let before = self.token.balance(who);
+                if let Ok(Ok(_)) = self.raffle.try_buy_tickets(who, &quantity, &0) {
+                    seen.spent[buyer] += before - self.token.balance(who);
+                }
+            }
+            Action::Advance(seconds) => {
+                self.env.ledger().with_mut(|l| {
+                    l.timestamp += seconds;
+                    l.sequence_number += 1;
+                });
+            }
+            Action::Finalize => {
+                let _ = self.raffle.try_finalize_raffle();
+            }
+            Action::Cancel => {
+                let _ = self
+                    .raffle
+                    .try_cancel_raffle(&CancelReason::CreatorCancelled);
+            }
+            Action::Claim { buyer } => {
+                let who = &self.buyers[buyer];
+                if let Ok(Ok(amount)) = self.raffle.try_claim_prize(who, &0) {
+                    assert!(amount > 0 && amount <= PRIZE, "claim paid {amount}");
+                    seen.prizes_paid += 1;
+                }
+            }
+            Action::Refund { buyer } => {
+                let who = &self.buyers[buyer];
+                for ticket_id in self.raffle.get_my_tickets(who).iter() {
+                    if let Ok(Ok(amount)) = self.raffle.try_refund_ticket(&ticket_id) {
+                        assert!(
+                            !seen.refunded_tickets.contains(&ticket_id),
+                            "ticket {ticket_id} refunded twice"
+                        );
+                        seen.refunded_tickets.push(ticket_id);
+                        seen.refunded[buyer] += amount;
+                    }
+                }
+            }
+            Action::RefundPrize => {
+                let _ = self.raffle.try_refund_prize();
+            }
+        }
+    }
+
+    fn check_invariants(&self, seen: &Ledgered) {
+        // Tokens only move between the accounts taking part.
+        let mut total = 0i128;
+        for holder in self.holders() {
+            let balance = self.token.balance(&holder);
+            assert!(balance >= 0, "negative balance");
+            total += balance;
+        }
+        assert_eq!(total, STARTING_BALANCE * (BUYERS as i128 + 1));
+
+        let raffle = self.raffle.get_raffle();
+        assert!(raffle.tickets_sold <= raffle.max_tickets);
+
+        // The single prize tier is paid at most once, and only once drawn.
+        assert!(seen.prizes_paid <= 1);
+        if seen.prizes_paid == 1 {
+            assert!(matches!(
+                raffle.status,
+                RaffleStatus::Finalized | RaffleStatus::Claimed
+            ));
+        }
+
+        // Refunds never return more than a buyer paid, and only follow a
+        // cancelled or failed raffle.
+        for buyer in 0..BUYERS {
+            assert!(seen.refunded[buyer] <= seen.spent[buyer]);
+        }
+        if !seen.refunded_tickets.is_empty() {
+            assert!(matches!(
+                raffle.status,
+                RaffleStatus::Cancelled | RaffleStatus::Failed
+            ));
+            assert_eq!(seen.prizes_paid, 0);
+        }
+
+        // The factory keeps pointing at the same instance throughout.
+        assert_eq!(
+            self.factory.get_raffle_by_id(&0),
+            Some(self.raffle.address.clone())
+        );
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn lifecycle_preserves_invariants(actions in prop::collection::vec(action(), 1..40)) {
+        let env = Env::default();
+        let world = World::new(&env);
+        let mut seen = Ledgered::default();
+
+        for action in actions.iter() {
+            world.apply(action, &mut seen);
+            world.check_invariants(&seen);
+        }
+    }
+}
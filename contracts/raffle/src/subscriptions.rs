@@ -0,0 +1,127 @@
+//! Auto-entry subscriptions.
+//!
+//! A user subscribes to a creator for a number of rounds; every raffle the
+//! creator launches is a round.  When the raffle opens, anyone may call its
+//! `process_subscriptions`, which enters each subscriber and pays through
+//! `debit_subscription` from their internal balance (see `balances`).
+
+use soroban_sdk::{Address, Env, Vec};
+
+use raffle_shared::constants::MAX_SUBSCRIBERS_PER_CREATOR;
+
+use crate::{balances, events, DataKey, Error, RaffleRecord, Subscription};
+
+fn subscribers(env: &Env, creator: &Address) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Subscribers(creator.clone()))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+pub(crate) fn subscription(env: &Env, user: &Address, creator: &Address) -> Option<Subscription> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Subscription(user.clone(), creator.clone()))
+}
+
+fn write(env: &Env, user: &Address, creator: &Address, subscription: &Subscription) {
+    let key = DataKey::Subscription(user.clone(), creator.clone());
+    if subscription.rounds_left == 0 {
+        env.storage().persistent().remove(&key);
+        let mut list = subscribers(env, creator);
+        if let Some(index) = list.first_index_of(user) {
+            list.remove(index);
+            env.storage()
+                .persistent()
+                .set(&DataKey::Subscribers(creator.clone()), &list);
+        }
+    } else {
+        env.storage().persistent().set(&key, subscription);
+    }
+
+    events::SubscriptionUpdated {
+        user: user.clone(),
+        creator: creator.clone(),
+        tickets_per_round: subscription.tickets_per_round,
+        rounds_left: subscription.rounds_left,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+}
+
+pub(crate) fn subscribe(
+    env: &Env,
+    user: Address,
+    creator: Address,
+    tickets_per_round: u32,
+    max_rounds: u32,
+) -> Result<(), Error> {
+    if tickets_per_round == 0 || max_rounds == 0 || user == creator {
+        return Err(Error::InvalidParameters);
+    }
+    let mut list = subscribers(env, &creator);
+    if !list.contains(&user) {
+        if list.len() >= MAX_SUBSCRIBERS_PER_CREATOR {
+            return Err(Error::InvalidParameters);
+        }
+        list.push_back(user.clone());
+        env.storage()
+            .persistent()
+            .set(&DataKey::Subscribers(creator.clone()), &list);
+    }
+    write(
+        env,
+        &user,
+        &creator,
+        &Subscription {
+            tickets_per_round,
+            rounds_left: max_rounds,
+        },
+    );
+    Ok(())
+}
+
+pub(crate) fn unsubscribe(env: &Env, user: Address, creator: Address) -> Result<(), Error> {
+    let mut current = subscription(env, &user, &creator).ok_or(Error::InvalidParameters)?;
+    current.rounds_left = 0;
+    write(env, &user, &creator, &current);
+    Ok(())
+}
+
+/// Subscribers of `creator` with rounds left, and their tickets per round.
+pub(crate) fn active(env: &Env, creator: &Address) -> Vec<(Address, u32)> {
+    let mut out = Vec::new(env);
+    for user in subscribers(env, creator).iter() {
+        if let Some(current) = subscription(env, &user, creator) {
+            out.push_back((user, current.tickets_per_round));
+        }
+    }
+    out
+}
+
+/// Pays `raffle` from `user`'s balance for one round of their subscription
+/// to the raffle's creator.
+pub(crate) fn debit(
+    env: &Env,
+    raffle: Address,
+    user: Address,
+    token: Address,
+    amount: i128,
+) -> Result<(), Error> {
+    let raffle_id: u32 = env
+        .storage()
+        .persistent()
+        .get(&DataKey::RaffleIdByAddress(raffle.clone()))
+        .ok_or(Error::NotAuthorized)?;
+    let record: RaffleRecord = env
+        .storage()
+        .persistent()
+        .get(&DataKey::RaffleRecord(raffle_id))
+        .ok_or(Error::RaffleNotFound)?;
+    let mut current = subscription(env, &user, &record.creator).ok_or(Error::NotAuthorized)?;
+
+    balances::debit(env, raffle, user.clone(), token, amount)?;
+    current.rounds_left -= 1;
+    write(env, &user, &record.creator, &current);
+    Ok(())
+}
@@ -0,0 +1,96 @@
+//! Test helpers for contracts and dApps that integrate with the factory.
+//!
+//! Enable the `testutils` feature in a dev-dependency to use them:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! raffle-factory = { path = "...", features = ["testutils"] }
+//! ```
+//!
+//! With the feature on, the factory registers the raffles it creates
+//! natively, as it does in its own tests, so no instance WASM is needed.
+//! The lifecycle and event helpers of `raffle_instance::testutils` are
+//! re-exported and work on raffles created here.
+
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::token::StellarAssetClient;
+use soroban_sdk::{Address, BytesN, Env};
+
+use raffle_instance::ContractClient as RaffleInstanceClient;
+use raffle_shared::RaffleConfig;
+
+pub use raffle_instance::testutils::{
+    decode_events, default_config, events_named, fast_forward_and_finalize, fast_forward_to_claims,
+    mint_and_buy, DecodedEvent,
+};
+
+use crate::{RaffleFactory, RaffleFactoryClient};
+
+/// An initialized factory and the parties around it.
+pub struct FactoryTestEnv {
+    pub env: Env,
+    pub factory: RaffleFactoryClient<'static>,
+    pub admin: Address,
+    pub treasury: Address,
+    /// Payment token, a Stellar Asset Contract the helpers can mint.
+    pub token: Address,
+}
+
+/// A raffle created through `FactoryTestEnv::create_raffle`.
+pub struct FactoryRaffle {
+    pub raffle: RaffleInstanceClient<'static>,
+    pub creator: Address,
+}
+
+/// A factory charging `protocol_fee_bp`, with no creation delay.  Mocks all
+/// auths.
+pub fn setup_factory_env(env: &Env, protocol_fee_bp: u32) -> FactoryTestEnv {
+    env.mock_all_auths();
+
+    let factory = RaffleFactoryClient::new(env, &env.register(RaffleFactory, ()));
+    let admin = Address::generate(env);
+    let treasury = Address::generate(env);
+    factory.init_factory(
+        &admin,
+        &BytesN::from_array(env, &[0u8; 32]),
+        &protocol_fee_bp,
+        &treasury,
+    );
+    factory.set_creation_delay(&0u64);
+
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+
+    FactoryTestEnv {
+        env: env.clone(),
+        factory,
+        admin,
+        treasury,
+        token,
+    }
+}
+
+/// A factory with no protocol fee and one active raffle on
+/// `default_config`, prize deposited.
+pub fn setup_raffle_env(env: &Env) -> (FactoryTestEnv, FactoryRaffle) {
+    let protocol = setup_factory_env(env, 0);
+    let raffle = protocol.create_raffle(|_| {});
+    (protocol, raffle)
+}
+
+impl FactoryTestEnv {
+    /// Creates a raffle on `default_config`, with `configure` applied, for a
+    /// new creator and deposits its prize.
+    pub fn create_raffle(&self, configure: impl FnOnce(&mut RaffleConfig)) -> FactoryRaffle {
+        let env = &self.env;
+        let creator = Address::generate(env);
+        let mut config = default_config(env, &self.token);
+        configure(&mut config);
+        StellarAssetClient::new(env, &self.token).mint(&creator, &config.prize_amount);
+
+        let raffle = RaffleInstanceClient::new(env, &self.factory.create_raffle(&creator, &config));
+        raffle.deposit_prize();
+        FactoryRaffle { raffle, creator }
+    }
+}
@@ -0,0 +1,210 @@
+//! Timelocked admin actions.
+//!
+//! Fee and treasury changes, instance WASM-hash rotations, factory upgrades,
+//! token rescues and changes to the delay itself are queued as `AdminOp`s
+//! and only applied once the configured delay has elapsed, giving users time
+//! to react.
+
+use soroban_sdk::{token, Address, BytesN, Env};
+
+use raffle_shared::constants::{
+    MAX_PROTOCOL_FEE_BP, MIN_TIMELOCK_DELAY_SECONDS, TIMELOCK_DELAY_SECONDS,
+};
+use raffle_shared::AdminOp;
+
+use crate::{
+    balances, bonds, checked_increment, events, require_valid_role_address, DataKey, Error,
+    PendingOp,
+};
+
+pub(crate) fn delay(env: &Env) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::TimelockDelay)
+        .unwrap_or(TIMELOCK_DELAY_SECONDS)
+}
+
+/// Rejects operations that could never be executed successfully.
+fn validate(env: &Env, op: &AdminOp) -> Result<(), Error> {
+    match op {
+        AdminOp::SetConfig(protocol_fee_bp, treasury) => {
+            if *protocol_fee_bp > MAX_PROTOCOL_FEE_BP {
+                return Err(Error::InvalidFee);
+            }
+            require_valid_role_address(env, treasury)
+        }
+        AdminOp::UpdateWasmHash(_) | AdminOp::UpgradeFactory(_) => Ok(()),
+        AdminOp::RescueTokens(_, _, amount) => {
+            if *amount <= 0 {
+                return Err(Error::InvalidParameters);
+            }
+            Ok(())
+        }
+        AdminOp::SetTimelockDelay(seconds) => {
+            if *seconds < MIN_TIMELOCK_DELAY_SECONDS {
+                return Err(Error::InvalidParameters);
+            }
+            Ok(())
+        }
+    }
+}
+
+pub(crate) fn queue(env: &Env, admin: Address, op: AdminOp) -> Result<u32, Error> {
+    validate(env, &op)?;
+
+    let op_id = checked_increment(
+        env.storage()
+            .persistent()
+            .get::<_, u32>(&DataKey::OpCounter)
+            .unwrap_or(0),
+    )?;
+    env.storage().persistent().set(&DataKey::OpCounter, &op_id);
+
+    let effective_timestamp = env.ledger().timestamp() + delay(env);
+    let pending = PendingOp {
+        op: op.clone(),
+        effective_timestamp,
+        proposed_by: admin.clone(),
+    };
+    env.storage()
+        .persistent()
+        .set(&DataKey::PendingOp(op_id), &pending);
+
+    events::ActionQueued {
+        op_id,
+        op,
+        effective_timestamp,
+        proposed_by: admin,
+    }
+    .publish(env);
+
+    Ok(op_id)
+}
+
+pub(crate) fn execute(env: &Env, admin: Address, op_id: u32) -> Result<(), Error> {
+    let pending: PendingOp = env
+        .storage()
+        .persistent()
+        .get(&DataKey::PendingOp(op_id))
+        .ok_or(Error::NoPendingOp)?;
+
+    if env.ledger().timestamp() < pending.effective_timestamp {
+        return Err(Error::TimelockNotElapsed);
+    }
+
+    // Re-validate: the world may have changed while the op was queued.
+    validate(env, &pending.op)?;
+    env.storage()
+        .persistent()
+        .remove(&DataKey::PendingOp(op_id));
+
+    match pending.op.clone() {
+        AdminOp::SetConfig(protocol_fee_bp, treasury) => {
+            let old_protocol_fee_bp: u32 = env
+                .storage()
+                .persistent()
+                .get(&DataKey::ProtocolFeeBP)
+                .unwrap_or(0);
+            let old_treasury: Option<Address> = env.storage().persistent().get(&DataKey::Treasury);
+            env.storage()
+                .persistent()
+                .set(&DataKey::ProtocolFeeBP, &protocol_fee_bp);
+            env.storage()
+                .persistent()
+                .set(&DataKey::Treasury, &treasury);
+
+            events::ConfigUpdated {
+                old_protocol_fee_bp,
+                new_protocol_fee_bp: protocol_fee_bp,
+                old_treasury,
+                new_treasury: treasury,
+                updated_by: admin.clone(),
+                timestamp: env.ledger().timestamp(),
+            }
+            .publish(env);
+        }
+        AdminOp::UpdateWasmHash(new_hash) => {
+            let old_wasm_hash: Option<BytesN<32>> =
+                env.storage().persistent().get(&DataKey::InstanceWasmHash);
+            env.storage()
+                .persistent()
+                .set(&DataKey::InstanceWasmHash, &new_hash);
+
+            events::WasmHashUpdated {
+                old_wasm_hash,
+                new_wasm_hash: new_hash,
+                updated_by: admin.clone(),
+                timestamp: env.ledger().timestamp(),
+            }
+            .publish(env);
+        }
+        AdminOp::RescueTokens(token, recipient, amount) => {
+            // User deposits and creation bonds are never rescuable.
+            let held = token::Client::new(env, &token)
+                .try_balance(&env.current_contract_address())
+                .map_err(|_| Error::InvalidParameters)?
+                .map_err(|_| Error::InvalidParameters)?;
+            let reserved = balances::total_deposits(env, &token) + bonds::total_held(env, &token);
+            if amount > held - reserved {
+                return Err(Error::InvalidParameters);
+            }
+            let _ = token::Client::new(env, &token)
+                .try_transfer(&env.current_contract_address(), &recipient, &amount)
+                .map_err(|_| Error::InvalidParameters)?;
+
+            events::FactoryTokensRescued {
+                rescued_by: admin.clone(),
+                token,
+                recipient,
+                amount,
+                timestamp: env.ledger().timestamp(),
+            }
+            .publish(env);
+        }
+        AdminOp::SetTimelockDelay(seconds) => {
+            env.storage()
+                .persistent()
+                .set(&DataKey::TimelockDelay, &seconds);
+        }
+        AdminOp::UpgradeFactory(new_wasm_hash) => {
+            env.deployer()
+                .update_current_contract_wasm(new_wasm_hash.clone());
+
+            events::FactoryUpgraded {
+                admin: admin.clone(),
+                new_wasm_hash,
+                timestamp: env.ledger().timestamp(),
+            }
+            .publish(env);
+        }
+    }
+
+    events::ActionExecuted {
+        op_id,
+        op: pending.op,
+        executed_by: admin,
+        executed_at: env.ledger().timestamp(),
+    }
+    .publish(env);
+
+    Ok(())
+}
+
+pub(crate) fn cancel(env: &Env, admin: Address, op_id: u32) -> Result<(), Error> {
+    if !env.storage().persistent().has(&DataKey::PendingOp(op_id)) {
+        return Err(Error::NoPendingOp);
+    }
+
+    env.storage()
+        .persistent()
+        .remove(&DataKey::PendingOp(op_id));
+
+    events::ActionCancelled {
+        op_id,
+        cancelled_by: admin,
+        cancelled_at: env.ledger().timestamp(),
+    }
+    .publish(env);
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+//! Instance version index.
+//!
+//! Every raffle is filed under the WASM hash its instance runs, from
+//! deployment until it is cleaned up, so operators can list the instances
+//! still on an old build with `get_instances_by_version`.  Raffles deployed
+//! before the index existed are not filed.
+
+use soroban_sdk::{Address, BytesN, Env, Vec};
+
+use crate::DataKey;
+
+fn filed_ids(env: &Env, wasm_hash: &BytesN<32>) -> Vec<u32> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::InstancesByWasmHash(wasm_hash.clone()))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+/// The WASM hash `raffle_id` is filed under, if any.
+pub(crate) fn of(env: &Env, raffle_id: u32) -> Option<BytesN<32>> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::RaffleWasmHash(raffle_id))
+}
+
+/// Drops `raffle_id` from the index, if it is filed.
+pub(crate) fn remove(env: &Env, raffle_id: u32) {
+    let Some(wasm_hash) = of(env, raffle_id) else {
+        return;
+    };
+    env.storage()
+        .persistent()
+        .remove(&DataKey::RaffleWasmHash(raffle_id));
+
+    let key = DataKey::InstancesByWasmHash(wasm_hash.clone());
+    let mut ids = filed_ids(env, &wasm_hash);
+    if let Some(index) = ids.first_index_of(raffle_id) {
+        ids.remove(index);
+    }
+    if ids.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &ids);
+    }
+}
+
+/// Files `raffle_id` under `wasm_hash`, replacing any earlier entry.
+pub(crate) fn index(env: &Env, raffle_id: u32, wasm_hash: &BytesN<32>) {
+    remove(env, raffle_id);
+    let mut ids = filed_ids(env, wasm_hash);
+    ids.push_back(raffle_id);
+    env.storage()
+        .persistent()
+        .set(&DataKey::InstancesByWasmHash(wasm_hash.clone()), &ids);
+    env.storage()
+        .persistent()
+        .set(&DataKey::RaffleWasmHash(raffle_id), wasm_hash);
+}
+
+/// Addresses of the instances filed under `wasm_hash`, oldest first.
+pub(crate) fn instances(env: &Env, wasm_hash: &BytesN<32>) -> Vec<Address> {
+    let mut addresses = Vec::new(env);
+    for raffle_id in filed_ids(env, wasm_hash).iter() {
+        if let Some(address) = env
+            .storage()
+            .persistent()
+            .get::<_, Address>(&DataKey::RaffleById(raffle_id))
+        {
+            addresses.push_back(address);
+        }
+    }
+    addresses
+}
@@ -0,0 +1,422 @@
+#![no_std]
+
+//! Fee-sharing staking for the Tikka protocol.
+//!
+//! Holders stake the protocol token and earn a share of the protocol fees
+//! the treasury pushes in with `deposit_fees`.  Fees collect for the current
+//! epoch and are shared out pro rata to the stake held when the epoch is
+//! closed with `advance_epoch`, so staking just before a deposit earns
+//! nothing until the epoch ends.  Unstaking starts a cooldown during which
+//! the tokens earn nothing and cannot be withdrawn.
+
+use soroban_sdk::{
+    contract, contracterror, contractevent, contractimpl, contracttype, token, Address, Env,
+};
+
+/// Fixed-point scale of the reward-per-token accumulator.
+const ACC_SCALE: i128 = 1_000_000_000_000;
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Config,
+    /// Current epoch number, starting at 0.
+    Epoch,
+    /// Timestamp the current epoch started.
+    EpochStart,
+    /// Fees deposited during the current epoch, not yet shared out.
+    PendingFees,
+    /// Reward per staked unit, scaled by `ACC_SCALE`.
+    AccRewardPerToken,
+    TotalStaked,
+    Stake(Address),
+    Unbonding(Address),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StakingConfig {
+    pub admin: Address,
+    pub stake_token: Address,
+    /// Token the protocol fees are paid in.
+    pub reward_token: Address,
+    pub epoch_seconds: u64,
+    pub cooldown_seconds: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StakeInfo {
+    pub amount: i128,
+    /// Accumulator value `amount` has already been credited up to.
+    pub reward_debt: i128,
+    /// Rewards credited but not yet claimed.
+    pub unclaimed: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Unbonding {
+    pub amount: i128,
+    pub unlock_at: u64,
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    InvalidAmount = 3,
+    InsufficientStake = 4,
+    EpochNotOver = 5,
+    CooldownActive = 6,
+    NothingToWithdraw = 7,
+    ArithmeticOverflow = 8,
+    InvalidParameters = 9,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["staking", "staked"])]
+pub struct Staked {
+    #[topic]
+    pub staker: Address,
+    pub amount: i128,
+    pub total_staked: i128,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["staking", "unstake_requested"])]
+pub struct UnstakeRequested {
+    #[topic]
+    pub staker: Address,
+    pub amount: i128,
+    pub unlock_at: u64,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["staking", "withdrawn"])]
+pub struct Withdrawn {
+    #[topic]
+    pub staker: Address,
+    pub amount: i128,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["staking", "fees_deposited"])]
+pub struct FeesDeposited {
+    #[topic]
+    pub epoch: u32,
+    pub from: Address,
+    pub amount: i128,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["staking", "epoch_advanced"])]
+pub struct EpochAdvanced {
+    #[topic]
+    pub epoch: u32,
+    /// Fees shared out for the closed epoch; 0 when nothing was staked and
+    /// the fees roll over.
+    pub distributed: i128,
+    pub total_staked: i128,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["staking", "reward_claimed"])]
+pub struct RewardClaimed {
+    #[topic]
+    pub staker: Address,
+    pub amount: i128,
+}
+
+fn config(env: &Env) -> Result<StakingConfig, Error> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Config)
+        .ok_or(Error::NotInitialized)
+}
+
+fn read_i128(env: &Env, key: &DataKey) -> i128 {
+    env.storage().instance().get(key).unwrap_or(0)
+}
+
+fn stake_of(env: &Env, staker: &Address) -> StakeInfo {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Stake(staker.clone()))
+        .unwrap_or_default()
+}
+
+/// Credits `stake` with everything earned since it was last settled.
+fn settle(env: &Env, stake: &mut StakeInfo) -> Result<(), Error> {
+    let acc = read_i128(env, &DataKey::AccRewardPerToken);
+    let earned = stake
+        .amount
+        .checked_mul(acc - stake.reward_debt)
+        .ok_or(Error::ArithmeticOverflow)?
+        / ACC_SCALE;
+    stake.unclaimed = stake
+        .unclaimed
+        .checked_add(earned)
+        .ok_or(Error::ArithmeticOverflow)?;
+    stake.reward_debt = acc;
+    Ok(())
+}
+
+fn write_stake(env: &Env, staker: &Address, stake: &StakeInfo) {
+    let key = DataKey::Stake(staker.clone());
+    if stake.amount == 0 && stake.unclaimed == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, stake);
+    }
+}
+
+#[contract]
+pub struct FeeStaking;
+
+#[contractimpl]
+impl FeeStaking {
+    pub fn init(env: Env, config: StakingConfig) -> Result<(), Error> {
+        if env.storage().instance().has(&DataKey::Config) {
+            return Err(Error::AlreadyInitialized);
+        }
+        if config.epoch_seconds == 0 {
+            return Err(Error::InvalidParameters);
+        }
+        env.storage().instance().set(&DataKey::Config, &config);
+        env.storage()
+            .instance()
+            .set(&DataKey::EpochStart, &env.ledger().timestamp());
+        Ok(())
+    }
+
+    pub fn get_config(env: Env) -> Result<StakingConfig, Error> {
+        config(&env)
+    }
+
+    pub fn stake(env: Env, staker: Address, amount: i128) -> Result<(), Error> {
+        staker.require_auth();
+        let config = config(&env)?;
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        token::Client::new(&env, &config.stake_token).transfer(
+            &staker,
+            env.current_contract_address(),
+            &amount,
+        );
+
+        let mut stake = stake_of(&env, &staker);
+        settle(&env, &mut stake)?;
+        stake.amount = stake
+            .amount
+            .checked_add(amount)
+            .ok_or(Error::ArithmeticOverflow)?;
+        write_stake(&env, &staker, &stake);
+        let total_staked = read_i128(&env, &DataKey::TotalStaked)
+            .checked_add(amount)
+            .ok_or(Error::ArithmeticOverflow)?;
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalStaked, &total_staked);
+
+        Staked {
+            staker,
+            amount,
+            total_staked,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Stops `amount` of the caller's stake earning and starts its
+    /// cooldown.  A further request adds to the amount unbonding and
+    /// restarts the cooldown.
+    pub fn request_unstake(env: Env, staker: Address, amount: i128) -> Result<u64, Error> {
+        staker.require_auth();
+        let config = config(&env)?;
+        let mut stake = stake_of(&env, &staker);
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if amount > stake.amount {
+            return Err(Error::InsufficientStake);
+        }
+        settle(&env, &mut stake)?;
+        stake.amount -= amount;
+        write_stake(&env, &staker, &stake);
+        env.storage().instance().set(
+            &DataKey::TotalStaked,
+            &(read_i128(&env, &DataKey::TotalStaked) - amount),
+        );
+
+        let unlock_at = env.ledger().timestamp() + config.cooldown_seconds;
+        let already: i128 = Self::get_unbonding(env.clone(), staker.clone())
+            .map(|unbonding| unbonding.amount)
+            .unwrap_or(0);
+        env.storage().persistent().set(
+            &DataKey::Unbonding(staker.clone()),
+            &Unbonding {
+                amount: already + amount,
+                unlock_at,
+            },
+        );
+
+        UnstakeRequested {
+            staker,
+            amount,
+            unlock_at,
+        }
+        .publish(&env);
+        Ok(unlock_at)
+    }
+
+    /// Returns the caller's unbonded tokens once the cooldown has passed.
+    pub fn withdraw(env: Env, staker: Address) -> Result<i128, Error> {
+        staker.require_auth();
+        let config = config(&env)?;
+        let unbonding =
+            Self::get_unbonding(env.clone(), staker.clone()).ok_or(Error::NothingToWithdraw)?;
+        if env.ledger().timestamp() < unbonding.unlock_at {
+            return Err(Error::CooldownActive);
+        }
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Unbonding(staker.clone()));
+        token::Client::new(&env, &config.stake_token).transfer(
+            &env.current_contract_address(),
+            &staker,
+            &unbonding.amount,
+        );
+
+        Withdrawn {
+            staker,
+            amount: unbonding.amount,
+        }
+        .publish(&env);
+        Ok(unbonding.amount)
+    }
+
+    /// Pushes `amount` of protocol fees in the reward token into the
+    /// current epoch.  Normally called by the treasury.
+    pub fn deposit_fees(env: Env, from: Address, amount: i128) -> Result<(), Error> {
+        from.require_auth();
+        let config = config(&env)?;
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        token::Client::new(&env, &config.reward_token).transfer(
+            &from,
+            env.current_contract_address(),
+            &amount,
+        );
+        let pending = read_i128(&env, &DataKey::PendingFees)
+            .checked_add(amount)
+            .ok_or(Error::ArithmeticOverflow)?;
+        env.storage()
+            .instance()
+            .set(&DataKey::PendingFees, &pending);
+
+        FeesDeposited {
+            epoch: Self::get_epoch(env.clone()),
+            from,
+            amount,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Closes the current epoch once it has run its length, sharing its
+    /// fees among current stakers.  Permissionless.
+    pub fn advance_epoch(env: Env) -> Result<u32, Error> {
+        let config = config(&env)?;
+        let started: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EpochStart)
+            .unwrap_or(0);
+        let now = env.ledger().timestamp();
+        if now < started + config.epoch_seconds {
+            return Err(Error::EpochNotOver);
+        }
+
+        let epoch = Self::get_epoch(env.clone());
+        let total_staked = read_i128(&env, &DataKey::TotalStaked);
+        let pending = read_i128(&env, &DataKey::PendingFees);
+        let mut distributed = 0;
+        if total_staked > 0 && pending > 0 {
+            let increment = pending
+                .checked_mul(ACC_SCALE)
+                .ok_or(Error::ArithmeticOverflow)?
+                / total_staked;
+            let acc = read_i128(&env, &DataKey::AccRewardPerToken)
+                .checked_add(increment)
+                .ok_or(Error::ArithmeticOverflow)?;
+            env.storage()
+                .instance()
+                .set(&DataKey::AccRewardPerToken, &acc);
+            // Rounding dust stays pending for the next epoch.
+            distributed = increment * total_staked / ACC_SCALE;
+            env.storage()
+                .instance()
+                .set(&DataKey::PendingFees, &(pending - distributed));
+        }
+        env.storage().instance().set(&DataKey::Epoch, &(epoch + 1));
+        env.storage().instance().set(&DataKey::EpochStart, &now);
+
+        EpochAdvanced {
+            epoch,
+            distributed,
+            total_staked,
+        }
+        .publish(&env);
+        Ok(epoch + 1)
+    }
+
+    /// Pays the caller every reward credited to their stake so far.
+    pub fn claim_rewards(env: Env, staker: Address) -> Result<i128, Error> {
+        staker.require_auth();
+        let config = config(&env)?;
+        let mut stake = stake_of(&env, &staker);
+        settle(&env, &mut stake)?;
+        let amount = stake.unclaimed;
+        stake.unclaimed = 0;
+        write_stake(&env, &staker, &stake);
+        if amount > 0 {
+            token::Client::new(&env, &config.reward_token).transfer(
+                &env.current_contract_address(),
+                &staker,
+                &amount,
+            );
+            RewardClaimed { staker, amount }.publish(&env);
+        }
+        Ok(amount)
+    }
+
+    pub fn get_stake(env: Env, staker: Address) -> StakeInfo {
+        stake_of(&env, &staker)
+    }
+
+    pub fn get_unbonding(env: Env, staker: Address) -> Option<Unbonding> {
+        env.storage().persistent().get(&DataKey::Unbonding(staker))
+    }
+
+    /// Rewards `staker` could claim now.
+    pub fn pending_rewards(env: Env, staker: Address) -> Result<i128, Error> {
+        let mut stake = stake_of(&env, &staker);
+        settle(&env, &mut stake)?;
+        Ok(stake.unclaimed)
+    }
+
+    pub fn get_epoch(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::Epoch).unwrap_or(0)
+    }
+
+    pub fn get_total_staked(env: Env) -> i128 {
+        read_i128(&env, &DataKey::TotalStaked)
+    }
+}
+
+#[cfg(test)]
+mod test;
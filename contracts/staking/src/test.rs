@@ -0,0 +1,100 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::token::StellarAssetClient;
+
+fn setup(
+    env: &Env,
+) -> (
+    FeeStakingClient<'_>,
+    StellarAssetClient<'_>,
+    StellarAssetClient<'_>,
+) {
+    let admin = Address::generate(env);
+    let stake_token = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    let reward_token = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    let client = FeeStakingClient::new(env, &env.register(FeeStaking, ()));
+    client.init(&StakingConfig {
+        admin,
+        stake_token: stake_token.clone(),
+        reward_token: reward_token.clone(),
+        epoch_seconds: 100,
+        cooldown_seconds: 50,
+    });
+    (
+        client,
+        StellarAssetClient::new(env, &stake_token),
+        StellarAssetClient::new(env, &reward_token),
+    )
+}
+
+#[test]
+fn fees_are_shared_pro_rata_per_epoch() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+    let (client, stake_mint, reward_mint) = setup(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    stake_mint.mint(&alice, &300);
+    stake_mint.mint(&bob, &100);
+    reward_mint.mint(&treasury, &1_000);
+
+    client.stake(&alice, &300);
+    client.stake(&bob, &100);
+    client.deposit_fees(&treasury, &400);
+    assert_eq!(client.pending_rewards(&alice), 0);
+    assert_eq!(client.try_advance_epoch(), Err(Ok(Error::EpochNotOver)));
+
+    env.ledger().set_timestamp(1_100);
+    assert_eq!(client.advance_epoch(), 1);
+    assert_eq!(client.pending_rewards(&alice), 300);
+    assert_eq!(client.pending_rewards(&bob), 100);
+
+    assert_eq!(client.claim_rewards(&alice), 300);
+    assert_eq!(
+        soroban_sdk::token::Client::new(&env, &reward_mint.address).balance(&alice),
+        300
+    );
+    assert_eq!(client.pending_rewards(&alice), 0);
+}
+
+#[test]
+fn unstaking_waits_out_the_cooldown_and_stops_earning() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+    let (client, stake_mint, reward_mint) = setup(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    stake_mint.mint(&alice, &100);
+    stake_mint.mint(&bob, &100);
+    reward_mint.mint(&treasury, &1_000);
+
+    client.stake(&alice, &100);
+    client.stake(&bob, &100);
+    assert_eq!(client.request_unstake(&bob, &100), 1_050);
+    assert_eq!(client.get_total_staked(), 100);
+
+    client.deposit_fees(&treasury, &200);
+    env.ledger().set_timestamp(1_100);
+    client.advance_epoch();
+    assert_eq!(client.pending_rewards(&alice), 200);
+    assert_eq!(client.pending_rewards(&bob), 0);
+
+    assert_eq!(
+        client.try_request_unstake(&alice, &101),
+        Err(Ok(Error::InsufficientStake))
+    );
+    assert_eq!(client.request_unstake(&alice, &50), 1_150);
+    assert_eq!(client.try_withdraw(&alice), Err(Ok(Error::CooldownActive)));
+    assert_eq!(client.withdraw(&bob), 100);
+    assert_eq!(client.try_withdraw(&bob), Err(Ok(Error::NothingToWithdraw)));
+}
@@ -0,0 +1,141 @@
+#![no_std]
+
+//! Ticket NFTs for Tikka raffles.
+//!
+//! A raffle instance configured with this contract as its `nft_contract`
+//! mints one token per ticket it sells, namespaced by the raffle's address.
+//! Tokens transfer freely, and the raffle pays each prize to whoever holds
+//! the winning ticket's token when the prize is claimed.
+
+use soroban_sdk::{
+    contract, contracterror, contractevent, contractimpl, contracttype, Address, Env,
+};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    /// Holder of ticket `u32` of raffle `Address`.
+    Owner(Address, u32),
+    /// Number of tokens held by an address, across raffles.
+    Balance(Address),
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub enum Error {
+    AlreadyMinted = 1,
+    NotMinted = 2,
+    NotOwner = 3,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["ticket_nft", "minted"])]
+pub struct Minted {
+    #[topic]
+    pub raffle_id: Address,
+    pub ticket_id: u32,
+    pub recipient: Address,
+}
+
+#[derive(Clone)]
+#[contractevent(topics = ["ticket_nft", "transferred"])]
+pub struct Transferred {
+    #[topic]
+    pub raffle_id: Address,
+    pub ticket_id: u32,
+    pub from: Address,
+    pub to: Address,
+}
+
+fn adjust_balance(env: &Env, owner: &Address, increase: bool) {
+    let key = DataKey::Balance(owner.clone());
+    let balance: u32 = env.storage().persistent().get(&key).unwrap_or(0);
+    let balance = if increase {
+        balance.saturating_add(1)
+    } else {
+        balance.saturating_sub(1)
+    };
+    if balance == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &balance);
+    }
+}
+
+#[contract]
+pub struct TicketNft;
+
+#[contractimpl]
+impl TicketNft {
+    /// Mints ticket `ticket_id` of `raffle_id` to `recipient`.  Only the
+    /// raffle itself may mint into its namespace.
+    pub fn mint(
+        env: Env,
+        recipient: Address,
+        ticket_id: u32,
+        raffle_id: Address,
+    ) -> Result<(), Error> {
+        raffle_id.require_auth();
+        let key = DataKey::Owner(raffle_id.clone(), ticket_id);
+        if env.storage().persistent().has(&key) {
+            return Err(Error::AlreadyMinted);
+        }
+        env.storage().persistent().set(&key, &recipient);
+        adjust_balance(&env, &recipient, true);
+
+        Minted {
+            raffle_id,
+            ticket_id,
+            recipient,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    pub fn transfer(
+        env: Env,
+        from: Address,
+        to: Address,
+        raffle_id: Address,
+        ticket_id: u32,
+    ) -> Result<(), Error> {
+        from.require_auth();
+        let key = DataKey::Owner(raffle_id.clone(), ticket_id);
+        let owner: Address = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::NotMinted)?;
+        if owner != from {
+            return Err(Error::NotOwner);
+        }
+        env.storage().persistent().set(&key, &to);
+        adjust_balance(&env, &from, false);
+        adjust_balance(&env, &to, true);
+
+        Transferred {
+            raffle_id,
+            ticket_id,
+            from,
+            to,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    pub fn owner_of(env: Env, raffle_id: Address, ticket_id: u32) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Owner(raffle_id, ticket_id))
+    }
+
+    pub fn balance(env: Env, owner: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Balance(owner))
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod test;
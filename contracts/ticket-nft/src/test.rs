@@ -0,0 +1,35 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::testutils::Address as _;
+
+#[test]
+fn tickets_are_minted_once_and_move_with_their_holder() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let client = TicketNftClient::new(&env, &env.register(TicketNft, ()));
+    let raffle = Address::generate(&env);
+    let other_raffle = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let collector = Address::generate(&env);
+
+    client.mint(&buyer, &1, &raffle);
+    client.mint(&buyer, &1, &other_raffle);
+    assert_eq!(
+        client.try_mint(&collector, &1, &raffle),
+        Err(Ok(Error::AlreadyMinted))
+    );
+    assert_eq!(client.balance(&buyer), 2);
+
+    assert_eq!(
+        client.try_transfer(&collector, &buyer, &raffle, &1),
+        Err(Ok(Error::NotOwner))
+    );
+    client.transfer(&buyer, &collector, &raffle, &1);
+    assert_eq!(client.owner_of(&raffle, &1), Some(collector.clone()));
+    assert_eq!(client.owner_of(&other_raffle, &1), Some(buyer.clone()));
+    assert_eq!(client.owner_of(&raffle, &2), None);
+    assert_eq!(client.balance(&buyer), 1);
+    assert_eq!(client.balance(&collector), 1);
+}
@@ -27,6 +27,7 @@ struct BuyTicketInput {
     /// How many tickets this buyer already holds (0 = first purchase)
     buyer_existing_count: u32,
     /// Ticket price (must be > 0 in a valid raffle; fuzzer may feed 0/neg)
+    #[allow(dead_code)]
     ticket_price: i64,
     /// Raffle end_time in ledger seconds (0 = no deadline)
     end_time: u64,
@@ -38,6 +39,7 @@ struct BuyTicketInput {
 
 #[derive(Debug, PartialEq, Eq)]
 enum BuyError {
+    #[allow(dead_code)]
     RaffleInactive, // status != Active  (not tested here; pre-condition)
     RaffleEnded,    // past end_time
     TicketsSoldOut, // tickets_sold >= max_tickets
@@ -1,7 +1,7 @@
 #!/usr/bin/env python3
 """
 Script to auto-generate error code documentation from Rust Error enum.
-This script parses the Error enum in contracts/raffle-instance/src/lib.rs
+This script parses the Error enum in contracts/raffle-shared/src/errors.rs
 and generates the markdown table for docs/ERRORS.md.
 """
 
@@ -17,7 +17,7 @@ def parse_error_enum(file_path):
     
     # Find the Error enum
     enum_match = re.search(
-        r'#\[contracterror\].*?pub enum Error \{(.*?)\}',
+        r'#\[contracterror(?:\(.*?\))?\].*?pub enum Error \{(.*?)\}',
         content,
         re.DOTALL
     )
@@ -213,7 +213,7 @@ def generate_typescript_mapping(errors):
 def main():
     # Get the repository root
     repo_root = Path(__file__).parent.parent
-    rust_file = repo_root / "contracts" / "raffle-instance" / "src" / "lib.rs"
+    rust_file = repo_root / "contracts" / "raffle-shared" / "src" / "errors.rs"
     
     if not rust_file.exists():
         print(f"Error: Rust file not found at {rust_file}")